@@ -0,0 +1,79 @@
+// Synthetic networks for the benchmarks, laid out on a grid over the Bordeaux area
+
+use nvt::nvt_config::NVTConfig;
+use nvt::nvt_models::{GTFSCache, LineMetadata, NVTModels, NetworkData, StopMetadata};
+
+/// Lines serving each stop
+const LINES_PER_STOP: usize = 3;
+
+/// `stop_count` stops about 350 m apart, each served by `LINES_PER_STOP` of `line_count` lines
+pub fn synthetic_metadata(stop_count: usize, line_count: usize) -> (Vec<StopMetadata>, Vec<LineMetadata>) {
+    let side = (stop_count as f64).sqrt().ceil() as usize;
+    let stops = (0..stop_count)
+        .map(|i| {
+            let line_refs = (0..LINES_PER_STOP)
+                .map(|k| format!("TBM:Line:{}:LOC", (i + k * 7) % line_count))
+                .collect();
+            let latitude = 44.73 + (i / side) as f64 * 0.0031;
+            let longitude = -0.74 + (i % side) as f64 * 0.0044;
+            (format!("s{}", i), format!("Stop {}", i), latitude, longitude, line_refs)
+        })
+        .collect();
+
+    let lines = (0..line_count)
+        .map(|n| {
+            let destinations = vec![("0".to_string(), format!("North {}", n)), ("1".to_string(), format!("South {}", n))];
+            (format!("TBM:Line:{}:LOC", n), format!("Line {}", n), n.to_string(), destinations)
+        })
+        .collect();
+
+    (stops, lines)
+}
+
+#[allow(dead_code)]
+pub fn synthetic_network(stop_count: usize, line_count: usize) -> NetworkData {
+    let (stops, lines) = synthetic_metadata(stop_count, line_count);
+    let (stop_to_lines, line_to_stops) = NVTModels::build_line_indices(&stops);
+    NVTModels::build_network_data(
+        stops,
+        lines,
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Default::default(),
+        Default::default(),
+        &stop_to_lines,
+        &line_to_stops,
+        &NVTConfig::default(),
+    )
+}
+
+/// `route_count` routes of `stops_per_route` stops each way, overlapping so that they share
+/// stops, with a departure every 15 minutes from 06:00 to 22:00 at every stop
+#[allow(dead_code)]
+pub fn synthetic_gtfs(route_count: usize, stops_per_route: usize) -> GTFSCache {
+    const HEADWAY_SECS: u32 = 15 * 60;
+    const SERVICE_SECS: (u32, u32) = (6 * 3600, 22 * 3600);
+
+    let stop_count = route_count * stops_per_route / 2;
+    let mut gtfs = GTFSCache::default();
+    for route in 0..route_count {
+        let route_id = route.to_string();
+        let outbound: Vec<String> = (0..stops_per_route)
+            .map(|i| format!("s{}", (route * stops_per_route / 2 + i * 3) % stop_count))
+            .collect();
+        let inbound: Vec<String> = outbound.iter().rev().cloned().collect();
+
+        for (direction_id, sequence) in [(0, outbound), (1, inbound)] {
+            for (position, stop_id) in sequence.iter().enumerate() {
+                let departures = gtfs.scheduled_departures.entry(stop_id.clone()).or_default();
+                for (trip, start) in (SERVICE_SECS.0..SERVICE_SECS.1).step_by(HEADWAY_SECS as usize).enumerate() {
+                    let trip_id = format!("{}-{}-{}", route, direction_id, trip);
+                    departures.push((route_id.clone(), direction_id, start + position as u32 * 90, String::new(), trip_id));
+                }
+            }
+            gtfs.stop_sequences.entry(route_id.clone()).or_default().insert(direction_id, sequence);
+        }
+    }
+    gtfs
+}
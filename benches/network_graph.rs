@@ -0,0 +1,33 @@
+// Stop graph construction and diameter, on the cached Bordeaux GTFS feed when there is
+// one (run nvt once to download it), otherwise on a synthetic network of similar size
+
+mod common;
+
+use chrono::NaiveDate;
+use criterion::{criterion_group, criterion_main, Criterion};
+use nvt::nvt_models::{GTFSCache, NVTModels};
+use std::hint::black_box;
+
+fn gtfs() -> GTFSCache {
+    GTFSCache::load().unwrap_or_else(|| common::synthetic_gtfs(80, 30))
+}
+
+fn network_graph(c: &mut Criterion) {
+    let gtfs = gtfs();
+    let date = NaiveDate::from_ymd_opt(2025, 3, 18).unwrap();
+    let mut group = c.benchmark_group("network_graph");
+    group.sample_size(10);
+
+    group.bench_function("compute_network_graph", |b| {
+        b.iter(|| NVTModels::compute_network_graph(black_box(&gtfs), date))
+    });
+
+    let graph = NVTModels::compute_network_graph(&gtfs, date);
+    group.bench_function("graph_diameter", |b| {
+        b.iter(|| NVTModels::graph_diameter(black_box(&graph)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, network_graph);
+criterion_main!(benches);
@@ -0,0 +1,82 @@
+// Stop/line reverse indices against the linear scans they replaced,
+// on a 1000-stop, 100-line network
+
+mod common;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use nvt::nvt_config::NVTConfig;
+use nvt::nvt_models::{NVTModels, Stop};
+use std::hint::black_box;
+
+const STOPS: usize = 1000;
+const LINES: usize = 100;
+
+fn build_network_data(c: &mut Criterion) {
+    let (stops, lines) = common::synthetic_metadata(STOPS, LINES);
+    let config = NVTConfig::default();
+
+    c.bench_function("build_network_data/1000_stops_100_lines", |b| {
+        b.iter(|| {
+            let (stop_to_lines, line_to_stops) = NVTModels::build_line_indices(&stops);
+            NVTModels::build_network_data(
+                stops.clone(),
+                lines.clone(),
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                Default::default(),
+                Default::default(),
+                &stop_to_lines,
+                &line_to_stops,
+                &config,
+            )
+        })
+    });
+}
+
+fn stops_for_every_line(c: &mut Criterion) {
+    let network = common::synthetic_network(STOPS, LINES);
+    let mut group = c.benchmark_group("stops_for_every_line");
+
+    group.bench_function("line_to_stops_index", |b| {
+        b.iter(|| {
+            network.lines
+                .iter()
+                .map(|line| NVTModels::get_stops_for_line(black_box(&line.line_ref), &network).len())
+                .sum::<usize>()
+        })
+    });
+    group.bench_function("linear_scan", |b| {
+        b.iter(|| {
+            network.lines
+                .iter()
+                .map(|line| {
+                    let line_ref = black_box(&line.line_ref);
+                    network.stops.iter().filter(|stop| stop.lines.contains(line_ref)).count()
+                })
+                .sum::<usize>()
+        })
+    });
+    group.finish();
+}
+
+fn stop_by_id(c: &mut Criterion) {
+    let network = common::synthetic_network(STOPS, LINES);
+    let ids: Vec<String> = network.stops.iter().map(|stop| stop.stop_id.clone()).collect();
+    let mut group = c.benchmark_group("stop_by_id_all_stops");
+
+    group.bench_function("stop_index", |b| {
+        b.iter(|| ids.iter().filter_map(|id| NVTModels::get_stop_by_id(black_box(id), &network)).count())
+    });
+    group.bench_function("linear_scan", |b| {
+        b.iter(|| {
+            ids.iter()
+                .filter_map(|id| network.stops.iter().find(|stop: &&Stop| stop.stop_id == *black_box(id)))
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, build_network_data, stops_for_every_line, stop_by_id);
+criterion_main!(benches);
@@ -0,0 +1,48 @@
+// Bounding-box stop lookups through the SpatialGrid against a linear scan,
+// on a 5000-stop network
+
+mod common;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use nvt::nvt_models::{NVTModels, Stop};
+use std::hint::black_box;
+
+const STOPS: usize = 5000;
+const LINES: usize = 100;
+/// Half the side of the queried box, about the area the map view shows when zoomed in
+const RADIUS_KM: f64 = 0.5;
+
+fn stops_in_bbox(c: &mut Criterion) {
+    let network = common::synthetic_network(STOPS, LINES);
+    let centre = &network.stops[STOPS / 2 + 35];
+    let (min_lat, max_lat, min_lon, max_lon) = NVTModels::bounding_box(centre.latitude, centre.longitude, RADIUS_KM);
+    let mut group = c.benchmark_group("stops_in_bbox_5000_stops");
+
+    group.bench_function("spatial_grid", |b| {
+        b.iter(|| NVTModels::get_stops_in_bbox(black_box(min_lat), max_lat, min_lon, max_lon, &network).len())
+    });
+    group.bench_function("linear_scan", |b| {
+        b.iter(|| {
+            network.stops
+                .iter()
+                .filter(|stop: &&Stop| {
+                    (black_box(min_lat)..=max_lat).contains(&stop.latitude)
+                        && (min_lon..=max_lon).contains(&stop.longitude)
+                })
+                .count()
+        })
+    });
+    group.finish();
+}
+
+fn nearest_stops(c: &mut Criterion) {
+    let network = common::synthetic_network(STOPS, LINES);
+    let centre = &network.stops[STOPS / 2 + 35];
+
+    c.bench_function("nearest_stops_5000_stops/300m", |b| {
+        b.iter(|| NVTModels::nearest_stops(black_box(centre.latitude), centre.longitude, 300.0, &network).len())
+    });
+}
+
+criterion_group!(benches, stops_in_bbox, nearest_stops);
+criterion_main!(benches);
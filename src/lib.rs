@@ -0,0 +1,309 @@
+pub mod nvt_config;
+pub mod nvt_models;
+pub mod nvt_views;
+pub mod nvt_controllers;
+pub mod nvt_favorites;
+pub mod nvt_mqtt;
+pub mod nvt_persistence;
+pub mod nvt_server;
+pub mod nvt_tui;
+
+use chrono::NaiveDate;
+use clap::Parser;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+/// Command-line arguments
+#[derive(Parser, Debug)]
+#[command(name = "nvt", version, about = "Real-time transit tracker for Bordeaux Métropole (TBM)")]
+pub struct Args {
+    /// Export stops and arrivals as Parquet files into DIRECTORY, then exit
+    #[arg(long, value_name = "DIRECTORY")]
+    pub export_parquet: Option<PathBuf>,
+
+    /// Write all stops as a GeoJSON FeatureCollection to FILE, then exit
+    #[arg(long, value_name = "FILE")]
+    pub export_stops_geojson: Option<PathBuf>,
+
+    /// Write the GPS positions of tracked vehicles as GeoJSON to FILE, then exit
+    #[arg(long, value_name = "FILE")]
+    pub export_vehicles_geojson: Option<PathBuf>,
+
+    /// Write the upcoming arrivals as CSV to FILE (only --stop's if given), then exit
+    #[arg(long, value_name = "FILE")]
+    pub csv: Option<PathBuf>,
+
+    /// Print a completion script for SHELL (bash, zsh, fish...) to stdout, then exit.
+    /// Stop IDs and line codes of the local GTFS cache are completed too.
+    #[arg(long, value_name = "SHELL", hide = true)]
+    pub generate_completions: Option<clap_complete::Shell>,
+
+    /// Delete the GTFS and SIRI-Lite caches before starting
+    #[arg(long)]
+    pub cache_clear: bool,
+
+    /// Delete only the cached GTFS feed (colors, timetables) before starting
+    #[arg(long)]
+    pub clear_gtfs_cache: bool,
+
+    /// Display stop names in this language when the GTFS feed has translations (e.g. "en")
+    #[arg(long, value_name = "LANG")]
+    pub lang: Option<String>,
+
+    /// Print the arrivals at --stop (optionally only --line) and exit instead of opening the menu.
+    /// Exit code 1 on network errors, 2 when the stop or line isn't found
+    #[arg(long, requires = "stop")]
+    pub cli: bool,
+
+    /// Print the stops of a trip with their real-time arrivals and delays, then exit.
+    /// Exit code 1 on network errors, 2 when the trip has no real-time update
+    #[arg(long, value_name = "TRIP_ID")]
+    pub trip: Option<String>,
+
+    /// Stop ID or name used by non-interactive commands such as --headway
+    #[arg(long, value_name = "STOP")]
+    pub stop: Option<String>,
+
+    /// Line code or name used by non-interactive commands such as --headway
+    #[arg(long, value_name = "LINE")]
+    pub line: Option<String>,
+
+    /// Re-print the departures at --stop (optionally only --line) every --interval seconds until Ctrl+C
+    #[arg(long, requires = "stop")]
+    pub watch: bool,
+
+    /// Show arrivals as a fixed-width departure board (auto-refresh and --watch)
+    #[arg(long)]
+    pub board: bool,
+
+    /// Only list stations or plain stops in the all-stops browser
+    #[arg(long = "type", value_enum, value_name = "TYPE")]
+    pub stop_type: Option<StopTypeFilter>,
+
+    /// Only follow night lines in --watch
+    #[arg(long, requires = "watch")]
+    pub night: bool,
+
+    /// Refresh interval of --watch
+    #[arg(long, value_name = "SECONDS", default_value_t = 30)]
+    pub interval: u64,
+
+    /// List the lines whose code or name matches PATTERN (a regex, or plain text), then exit
+    #[arg(long, value_name = "PATTERN")]
+    pub search_lines: Option<String>,
+
+    /// With --search-lines, only list circular lines
+    #[arg(long, requires = "search_lines")]
+    pub circular: bool,
+
+    /// Print the distribution of live delays on LINE_CODE, then exit
+    #[arg(long, value_name = "LINE_CODE")]
+    pub delay_stats: Option<String>,
+
+    /// Check the network data for integrity issues, then exit (status 3 if any are found)
+    #[arg(long)]
+    pub validate: bool,
+
+    /// List the stops that get no real-time data, grouped by line, then exit
+    #[arg(long)]
+    pub coverage_report: bool,
+
+    /// Print the unusually long scheduled waits at every stop of --line, then exit
+    #[arg(long, requires = "line")]
+    pub gaps: bool,
+
+    /// Print the scheduled headway for --stop (optionally only --line), then exit
+    #[arg(long, requires = "stop")]
+    pub headway: bool,
+
+    /// Plan the fastest journey from this stop (ID or name) to --to, then exit
+    #[arg(long, value_name = "STOP", requires = "to")]
+    pub from: Option<String>,
+
+    /// Destination stop (ID or name) of --from
+    #[arg(long, value_name = "STOP", requires = "from")]
+    pub to: Option<String>,
+
+    /// List the stops reachable from this stop (ID or name) within --minutes, then exit
+    #[arg(long, value_name = "FROM_STOP")]
+    pub isochrone: Option<String>,
+
+    /// Travel time budget of --isochrone
+    #[arg(long, value_name = "N", default_value_t = 30)]
+    pub minutes: u32,
+
+    /// Append observed arrivals with their delay to PATH as JSON lines
+    #[arg(long, value_name = "PATH")]
+    pub log: Option<PathBuf>,
+
+    /// Publish the arrivals of pinned stops to this MQTT broker (host[:port]) after each refresh
+    #[arg(long, value_name = "BROKER_URL")]
+    pub mqtt: Option<String>,
+
+    /// Topic prefix for --mqtt; arrivals go to PREFIX/arrivals/STOP_ID
+    #[arg(long, value_name = "PREFIX", default_value = nvt_mqtt::NVTMqttPublisher::DEFAULT_TOPIC_PREFIX, requires = "mqtt")]
+    pub mqtt_topic: String,
+
+    /// Merge the SIRI-SX alerts with the GTFS-RT ones for this session (use_siri_alerts in the config)
+    #[arg(long)]
+    pub siri_alerts: bool,
+
+    /// Print each line's on-time rate from the arrival log given with --log, then exit
+    #[arg(long, requires = "log")]
+    pub report: bool,
+
+    /// Print each line's mean ETA error from the arrival log given with --log, then exit
+    #[arg(long, requires = "log")]
+    pub accuracy_report: bool,
+
+    /// Print the historical load of --line at --stop for each hour, from the arrival log given with --log, then exit
+    #[arg(long, requires_all = ["log", "stop", "line"])]
+    pub occupancy_trend: bool,
+
+    /// Only use the last N days of the arrival log for --report and --accuracy-report
+    #[arg(long, value_name = "N")]
+    pub days: Option<u32>,
+
+    /// List stops near a coordinate (radius in metres, default 300), then exit
+    #[arg(long, value_name = "LAT,LON[,RADIUS_M]", value_parser = parse_near)]
+    pub near: Option<NearQuery>,
+
+    /// With --near, only list wheelchair accessible stops
+    #[arg(long, requires = "near")]
+    pub accessible: bool,
+
+    /// Only show arrivals FROM to TO minutes from now, e.g. `5:20`
+    #[arg(long, value_name = "FROM_MIN:TO_MIN", value_parser = parse_window)]
+    pub window: Option<(u32, u32)>,
+
+    /// Hide alerts below this GTFS-RT severity (2 info, 3 warning, 4 severe)
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub min_severity: u32,
+
+    /// Use the timetable of this day (YYYY-MM-DD) instead of today's
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub date: Option<NaiveDate>,
+
+    /// Read settings from this TOML file instead of ~/.config/tbm_nvt/config.toml
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Output format; `json` prints the departures at --stop to stdout and exits
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Print line badges as [CODE] without ANSI colors (also enabled by a non-empty NO_COLOR)
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Use the full-screen terminal interface instead of the menu
+    #[arg(long)]
+    pub tui: bool,
+
+    /// Serve live data as a JSON REST API on HOST:PORT (default 127.0.0.1:8080)
+    #[arg(long, value_name = "HOST:PORT", num_args = 0..=1, default_missing_value = nvt_server::NVTServer::DEFAULT_ADDR)]
+    pub serve: Option<SocketAddr>,
+
+    /// Serve /metrics on this port instead of the --serve port
+    #[arg(long, value_name = "PORT", requires = "serve")]
+    pub metrics_port: Option<u16>,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopTypeFilter {
+    Station,
+    Stop,
+}
+
+/// Coordinate search parsed from --near
+#[derive(Debug, Clone, Copy)]
+pub struct NearQuery {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub radius_m: f64,
+}
+
+fn parse_near(value: &str) -> Result<NearQuery, String> {
+    let parts: Vec<&str> = value.split(',').map(str::trim).collect();
+    if parts.len() < 2 || parts.len() > 3 {
+        return Err("expected LAT,LON or LAT,LON,RADIUS_M".to_string());
+    }
+
+    let number = |s: &str, what: &str| {
+        s.parse::<f64>().map_err(|_| format!("invalid {}: '{}'", what, s))
+    };
+    let latitude = number(parts[0], "latitude")?;
+    let longitude = number(parts[1], "longitude")?;
+    let radius_m = match parts.get(2) {
+        Some(r) => number(r, "radius")?,
+        None => 300.0,
+    };
+
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return Err("coordinates out of range".to_string());
+    }
+    if radius_m <= 0.0 {
+        return Err("radius must be positive".to_string());
+    }
+
+    Ok(NearQuery { latitude, longitude, radius_m })
+}
+
+fn parse_window(value: &str) -> Result<(u32, u32), String> {
+    let (from, to) = value.split_once(':').ok_or("expected FROM_MIN:TO_MIN, e.g. 5:20")?;
+    let minutes = |s: &str| {
+        s.trim().parse::<u32>().map_err(|_| format!("invalid number of minutes: '{}'", s))
+    };
+    let (from, to) = (minutes(from)?, minutes(to)?);
+
+    if from > to {
+        return Err("FROM_MIN must not be greater than TO_MIN".to_string());
+    }
+    Ok((from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_near_defaults_radius() {
+        let near = parse_near("44.8260,-0.5566").unwrap();
+        assert_eq!((near.latitude, near.longitude, near.radius_m), (44.8260, -0.5566, 300.0));
+    }
+
+    #[test]
+    fn parse_near_reads_radius() {
+        let near = parse_near(" 44.8260 , -0.5566 , 500 ").unwrap();
+        assert_eq!(near.radius_m, 500.0);
+    }
+
+    #[test]
+    fn parse_near_rejects_invalid_input() {
+        assert!(parse_near("44.8260").is_err());
+        assert!(parse_near("north,-0.5566").is_err());
+        assert!(parse_near("95,-0.5566").is_err());
+        assert!(parse_near("44.8260,-0.5566,0").is_err());
+    }
+
+    #[test]
+    fn parse_window_reads_minute_range() {
+        assert_eq!(parse_window("5:25"), Ok((5, 25)));
+        assert_eq!(parse_window(" 0 : 15 "), Ok((0, 15)));
+        assert_eq!(parse_window("10:10"), Ok((10, 10)));
+    }
+
+    #[test]
+    fn parse_window_rejects_invalid_input() {
+        assert!(parse_window("15").is_err());
+        assert!(parse_window("-5:20").is_err());
+        assert!(parse_window("5:soon").is_err());
+        assert!(parse_window("20:5").is_err());
+    }
+}
@@ -1,10 +1,11 @@
-mod nvt_models;
-mod nvt_views;
-mod nvt_controllers;
-
-use nvt_controllers::NVTControllers;
+use clap::Parser;
+use nvt::Args;
+use nvt::nvt_controllers::NVTControllers;
 
 fn main() {
+    let args = Args::parse();
+    env_logger::init();
+
     // Set up panic hook for better error messages
     std::panic::set_hook(Box::new(|panic_info| {
         eprintln!("\n{}", "═".repeat(70));
@@ -19,9 +20,19 @@ fn main() {
         eprintln!("\n{}", "═".repeat(70));
     }));
 
+    // Async runtime for network requests; the CLI blocks on it via Handle::current()
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("❌ Failed to start async runtime: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let _runtime_guard = runtime.enter();
+
     // Run the application
     match std::panic::catch_unwind(|| {
-        NVTControllers::run();
+        NVTControllers::run(&args);
     }) {
         Ok(_) => {
             // Normal exit
@@ -31,4 +42,4 @@ fn main() {
             std::process::exit(1);
         }
     }
-}
\ No newline at end of file
+}
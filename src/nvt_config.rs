@@ -0,0 +1,111 @@
+// User configuration for TBM Next Vehicle
+// Settings are read from ~/.config/tbm_nvt/config.toml (or --config PATH);
+// any field missing from the file keeps its default value.
+
+use crate::nvt_models::status;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NVTConfig {
+    pub api_key: String,
+    /// Root URL of the TBM (Mecatran) API the feeds are fetched from
+    pub api_base_url: String,
+    pub request_timeout_secs: u64,
+    /// Stops and lines older than this are fetched again
+    pub static_max_age_secs: u64,
+    /// Real-time data refresh interval in auto-refresh mode
+    pub dynamic_max_age_secs: u64,
+    pub max_arrivals_per_stop: usize,
+    /// GPS fixes older than this are considered lost vehicles
+    pub vehicle_staleness_secs: u64,
+    pub max_retry_attempts: u32,
+    /// Estimate vehicle speeds from consecutive GPS fixes during auto-refresh
+    pub show_speed: bool,
+    /// HTTP(S) proxy for all API requests; HTTP_PROXY / HTTPS_PROXY are used when unset
+    pub proxy_url: Option<String>,
+    /// Accept invalid TLS certificates, for proxies that intercept HTTPS
+    pub proxy_no_verify_ssl: bool,
+    /// Show arrivals as "in 5 min" rather than as a clock time
+    pub use_relative_time: bool,
+    /// Use the SIRI stop timetable of the monitored stop and line instead of the GTFS one
+    pub use_siri_timetable: bool,
+    /// Also fetch alerts from the SIRI-SX endpoint and merge them with the GTFS-RT ones
+    pub use_siri_alerts: bool,
+    /// Walking speed used for walks between stops in planned journeys
+    pub walking_speed_kmh: f32,
+    /// File the configuration was read from, None when using defaults
+    #[serde(skip)]
+    pub source: Option<PathBuf>,
+}
+
+impl Default for NVTConfig {
+    fn default() -> Self {
+        NVTConfig {
+            api_key: "opendata-bordeaux-metropole-flux-gtfs-rt".to_string(),
+            api_base_url: "https://bdx.mecatran.com/utw/ws".to_string(),
+            request_timeout_secs: 15,
+            static_max_age_secs: 3600,
+            dynamic_max_age_secs: 30,
+            max_arrivals_per_stop: 10,
+            vehicle_staleness_secs: 120,
+            max_retry_attempts: 3,
+            show_speed: false,
+            proxy_url: None,
+            proxy_no_verify_ssl: false,
+            use_relative_time: true,
+            use_siri_timetable: false,
+            use_siri_alerts: false,
+            walking_speed_kmh: 4.5,
+            source: None,
+        }
+    }
+}
+
+impl NVTConfig {
+    pub fn config_path() -> PathBuf {
+        let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+        path.push("tbm_nvt");
+        path.push("config.toml");
+        path
+    }
+
+    /// Load the default config file, or the built-in defaults if there is none
+    pub fn load() -> NVTConfig {
+        let path = Self::config_path();
+        if !path.exists() {
+            return NVTConfig::default();
+        }
+        Self::load_from(&path)
+    }
+
+    /// Load a config file, falling back to the defaults if it can't be read or parsed
+    pub fn load_from(path: &Path) -> NVTConfig {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                eprintln!("⚠️  Could not read config {:?} ({}), using defaults", path, e);
+                return NVTConfig::default();
+            }
+        };
+
+        match toml::from_str::<NVTConfig>(&contents) {
+            Ok(mut config) => {
+                status!("⚙️  Loaded configuration from {:?}", path);
+                config.source = Some(path.to_path_buf());
+                config
+            }
+            Err(e) => {
+                eprintln!("⚠️  Invalid config {:?} ({}), using defaults", path, e);
+                NVTConfig::default()
+            }
+        }
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+}
@@ -1,24 +1,277 @@
 // Controllers for TBM Next Vehicle application
-use crate::nvt_models::{NVTModels, NetworkData, CachedNetworkData, Line, Stop, RealTimeInfo};
+use crate::nvt_config::NVTConfig;
+use crate::nvt_favorites::Favorites;
+use crate::nvt_models::{NVTModels, NetworkData, CachedNetworkData, Line, LineFrequency, Stop, StopType, RealTimeInfo, ArrivalLogger};
+use crate::nvt_mqtt::NVTMqttPublisher;
+use crate::nvt_server::NVTServer;
+use crate::nvt_tui::NVTTui;
 use crate::nvt_views::NVTViews;
+use crate::{Args, NearQuery, OutputFormat, StopTypeFilter};
+use chrono::{NaiveDate, TimeZone};
+use clap::CommandFactory;
+use clap::builder::PossibleValuesParser;
+#[cfg(unix)]
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use chrono_tz::Europe::Paris;
+use std::collections::HashSet;
 use std::io::{self, Write};
+use std::path::Path;
 use std::sync::mpsc::{channel, Sender, Receiver};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
+use tokio::runtime::Handle;
+
+/// Text typed at the autocomplete stop prompt and the highlighted match
+#[cfg(unix)]
+#[derive(Default)]
+struct StopAutocomplete {
+    input: String,
+    selected: usize,
+}
+
+#[cfg(unix)]
+impl StopAutocomplete {
+    const MAX_MATCHES: usize = 10;
+
+    /// First distinct stop names containing the typed text
+    fn matches<'a>(&self, network: &'a NetworkData) -> Vec<&'a str> {
+        let query = self.input.trim().to_lowercase();
+        let mut names: Vec<&str> = Vec::new();
+        if query.is_empty() {
+            return names;
+        }
+
+        for stop in &network.stops {
+            if names.len() == Self::MAX_MATCHES {
+                break;
+            }
+            if stop.stop_name.to_lowercase().contains(&query) && !names.contains(&stop.stop_name.as_str()) {
+                names.push(&stop.stop_name);
+            }
+        }
+        names
+    }
+
+    /// Apply a key press. Returns the confirmed text on Enter (the highlighted match if there
+    /// is one), or an empty string when the prompt is abandoned with Esc or Ctrl+C.
+    fn handle_key(&mut self, key: KeyEvent, matches: &[&str]) -> Option<String> {
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Some(String::new()),
+            KeyCode::Esc => return Some(String::new()),
+            KeyCode::Enter => {
+                return Some(matches.get(self.selected).map_or_else(|| self.input.trim().to_string(), |m| m.to_string()));
+            }
+            KeyCode::Tab => {
+                if let Some(name) = matches.get(self.selected) {
+                    self.input = name.to_string();
+                    self.selected = 0;
+                }
+            }
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.selected = (self.selected + 1).min(matches.len().saturating_sub(1)),
+            KeyCode::Backspace => {
+                self.input.pop();
+                self.selected = 0;
+            }
+            KeyCode::Char(c) => {
+                self.input.push(c);
+                self.selected = 0;
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Leaves raw mode when dropped, including on early return or panic
+#[cfg(unix)]
+struct RawModeGuard;
+
+#[cfg(unix)]
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+}
 
 pub struct NVTControllers;
 
 impl NVTControllers {
+    /// Exit status of non-interactive commands when the TBM data can't be fetched
+    const EXIT_NETWORK_ERROR: i32 = 1;
+    /// Exit status of non-interactive commands when --stop or --line matches nothing
+    const EXIT_NOT_FOUND: i32 = 2;
+    /// Exit status of --validate when the network data has integrity issues
+    const EXIT_VALIDATION_FAILED: i32 = 3;
+
     /// Main application loop
-    pub fn run() {
+    pub fn run(args: &Args) {
+        if let Some(shell) = args.generate_completions {
+            Self::handle_generate_completions(shell);
+            return;
+        }
+
+        if args.format == OutputFormat::Json {
+            NVTModels::set_quiet(true);
+        }
+        // https://no-color.org/: any non-empty NO_COLOR disables colors
+        let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        NVTViews::set_color(!(args.no_color || no_color_env));
+
+        let mut config = match &args.config {
+            Some(path) => NVTConfig::load_from(path),
+            None => NVTConfig::load(),
+        };
+        if args.siri_alerts {
+            config.use_siri_alerts = true;
+        }
+        NVTViews::set_relative_time(config.use_relative_time);
+        if let Some(log_path) = &args.log {
+            NVTViews::set_history_log(log_path.clone());
+        }
+
+        if args.cache_clear {
+            match NVTModels::clear_caches() {
+                Ok(_) => println!("✓ GTFS and SIRI caches cleared"),
+                Err(e) => eprintln!("⚠️  {}", e),
+            }
+        }
+
+        if args.clear_gtfs_cache {
+            match NVTModels::invalidate_gtfs_cache() {
+                Ok(_) => println!("✓ GTFS cache cleared"),
+                Err(e) => eprintln!("⚠️  {}", e),
+            }
+        }
+
+        if let Some(dir) = &args.export_parquet {
+            Self::handle_export_parquet(dir, &config);
+            return;
+        }
+
+        if args.export_stops_geojson.is_some() || args.export_vehicles_geojson.is_some() {
+            Self::handle_export_geojson(
+                args.export_stops_geojson.as_deref(),
+                args.export_vehicles_geojson.as_deref(),
+                &config,
+            );
+            return;
+        }
+
+        if let Some(path) = &args.csv {
+            Self::handle_export_csv(path, args.stop.as_deref(), &config);
+            return;
+        }
+
+        if args.report
+            && let Some(log_path) = &args.log
+        {
+            Self::handle_report(log_path, args.days);
+            return;
+        }
+
+        if args.accuracy_report
+            && let Some(log_path) = &args.log
+        {
+            Self::handle_accuracy_report(log_path, args.days);
+            return;
+        }
+
+        if args.occupancy_trend
+            && let (Some(log_path), Some(stop), Some(line)) = (&args.log, &args.stop, &args.line)
+        {
+            Self::handle_occupancy_trend(log_path, stop, line, &config);
+            return;
+        }
+
+        if let Some(near) = &args.near {
+            Self::handle_near(near, args.accessible, &config);
+            return;
+        }
+
+        if let Some(pattern) = &args.search_lines {
+            Self::handle_search_lines(pattern, args.circular, &config);
+            return;
+        }
+
+        if let Some(line) = &args.delay_stats {
+            Self::handle_delay_stats(line, &config);
+            return;
+        }
+
+        if args.validate {
+            Self::handle_validate(&config);
+        }
+
+        if args.coverage_report {
+            Self::handle_coverage_report(&config);
+            return;
+        }
+
+        if args.gaps
+            && let Some(line) = &args.line
+        {
+            Self::handle_gaps(line, args.date, &config);
+            return;
+        }
+
+        if args.headway {
+            Self::handle_headway(args.stop.as_deref().unwrap_or_default(), args.line.as_deref(), args.date, &config);
+            return;
+        }
+
+        if let (Some(from), Some(to)) = (&args.from, &args.to) {
+            Self::handle_journey(from, to, args.date, &config);
+            return;
+        }
+
+        if let Some(from) = &args.isochrone {
+            Self::handle_isochrone(from, args.minutes, args.date, &config);
+            return;
+        }
+
+        if args.watch {
+            Self::handle_watch(args, &config);
+            return;
+        }
+
+        if let Some(trip_id) = &args.trip {
+            Self::handle_trip(trip_id, &config);
+            return;
+        }
+
+        if args.format == OutputFormat::Json {
+            Self::handle_json_departures(args, &config);
+            return;
+        }
+
+        if args.cli
+            && let Some(stop) = &args.stop
+        {
+            Self::handle_cli_departures(stop, args, &config);
+            return;
+        }
+
+        if let Some(addr) = args.serve {
+            if let Err(e) = Handle::current().block_on(NVTServer::run_server(addr, args.metrics_port, config)) {
+                NVTViews::network_error(&format!("{}", e));
+            }
+            return;
+        }
+
+        if let Some(stop) = &args.stop {
+            eprintln!("💡 --stop has no effect on the menu; use `--cli --stop {}` to print its arrivals directly", stop);
+        }
+
         Self::show_welcome_screen();
 
         println!("\n🔄 Loading TBM network data...");
         println!("   Please wait, this may take a moment...");
 
         // Initialize cache
-        let mut cache = match NVTModels::initialize_cache() {
+        let mut cache = match Handle::current().block_on(NVTModels::initialize_cache(&config)) {
             Ok(data) => {
                 println!("\n✓ Network data loaded successfully!");
                 data
@@ -30,9 +283,50 @@ impl NVTControllers {
                 return;
             }
         };
+        cache.display_lang = args.lang.clone();
+        cache.service_date = args.date;
+        cache.min_severity = args.min_severity;
+        if !args.tui {
+            NVTViews::show_network_summary(&NVTModels::network_summary(&cache.to_network_data()));
+        }
+
+        if let Some(path) = &args.log {
+            match ArrivalLogger::open(path.clone()) {
+                Ok(logger) => {
+                    println!("📝 Logging arrivals to {:?}", path);
+                    cache.arrival_logger = Some(Arc::new(Mutex::new(logger)));
+                }
+                Err(e) => eprintln!("⚠️  {}", e),
+            }
+        }
+
+        if let Some(broker) = &args.mqtt {
+            match NVTMqttPublisher::new(broker, &args.mqtt_topic) {
+                Ok(publisher) => {
+                    let stop_ids = Favorites::load().stops;
+                    if stop_ids.is_empty() {
+                        println!("💡 No pinned stops: pin stops in My favorites to publish them over MQTT");
+                    } else {
+                        println!("📡 Publishing arrivals of {} pinned stop(s) to {} ({}/arrivals/...)",
+                                 stop_ids.len(), broker, args.mqtt_topic);
+                    }
+                    cache.mqtt_publisher = Some(Arc::new(publisher.with_stops(stop_ids)));
+                }
+                Err(e) => eprintln!("⚠️  {}", e),
+            }
+        }
+
+        if args.tui {
+            if let Err(e) = NVTTui::run(&mut cache, &config) {
+                eprintln!("❌ Terminal interface failed: {}", e);
+            }
+            return;
+        }
 
         let mut selected_line: Option<String> = None;
         let mut selected_stop: Option<String> = None;
+        let mut last_arrivals: Vec<RealTimeInfo> = Vec::new();
+        let mut favorites = Favorites::load();
 
         loop {
 
@@ -56,14 +350,17 @@ impl NVTControllers {
                     Self::pause();
                 }
                 "3" => {
-                    Self::handle_show_next_vehicle_with_refresh(
+                    last_arrivals = Self::handle_show_next_vehicle_with_refresh(
                         &mut cache,
+                        &config,
                         &selected_line,
-                        &selected_stop
+                        &selected_stop,
+                        args.board,
+                        args.window,
                     );
                 }
                 "4" => {
-                    Self::handle_show_all_stops(&network);
+                    Self::handle_show_all_stops(&network, args.stop_type);
                     Self::pause();
                 }
                 "5" => {
@@ -74,6 +371,29 @@ impl NVTControllers {
                     println!("\n{}", NVTModels::get_cache_stats(&cache));
                     Self::pause();
                 }
+                "7" => {
+                    Self::handle_show_line_stops(&network, &selected_line);
+                    Self::pause();
+                }
+                "8" => {
+                    Self::handle_favorites(&network, &mut favorites, &mut selected_line, &mut selected_stop);
+                    Self::pause();
+                }
+                "9" => {
+                    Self::handle_trip_info(&cache, &network, &last_arrivals);
+                    Self::pause();
+                }
+                "10" => {
+                    Self::handle_line_frequency_table(&mut cache, &network);
+                }
+                "h" | "H" => {
+                    Self::handle_busiest_stop(&network);
+                    Self::pause();
+                }
+                "c" | "C" => {
+                    Self::handle_compare_stops(&network);
+                    Self::pause();
+                }
                 "0" => {
                     NVTViews::goodbye_message();
                     break;
@@ -82,7 +402,7 @@ impl NVTControllers {
                     // Just pressed Enter, show menu again
                 }
                 _ => {
-                    println!("\n✗ Invalid option '{}'. Please select 0-6.", choice.trim());
+                    println!("\n✗ Invalid option '{}'. Please select an option from the menu.", choice.trim());
                     Self::pause();
                 }
             }
@@ -115,6 +435,51 @@ impl NVTControllers {
         io::stdin().read_line(&mut dummy).unwrap();
     }
 
+    /// Stop prompt listing up to 10 matching stop names while typing: Up/Down move the
+    /// highlight, Tab completes it, Enter confirms. Falls back to the line prompt when
+    /// stdin isn't a terminal.
+    #[cfg(unix)]
+    fn handle_interactive_stop_autocomplete(network: &NetworkData) -> String {
+        use std::io::IsTerminal;
+
+        if !io::stdin().is_terminal() {
+            return NVTViews::prompt_stop();
+        }
+        NVTViews::show_stop_prompt_hint();
+        if crossterm::terminal::enable_raw_mode().is_err() {
+            return NVTViews::read_stop_input();
+        }
+        let guard = RawModeGuard;
+
+        let mut prompt = StopAutocomplete::default();
+        let confirmed = loop {
+            let matches = prompt.matches(network);
+            NVTViews::render_autocomplete_list(&matches, prompt.selected);
+            NVTViews::render_autocomplete_prompt(&prompt.input);
+
+            match event::read() {
+                Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                    if let Some(text) = prompt.handle_key(key, &matches) {
+                        break text;
+                    }
+                }
+                Ok(_) => {}
+                Err(_) => break prompt.input.trim().to_string(),
+            }
+        };
+
+        NVTViews::render_autocomplete_list(&[], 0);
+        NVTViews::render_autocomplete_prompt(&confirmed);
+        drop(guard);
+        println!();
+        confirmed
+    }
+
+    #[cfg(not(unix))]
+    fn handle_interactive_stop_autocomplete(_network: &NetworkData) -> String {
+        NVTViews::prompt_stop()
+    }
+
     /// Handle line selection with improved error handling
     fn handle_line_selection(network: &NetworkData) -> Option<String> {
         let line_input = NVTViews::prompt_line();
@@ -160,21 +525,30 @@ impl NVTControllers {
         network: &NetworkData,
         selected_line: &Option<String>,
     ) -> Option<String> {
-        let stop_input = NVTViews::prompt_stop();
+        let stop_input = Self::handle_interactive_stop_autocomplete(network);
 
         if stop_input.is_empty() {
             println!("\n⚠️  No input provided");
             return None;
         }
 
-        // Find matching stops (partial match)
+        // "A→B" / "A,B" naming two lines: look for the stops where they meet
+        if let Some((input_a, input_b)) = stop_input.split_once(['→', ','])
+            .or_else(|| stop_input.split_once("->"))
+            && let Some(line_a) = Self::resolve_line(input_a.trim(), network)
+            && let Some(line_b) = Self::resolve_line(input_b.trim(), network)
+        {
+            return Self::handle_transfer_selection(line_a, line_b, network);
+        }
+
+        // Find matching stops (partial match); entrances and other station nodes aren't boardable
         let matching_stops: Vec<&Stop> = network.stops.iter()
+            .filter(|s| matches!(s.stop_type, StopType::Platform | StopType::Station | StopType::Unknown))
             .filter(|s| s.stop_name.to_lowercase().contains(&stop_input.to_lowercase()))
             .collect();
 
         if matching_stops.is_empty() {
-            NVTViews::invalid_stop(&stop_input);
-            return None;
+            return Self::handle_fuzzy_stop_fallback(&stop_input, network);
         }
 
         // Filter by selected line if applicable
@@ -198,31 +572,76 @@ impl NVTControllers {
 
         // Handle selection
         let selected_stop = if filtered_stops.len() > 1 {
-            NVTViews::show_stop_choices(&filtered_stops, network);
-            Self::select_from_list(&filtered_stops)
+            // All matches being platforms of one station, their departures can be compared first
+            let is_station = NVTModels::find_stop_group(&filtered_stops[0].stop_name, network)
+                .is_some_and(|group| filtered_stops.iter().all(|s| group.iter().any(|g| g.stop_id == s.stop_id)));
+            NVTViews::show_stop_choices(&filtered_stops, is_station, network);
+            if is_station {
+                Self::select_platform(&filtered_stops, network)
+            } else {
+                Self::select_from_list(&filtered_stops)
+            }
         } else {
             Some(filtered_stops[0])
         };
 
         match selected_stop {
             Some(stop) => {
-                NVTViews::show_stop_selected(stop, network);
+                Self::show_stop_detail(stop, network);
                 Some(stop.stop_id.clone())
             }
             None => None,
         }
     }
 
-    /// Handle showing next vehicles with auto-refresh
+    /// Show a stop picked in stop selection, with the frequency of each of its lines
+    fn show_stop_detail(stop: &Stop, network: &NetworkData) {
+        let lines = NVTModels::list_lines_at_stop_with_frequency(&stop.stop_id, network, &network.gtfs);
+        NVTViews::show_stop_detail(stop, &lines, network);
+    }
+
+    /// List the transfer stops between two lines and let the user pick one
+    fn handle_transfer_selection(line_a: &Line, line_b: &Line, network: &NetworkData) -> Option<String> {
+        let transfers = NVTModels::find_transfer_stops(&line_a.line_ref, &line_b.line_ref, network);
+        NVTViews::show_transfer_stops(line_a, line_b, &transfers);
+        if transfers.is_empty() {
+            return None;
+        }
+
+        let stop = Self::select_from_list(&transfers)?;
+        Self::show_stop_detail(stop, network);
+        Some(stop.stop_id.clone())
+    }
+
+    /// Offer close matches when the stop name search finds nothing (typos)
+    fn handle_fuzzy_stop_fallback(stop_input: &str, network: &NetworkData) -> Option<String> {
+        let suggestions = NVTModels::find_stops_fuzzy(stop_input, network, 5);
+
+        if suggestions.is_empty() {
+            NVTViews::invalid_stop(stop_input);
+            return None;
+        }
+
+        NVTViews::show_fuzzy_stop_suggestions(stop_input, &suggestions);
+        let stop = Self::select_from_list(&suggestions)?;
+        Self::show_stop_detail(stop, network);
+        Some(stop.stop_id.clone())
+    }
+
+    /// Handle showing next vehicles with auto-refresh.
+    /// Returns the vehicles shown in the last refresh cycle.
     fn handle_show_next_vehicle_with_refresh(
         cache: &mut CachedNetworkData,
+        config: &NVTConfig,
         selected_line: &Option<String>,
         selected_stop: &Option<String>,
-    ) {
+        board: bool,
+        window: Option<(u32, u32)>,
+    ) -> Vec<RealTimeInfo> {
         if selected_stop.is_none() {
             NVTViews::no_stop_selected();
             Self::pause();
-            return;
+            return Vec::new();
         }
 
         let stop_id = selected_stop.as_ref().unwrap().clone();
@@ -231,7 +650,7 @@ impl NVTControllers {
         println!("\n{}", "═".repeat(70));
         println!("🔄 AUTO-REFRESH MODE");
         println!("{}", "═".repeat(70));
-        println!("   Data refreshes automatically every 30 seconds");
+        println!("   Data refreshes automatically every {} seconds", config.dynamic_max_age_secs);
         println!("   Press ENTER at any time to return to menu");
         println!("{}", "═".repeat(70));
 
@@ -244,7 +663,7 @@ impl NVTControllers {
             if refresh_count > 1 {
                 NVTViews::show_loading("Refreshing data");
 
-                match NVTModels::smart_refresh(cache) {
+                match Handle::current().block_on(NVTModels::smart_refresh(cache, config)) {
                     Ok(_) => {
                         NVTViews::clear_loading();
                         println!("✓ Data refreshed successfully");
@@ -255,31 +674,87 @@ impl NVTControllers {
                         println!("   Using cached data, will retry next cycle...");
                     }
                 }
+            } else {
+                Self::refresh_targeted_alerts(cache, config, &stop_id, &line_ref);
+            }
+            if config.use_siri_timetable && let Some(line_ref) = &line_ref {
+                Self::refresh_siri_timetable(cache, config, &stop_id, line_ref);
             }
 
             // Display data
             Self::clear_screen();
             Self::display_refresh_header(refresh_count, cache);
 
-            let network = cache.to_network_data(); // Make this line not hang out whole program
-            Self::display_next_vehicles(&network, &line_ref, &Some(stop_id.clone()));
+            let mut network = cache.to_network_data(); // Make this line not hang out whole program
+            if let Some(window) = window {
+                Self::retain_time_window(&mut network, window);
+            }
+            let shown = Self::display_next_vehicles(&network, &line_ref, &Some(stop_id.clone()), board);
 
             // Show cache stats
             println!("\n{}", NVTModels::get_cache_stats(cache));
 
             // Wait for input or timeout
             println!("\n{}", "─".repeat(70));
-            println!("⏱️  Next refresh in 30 seconds (or press ENTER to exit)");
+            println!("⏱️  Next refresh in {} seconds (or press ENTER to exit)", config.dynamic_max_age_secs);
             println!("{}", "─".repeat(70));
 
-            if Self::wait_for_input_or_timeout(30) {
+            if Self::wait_for_input_or_timeout(config.dynamic_max_age_secs) {
                 println!("\n👋 Exiting auto-refresh mode...");
                 // Don't call pause here - return directly
-                return;
+                return shown;
             }
         }
     }
 
+    /// Print the completion script, offering the cached stop IDs and line codes for --stop/--line.
+    /// Those options still accept any value: only the generated script knows the candidates.
+    fn handle_generate_completions(shell: clap_complete::Shell) {
+        let mut command = Args::command();
+        for (arg, values) in [("stop", NVTModels::list_cached_stop_ids()), ("line", NVTModels::list_cached_line_codes())] {
+            if !values.is_empty() {
+                command = command.mut_arg(arg, |a| a.value_parser(PossibleValuesParser::new(values)));
+            }
+        }
+
+        let name = command.get_name().to_string();
+        let mut script = Vec::new();
+        clap_complete::generate(shell, &mut command, name, &mut script);
+        // A closed pipe (e.g. `| head`) isn't worth a panic
+        let _ = io::stdout().write_all(&script);
+    }
+
+    /// Fetch fresh alerts for the monitored stop (and line) on top of the cached feed
+    fn refresh_targeted_alerts(
+        cache: &mut CachedNetworkData,
+        config: &NVTConfig,
+        stop_id: &str,
+        line_ref: &Option<String>,
+    ) {
+        match Handle::current().block_on(NVTModels::fetch_alerts_for_stop(stop_id, config)) {
+            Ok(alerts) => NVTModels::merge_alerts(cache, alerts),
+            Err(e) => eprintln!("⚠️  Could not fetch alerts for stop: {}", e),
+        }
+
+        if let Some(route_id) = line_ref.as_deref().and_then(NVTModels::extract_line_id) {
+            match Handle::current().block_on(NVTModels::fetch_alerts_for_route(route_id, config)) {
+                Ok(alerts) => NVTModels::merge_alerts(cache, alerts),
+                Err(e) => eprintln!("⚠️  Could not fetch alerts for line: {}", e),
+            }
+        }
+    }
+
+    /// Fetch the SIRI stop timetable of the monitored stop and line (use_siri_timetable)
+    fn refresh_siri_timetable(cache: &mut CachedNetworkData, config: &NVTConfig, stop_id: &str, line_ref: &str) {
+        let now = NVTModels::get_current_timestamp();
+        match Handle::current().block_on(NVTModels::fetch_line_timetable_siri(line_ref, stop_id, now, config)) {
+            Ok(departures) => {
+                cache.siri_timetables.insert(stop_id.to_string(), departures);
+            }
+            Err(e) => eprintln!("⚠️  Could not fetch the stop timetable: {}", e),
+        }
+    }
+
     /// Wait for user input with timeout - COMPLETELY REWRITTEN
     fn wait_for_input_or_timeout(seconds: u64) -> bool {
         let exit_flag = Arc::new(Mutex::new(false));
@@ -328,23 +803,25 @@ impl NVTControllers {
         println!("{}", "═".repeat(70));
     }
 
-    /// Display next vehicles (single display)
+    /// Display next vehicles (single display), returning the vehicles shown.
+    /// `board` switches to the fixed-width departure board layout.
     fn display_next_vehicles(
         network: &NetworkData,
         selected_line: &Option<String>,
         selected_stop: &Option<String>,
-    ) {
+        board: bool,
+    ) -> Vec<RealTimeInfo> {
         if selected_stop.is_none() {
             NVTViews::no_stop_selected();
-            return;
+            return Vec::new();
         }
 
         let stop_id = selected_stop.as_ref().unwrap();
-        let stop = network.stops.iter().find(|s| &s.stop_id == stop_id);
+        let stop = NVTModels::get_stop_by_id(stop_id, network);
 
         if stop.is_none() {
             println!("\n✗ Stop not found in network data");
-            return;
+            return Vec::new();
         }
 
         let stop = stop.unwrap();
@@ -364,25 +841,126 @@ impl NVTControllers {
             }
         }
 
-        NVTViews::show_next_vehicles(
-            stop,
-            &vehicles,
-            selected_line.as_ref().and_then(|lr| {
-                network.lines.iter().find(|l| &l.line_ref == lr)
-            }),
-            network,
-        );
+        if board {
+            // The board lists cancelled trips in place, marked in red
+            let selected_route = selected_line.as_deref().and_then(NVTModels::extract_line_id);
+            let mut rows = vehicles.clone();
+            rows.extend(stop.cancelled_arrivals.iter()
+                .filter(|rt| selected_route.is_none() || rt.route_id.as_deref() == selected_route));
+            rows.sort_by_key(|rt| rt.timestamp.unwrap_or(i64::MAX));
+            NVTViews::show_departure_board(stop, &rows, network);
+            // Vehicle numbers count the board rows shown from the top, cancelled trips left out
+            return rows.into_iter().take(10).filter(|rt| !rt.cancelled).cloned().collect();
+        } else {
+            NVTViews::show_next_vehicles(
+                stop,
+                &vehicles,
+                selected_line.as_ref().and_then(|lr| {
+                    network.lines.iter().find(|l| &l.line_ref == lr)
+                }),
+                network,
+            );
+        }
+
+        vehicles.into_iter().take(10).cloned().collect()
+    }
+
+    /// Handle showing the full trip of a vehicle from the last arrivals display
+    fn handle_trip_info(
+        cache: &CachedNetworkData,
+        network: &NetworkData,
+        last_arrivals: &[RealTimeInfo],
+    ) {
+        if last_arrivals.is_empty() {
+            NVTViews::no_arrivals_shown();
+            return;
+        }
+
+        print!("\n➜ Enter vehicle number (1-{}): ", last_arrivals.len());
+        io::stdout().flush().unwrap();
+
+        let input = Self::read_input();
+        let vehicle = match input.trim().parse::<usize>() {
+            Ok(num) if num > 0 && num <= last_arrivals.len() => &last_arrivals[num - 1],
+            _ => {
+                println!("✗ Invalid selection. Please enter a number between 1 and {}", last_arrivals.len());
+                return;
+            }
+        };
+
+        let journey = NVTModels::get_vehicle_journey(&vehicle.trip_id, cache, network);
+        if journey.is_empty() {
+            println!("\n✗ No trip details available for trip {}", vehicle.trip_id);
+            return;
+        }
+
+        NVTViews::show_trip_details(&journey);
+    }
+
+    /// Handle showing the ordered stops of the selected line, in both directions
+    fn handle_show_line_stops(network: &NetworkData, selected_line: &Option<String>) {
+        let line = match selected_line.as_ref().and_then(|lr| network.lines.iter().find(|l| &l.line_ref == lr)) {
+            Some(line) => line,
+            None => {
+                NVTViews::line_required();
+                return;
+            }
+        };
+
+        for direction_id in [0, 1] {
+            let sequence = NVTModels::get_stop_sequence(&line.line_ref, direction_id, &network.gtfs);
+            if !sequence.is_empty() {
+                NVTViews::show_line_stop_sequence(line, direction_id, &sequence, network);
+            }
+        }
+    }
+
+    /// Peak/off-peak headway and service span of every line, sorted by peak headway or line code
+    fn handle_line_frequency_table(cache: &mut CachedNetworkData, network: &NetworkData) {
+        let service_date = NVTModels::service_date(network.service_date);
+        let mut rows: Vec<(&Line, LineFrequency)> = network.lines.iter()
+            .map(|line| {
+                let (peak_headway_s, off_peak_headway_s) = *cache.headway_cache
+                    .entry((line.line_ref.clone(), service_date))
+                    .or_insert_with(|| NVTModels::line_headways(&line.line_ref, service_date, &network.gtfs));
+                let service_span = NVTModels::line_service_span(&line.line_ref, service_date, &network.gtfs);
+                (line, LineFrequency { peak_headway_s, off_peak_headway_s, service_span })
+            })
+            .collect();
+
+        let mut sort_by_peak = true;
+        loop {
+            if sort_by_peak {
+                rows.sort_by_key(|(line, frequency)| (frequency.peak_headway_s.is_none(), frequency.peak_headway_s, line.line_code.clone()));
+            } else {
+                rows.sort_by(|(a, _), (b, _)| (a.line_code.len(), &a.line_code).cmp(&(b.line_code.len(), &b.line_code)));
+            }
+            NVTViews::show_line_frequency_table(&rows, sort_by_peak);
+
+            match NVTViews::prompt_frequency_sort().as_str() {
+                "s" => sort_by_peak = !sort_by_peak,
+                _ => break,
+            }
+        }
     }
 
-    /// Handle showing all stops
-    fn handle_show_all_stops(network: &NetworkData) {
+    /// Handle showing all stops, optionally only stations or only plain stops (--type)
+    fn handle_show_all_stops(network: &NetworkData, filter: Option<StopTypeFilter>) {
         NVTViews::all_stops_warning();
         print!("\nContinue? (y/n): ");
         io::stdout().flush().unwrap();
 
         let input = Self::read_input();
         if input.trim().eq_ignore_ascii_case("y") {
-            NVTViews::show_all_stops(&network.stops, network);
+            let stops: Vec<Stop> = network.stops.iter()
+                .filter(|s| match filter {
+                    Some(StopTypeFilter::Station) => s.stop_type == StopType::Station,
+                    Some(StopTypeFilter::Stop) => matches!(s.stop_type, StopType::Platform | StopType::Unknown),
+                    None => true,
+                })
+                .cloned()
+                .collect();
+            NVTViews::show_all_stops(&stops, network);
         } else {
             NVTViews::operation_cancelled();
         }
@@ -402,35 +980,831 @@ impl NVTControllers {
         }
     }
 
-    /// Select from a list of items
-    fn select_from_list<'a>(items: &[&'a Stop]) -> Option<&'a Stop> {
-        print!("\n➜ Enter number (1-{}): ", items.len());
-        io::stdout().flush().unwrap();
-
-        let input = Self::read_input();
+    /// Print the load indicator of a line at a stop for each hour of the day with logged arrivals
+    fn handle_occupancy_trend(log_path: &Path, stop_query: &str, line_query: &str, config: &NVTConfig) {
+        println!("\n🔄 Loading TBM network data...");
 
-        match input.trim().parse::<usize>() {
-            Ok(num) if num > 0 && num <= items.len() => Some(items[num - 1]),
-            _ => {
-                println!("✗ Invalid selection. Please enter a number between 1 and {}", items.len());
-                None
+        let cache = match Handle::current().block_on(NVTModels::initialize_cache(config)) {
+            Ok(data) => data,
+            Err(e) => {
+                NVTViews::network_error(&format!("{}", e));
+                return;
             }
-        }
-    }
+        };
+        let network = cache.to_network_data();
 
-    /// Read input from stdin with error handling
-    fn read_input() -> String {
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-            Ok(_) => input,
+        let Some(stop) = Self::resolve_stop(stop_query, &network) else {
+            NVTViews::invalid_stop(stop_query);
+            return;
+        };
+        let Some(line) = Self::resolve_line(line_query, &network) else {
+            NVTViews::invalid_line(line_query);
+            return;
+        };
+
+        let loads = match NVTModels::compute_load_indicators(log_path, &stop.stop_id) {
+            Ok(loads) => loads,
             Err(e) => {
-                eprintln!("⚠️  Error reading input: {}", e);
-                String::new()
+                eprintln!("✗ {}", e);
+                return;
             }
-        }
+        };
+        let route_id = NVTModels::extract_line_id(&line.line_ref).unwrap_or(&line.line_ref);
+        let mut hourly: Vec<(u8, f32)> = loads.into_iter()
+            .filter(|((route, _), _)| route == route_id)
+            .map(|((_, hour), load)| (hour, load))
+            .collect();
+        hourly.sort_by_key(|(hour, _)| *hour);
+
+        NVTViews::show_occupancy_trend(stop, line, &hourly, &network);
     }
 
-    /// Clear screen (cross-platform)
+    /// Print scheduled headways for a stop given on the command line
+    fn handle_headway(stop_query: &str, line_query: Option<&str>, date: Option<NaiveDate>, config: &NVTConfig) {
+        println!("\n🔄 Loading TBM network data...");
+
+        let cache = match Handle::current().block_on(NVTModels::initialize_cache(config)) {
+            Ok(data) => data,
+            Err(e) => {
+                NVTViews::network_error(&format!("{}", e));
+                return;
+            }
+        };
+        let network = cache.to_network_data();
+        let service_date = NVTModels::service_date(date);
+
+        let stop = match Self::resolve_stop(stop_query, &network) {
+            Some(stop) => stop,
+            None => {
+                NVTViews::invalid_stop(stop_query);
+                return;
+            }
+        };
+
+        let lines: Vec<&Line> = match line_query {
+            Some(query) => match Self::resolve_line(query, &network) {
+                Some(line) => vec![line],
+                None => {
+                    NVTViews::invalid_line(query);
+                    return;
+                }
+            },
+            None => stop.lines.iter()
+                .filter_map(|lr| network.lines.iter().find(|l| &l.line_ref == lr))
+                .collect(),
+        };
+
+        let mut headways = Vec::new();
+        for line in lines {
+            let route_id = NVTModels::extract_line_id(&line.line_ref).unwrap_or(&line.line_ref);
+            for direction_id in [0, 1] {
+                let headway = NVTModels::compute_headway_seconds(&stop.stop_id, route_id, direction_id, service_date, &network.gtfs);
+                let destination = line.destinations.iter()
+                    .find(|(dir_ref, _)| dir_ref == &direction_id.to_string())
+                    .map(|(_, place)| place.clone())
+                    .unwrap_or_else(|| format!("direction {}", direction_id));
+                headways.push((line, destination, headway));
+            }
+        }
+
+        NVTViews::show_headways(stop, &headways, &network);
+    }
+
+    /// Print the lines matching a --search-lines pattern, only the circular ones with --circular
+    fn handle_search_lines(pattern: &str, circular_only: bool, config: &NVTConfig) {
+        println!("\n🔄 Loading TBM network data...");
+
+        let cache = match Handle::current().block_on(NVTModels::initialize_cache(config)) {
+            Ok(data) => data,
+            Err(e) => {
+                NVTViews::network_error(&format!("{}", e));
+                return;
+            }
+        };
+        let network = cache.to_network_data();
+
+        let is_regex = regex::Regex::new(pattern).is_ok();
+        let mut lines = NVTModels::search_lines(pattern, &network);
+        if circular_only {
+            lines.retain(|line| line.is_circular);
+        }
+        NVTViews::show_line_search_results(pattern, is_regex, &lines);
+    }
+
+    /// Print the live delay distribution of one line
+    fn handle_delay_stats(line_query: &str, config: &NVTConfig) {
+        println!("\n🔄 Loading TBM network data...");
+
+        let cache = match Handle::current().block_on(NVTModels::initialize_cache(config)) {
+            Ok(data) => data,
+            Err(e) => {
+                NVTViews::network_error(&format!("{}", e));
+                return;
+            }
+        };
+        let network = cache.to_network_data();
+
+        let Some(line) = Self::resolve_line(line_query, &network) else {
+            NVTViews::invalid_line(line_query);
+            return;
+        };
+        let route_id = NVTModels::extract_line_id(&line.line_ref).unwrap_or(&line.line_ref);
+
+        NVTViews::show_delay_statistics(line, &NVTModels::compute_delay_statistics(route_id, &network));
+    }
+
+    /// List every integrity issue of the network data and exit with a script-friendly status
+    fn handle_validate(config: &NVTConfig) -> ! {
+        println!("\n🔄 Loading TBM network data...");
+
+        let cache = match Handle::current().block_on(NVTModels::initialize_cache(config)) {
+            Ok(data) => data,
+            Err(e) => {
+                NVTViews::network_error(&format!("{}", e));
+                std::process::exit(Self::EXIT_NETWORK_ERROR);
+            }
+        };
+
+        let violations = NVTModels::validate_network_data(&cache.to_network_data());
+        NVTViews::show_validation_report(&violations);
+        std::process::exit(if violations.is_empty() { 0 } else { Self::EXIT_VALIDATION_FAILED });
+    }
+
+    /// List the stops without real-time data under each line serving them
+    fn handle_coverage_report(config: &NVTConfig) {
+        println!("\n🔄 Loading TBM network data...");
+
+        let cache = match Handle::current().block_on(NVTModels::initialize_cache(config)) {
+            Ok(data) => data,
+            Err(e) => {
+                NVTViews::network_error(&format!("{}", e));
+                return;
+            }
+        };
+        let network = cache.to_network_data();
+
+        let uncovered = NVTModels::stops_without_real_time(&network);
+        let uncovered_ids: HashSet<&str> = uncovered.iter().map(|s| s.stop_id.as_str()).collect();
+
+        let by_line: Vec<(&Line, Vec<&Stop>, usize)> = network.lines
+            .iter()
+            .filter_map(|line| {
+                let stops = NVTModels::get_stops_for_line(&line.line_ref, &network);
+                let total = stops.len();
+                let missing: Vec<&Stop> = stops.into_iter()
+                    .filter(|s| uncovered_ids.contains(s.stop_id.as_str()))
+                    .collect();
+                (!missing.is_empty()).then_some((line, missing, total))
+            })
+            .collect();
+
+        NVTViews::show_coverage_report(uncovered.len(), network.stops.len(), &by_line);
+    }
+
+    /// List the service gaps of a line's timetable at each of its stops, in both directions
+    fn handle_gaps(line_query: &str, date: Option<NaiveDate>, config: &NVTConfig) {
+        println!("\n🔄 Loading TBM network data...");
+
+        let cache = match Handle::current().block_on(NVTModels::initialize_cache(config)) {
+            Ok(data) => data,
+            Err(e) => {
+                NVTViews::network_error(&format!("{}", e));
+                return;
+            }
+        };
+        let network = cache.to_network_data();
+
+        let Some(line) = Self::resolve_line(line_query, &network) else {
+            NVTViews::invalid_line(line_query);
+            return;
+        };
+        let route_id = NVTModels::extract_line_id(&line.line_ref).unwrap_or(&line.line_ref);
+
+        // Scan the whole service day
+        let day_start = NVTModels::service_date(date)
+            .and_hms_opt(0, 0, 0)
+            .and_then(|dt| Paris.from_local_datetime(&dt).earliest())
+            .map_or(0, |dt| dt.timestamp());
+
+        let mut gaps = Vec::new();
+        for direction_id in [0, 1] {
+            let destination = line.destinations.iter()
+                .find(|(dir_ref, _)| dir_ref == &direction_id.to_string())
+                .map(|(_, place)| place.clone())
+                .unwrap_or_else(|| format!("direction {}", direction_id));
+
+            let sequence = NVTModels::get_stop_sequence(&line.line_ref, direction_id, &network.gtfs);
+            let stops: Vec<&Stop> = if sequence.is_empty() {
+                NVTModels::get_stops_for_line(&line.line_ref, &network)
+            } else {
+                sequence.iter().filter_map(|id| NVTModels::get_stop_by_id(id, &network)).collect()
+            };
+
+            for stop in stops {
+                let stop_gaps = NVTModels::find_service_gaps(&stop.stop_id, route_id, direction_id, day_start, &network.gtfs);
+                if !stop_gaps.is_empty() {
+                    gaps.push((stop, destination.clone(), stop_gaps));
+                }
+            }
+        }
+
+        NVTViews::show_service_gaps(line, &gaps, &network);
+    }
+
+    /// Print the departures at --stop (optionally only --line) as JSON (--format json).
+    /// Errors go to stderr as JSON and exit with status 1.
+    fn handle_json_departures(args: &Args, config: &NVTConfig) {
+        let fail = |message: String, code: i32| -> ! {
+            NVTViews::json_error(&message);
+            std::process::exit(code);
+        };
+
+        let Some(stop_query) = args.stop.as_deref() else {
+            fail("--format json requires --stop".to_string(), 1);
+        };
+
+        let mut cache = match Handle::current().block_on(NVTModels::initialize_cache(config)) {
+            Ok(data) => data,
+            Err(e) => fail(e.to_string(), Self::EXIT_NETWORK_ERROR),
+        };
+        cache.display_lang = args.lang.clone();
+        cache.service_date = args.date;
+        cache.min_severity = args.min_severity;
+        let mut network = cache.to_network_data();
+        if let Some(window) = args.window {
+            Self::retain_time_window(&mut network, window);
+        }
+
+        let Some(stop) = Self::resolve_stop(stop_query, &network) else {
+            fail(format!("Unknown stop: {}", stop_query), Self::EXIT_NOT_FOUND);
+        };
+
+        let line = match args.line.as_deref() {
+            Some(line_query) => match Self::resolve_line(line_query, &network) {
+                Some(line) => Some(line),
+                None => fail(format!("Unknown line: {}", line_query), Self::EXIT_NOT_FOUND),
+            },
+            None => None,
+        };
+
+        let vehicles = Self::departures_for(&stop.stop_id, line, &network);
+        println!("{}", NVTViews::format_arrivals_json(&vehicles, &network));
+    }
+
+    /// Print the arrivals at --stop (optionally only --line) once, as text or a --board (--cli)
+    fn handle_cli_departures(stop_query: &str, args: &Args, config: &NVTConfig) {
+        let mut cache = match Handle::current().block_on(NVTModels::initialize_cache(config)) {
+            Ok(data) => data,
+            Err(e) => {
+                NVTViews::network_error(&format!("{}", e));
+                std::process::exit(Self::EXIT_NETWORK_ERROR);
+            }
+        };
+        cache.display_lang = args.lang.clone();
+        cache.service_date = args.date;
+        cache.min_severity = args.min_severity;
+        let mut network = cache.to_network_data();
+        if let Some(window) = args.window {
+            Self::retain_time_window(&mut network, window);
+        }
+
+        let Some(stop) = Self::resolve_stop(stop_query, &network) else {
+            NVTViews::invalid_stop(stop_query);
+            std::process::exit(Self::EXIT_NOT_FOUND);
+        };
+
+        let line_ref = match args.line.as_deref() {
+            Some(line_query) => match Self::resolve_line(line_query, &network) {
+                Some(line) => Some(line.line_ref.clone()),
+                None => {
+                    NVTViews::invalid_line(line_query);
+                    std::process::exit(Self::EXIT_NOT_FOUND);
+                }
+            },
+            None => None,
+        };
+
+        Self::display_next_vehicles(&network, &line_ref, &Some(stop.stop_id.clone()), args.board);
+    }
+
+    /// Print the real-time stop sequence of a trip (--trip)
+    fn handle_trip(trip_id: &str, config: &NVTConfig) {
+        let cache = match Handle::current().block_on(NVTModels::initialize_cache(config)) {
+            Ok(data) => data,
+            Err(e) => {
+                NVTViews::network_error(&format!("{}", e));
+                std::process::exit(Self::EXIT_NETWORK_ERROR);
+            }
+        };
+        let network = cache.to_network_data();
+
+        let journey = NVTModels::get_vehicle_journey(trip_id, &cache, &network);
+        if journey.is_empty() {
+            eprintln!("✗ No real-time update for trip {}", trip_id);
+            std::process::exit(Self::EXIT_NOT_FOUND);
+        }
+
+        NVTViews::show_trip_table(trip_id, &journey);
+    }
+
+    /// Next vehicles at a stop, only those of `line` when given
+    fn departures_for(stop_id: &str, line: Option<&Line>, network: &NetworkData) -> Vec<RealTimeInfo> {
+        let line_id = line.map(|l| NVTModels::extract_line_id(&l.line_ref).unwrap_or(""));
+        NVTModels::get_next_vehicles_for_stop(stop_id, network)
+            .into_iter()
+            .filter(|v| line_id.is_none() || v.route_id.as_deref() == line_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Drop the real-time departures outside --window, `(from, to)` minutes from now
+    fn retain_time_window(network: &mut NetworkData, (from_min, to_min): (u32, u32)) {
+        let now = NVTModels::get_current_timestamp();
+        let (from_ts, to_ts) = (now + from_min as i64 * 60, now + to_min as i64 * 60);
+
+        for stop in &mut network.stops {
+            stop.real_time = NVTModels::filter_real_time_by_time_window(&stop.real_time, from_ts, to_ts)
+                .into_iter()
+                .cloned()
+                .collect();
+        }
+    }
+
+    /// Drop the real-time departures of lines that aren't night lines
+    fn retain_night_departures(network: &mut NetworkData) {
+        let night_routes: HashSet<String> = network.lines.iter()
+            .filter(|l| l.is_night_service)
+            .filter_map(|l| NVTModels::extract_line_id(&l.line_ref).map(str::to_string))
+            .collect();
+
+        for stop in &mut network.stops {
+            stop.real_time.retain(|v| v.route_id.as_ref().is_some_and(|r| night_routes.contains(r)));
+        }
+    }
+
+    /// Re-print the departure board of --stop every --interval seconds until Ctrl+C.
+    /// In JSON mode each update is one `{"updated_at", "arrivals"}` record prefixed with U+001E.
+    fn handle_watch(args: &Args, config: &NVTConfig) {
+        let json = args.format == OutputFormat::Json;
+        let report_error = |message: &str| {
+            if json {
+                NVTViews::json_error(message);
+            } else {
+                eprintln!("✗ {}", message);
+            }
+        };
+
+        let running = Arc::new(AtomicBool::new(true));
+        let handler_flag = Arc::clone(&running);
+        if let Err(e) = ctrlc::set_handler(move || handler_flag.store(false, Ordering::SeqCst)) {
+            report_error(&format!("Could not install Ctrl+C handler: {}", e));
+            return;
+        }
+
+        let mut cache = match Handle::current().block_on(NVTModels::initialize_cache(config)) {
+            Ok(data) => data,
+            Err(e) => {
+                report_error(&e.to_string());
+                return;
+            }
+        };
+        cache.display_lang = args.lang.clone();
+        cache.service_date = args.date;
+        cache.min_severity = args.min_severity;
+
+        let stop_query = args.stop.as_deref().unwrap_or_default();
+        let network = cache.to_network_data();
+        let Some(stop_id) = Self::resolve_stop(stop_query, &network).map(|s| s.stop_id.clone()) else {
+            report_error(&format!("Unknown stop: {}", stop_query));
+            return;
+        };
+        let line_ref = match args.line.as_deref() {
+            Some(line_query) => match Self::resolve_line(line_query, &network) {
+                Some(line) => Some(line.line_ref.clone()),
+                None => {
+                    report_error(&format!("Unknown line: {}", line_query));
+                    return;
+                }
+            },
+            None => None,
+        };
+        if args.night
+            && let Some(line) = line_ref.as_ref().and_then(|lr| network.lines.iter().find(|l| &l.line_ref == lr))
+            && !line.is_night_service
+        {
+            report_error(&format!("Line {} is not a night line", line.line_code));
+            return;
+        }
+
+        let interval = Duration::from_secs(args.interval.max(1));
+        let mut first_cycle = true;
+        while running.load(Ordering::SeqCst) {
+            if !first_cycle
+                && let Err(e) = Handle::current().block_on(NVTModels::smart_refresh(&mut cache, config))
+            {
+                report_error(&format!("Refresh failed, showing cached data: {}", e));
+            }
+            first_cycle = false;
+
+            let mut network = cache.to_network_data();
+            if args.night {
+                Self::retain_night_departures(&mut network);
+            }
+            if let Some(window) = args.window {
+                Self::retain_time_window(&mut network, window);
+            }
+            if json {
+                let line = line_ref.as_ref().and_then(|lr| network.lines.iter().find(|l| &l.line_ref == lr));
+                let vehicles = Self::departures_for(&stop_id, line, &network);
+                println!("\u{1e}{}", NVTViews::format_watch_json(&vehicles, &network));
+                let _ = io::stdout().flush();
+            } else {
+                Self::clear_screen();
+                Self::display_next_vehicles(&network, &line_ref, &Some(stop_id.clone()), args.board);
+                println!("\n⏱️  Refreshing every {} s, press Ctrl+C to stop", interval.as_secs());
+            }
+
+            let started = std::time::Instant::now();
+            while running.load(Ordering::SeqCst) && started.elapsed() < interval {
+                thread::sleep(Duration::from_millis(100));
+            }
+        }
+
+        if json {
+            eprintln!("\nBye!");
+        } else {
+            println!("\nBye!");
+        }
+    }
+
+    /// Find a stop by ID, then exact name, then partial name
+    fn resolve_stop<'a>(query: &str, network: &'a NetworkData) -> Option<&'a Stop> {
+        let query = query.trim();
+        let normalized = NVTModels::normalize_stop_id(query);
+        NVTModels::get_stop_by_id(&normalized, network)
+            .or_else(|| NVTModels::get_stop_by_name(query, network))
+            .or_else(|| {
+                network.stops.iter()
+                    .find(|s| s.stop_name.to_lowercase().contains(&query.to_lowercase()))
+            })
+    }
+
+    /// Show pinned stops and lines; pin/unpin the current selection or select a pinned stop
+    fn handle_favorites(
+        network: &NetworkData,
+        favorites: &mut Favorites,
+        selected_line: &mut Option<String>,
+        selected_stop: &mut Option<String>,
+    ) {
+        NVTViews::show_favorites(favorites, network);
+
+        match NVTViews::prompt_favorites_action().as_str() {
+            "" => return,
+            "s" => match selected_stop.as_deref() {
+                Some(stop_id) if favorites.has_stop(stop_id) => {
+                    favorites.remove_stop(stop_id);
+                    println!("✓ Stop removed from favorites");
+                }
+                Some(stop_id) => {
+                    favorites.add_stop(stop_id);
+                    println!("⭐ Stop added to favorites");
+                }
+                None => {
+                    NVTViews::no_stop_selected();
+                    return;
+                }
+            },
+            "l" => match selected_line.as_deref() {
+                Some(line_ref) if favorites.has_line(line_ref) => {
+                    favorites.remove_line(line_ref);
+                    println!("✓ Line removed from favorites");
+                }
+                Some(line_ref) => {
+                    favorites.add_line(line_ref);
+                    println!("⭐ Line added to favorites");
+                }
+                None => {
+                    NVTViews::line_required();
+                    return;
+                }
+            },
+            choice => {
+                let stop = choice.parse::<usize>().ok()
+                    .and_then(|n| n.checked_sub(1))
+                    .and_then(|i| favorites.stops.get(i))
+                    .and_then(|stop_id| NVTModels::get_stop_by_id(stop_id, network));
+                match stop {
+                    Some(stop) => {
+                        // Keep the line filter only if it serves the pinned stop
+                        if selected_line.as_ref().is_some_and(|lr| !stop.lines.contains(lr)) {
+                            *selected_line = None;
+                        }
+                        *selected_stop = Some(stop.stop_id.clone());
+                        NVTViews::show_stop_selected(stop, network);
+                    }
+                    None => println!("\n✗ Invalid choice '{}'", choice),
+                }
+                return;
+            }
+        }
+
+        if let Err(e) = favorites.save() {
+            eprintln!("⚠️  Could not save favorites: {}", e);
+        }
+    }
+
+    /// Plan a journey between two stops given with --from/--to, leaving now
+    fn handle_journey(from_query: &str, to_query: &str, date: Option<NaiveDate>, config: &NVTConfig) {
+        println!("\n🔄 Loading TBM network data...");
+
+        let cache = match Handle::current().block_on(NVTModels::initialize_cache(config)) {
+            Ok(data) => data,
+            Err(e) => {
+                NVTViews::network_error(&format!("{}", e));
+                return;
+            }
+        };
+        let mut network = cache.to_network_data();
+        network.service_date = date;
+
+        let Some(from) = Self::resolve_stop(from_query, &network) else {
+            NVTViews::invalid_stop(from_query);
+            return;
+        };
+        let Some(to) = Self::resolve_stop(to_query, &network) else {
+            NVTViews::invalid_stop(to_query);
+            return;
+        };
+
+        let now = NVTModels::get_current_timestamp();
+        let journey = NVTModels::plan_journey(&from.stop_id, &to.stop_id, now, config.walking_speed_kmh, &network, &network.gtfs);
+        NVTViews::show_journey(journey.as_ref(), from, to, now, &network);
+    }
+
+    /// List the stops reachable from --isochrone within --minutes, leaving now
+    fn handle_isochrone(from_query: &str, max_minutes: u32, date: Option<NaiveDate>, config: &NVTConfig) {
+        println!("\n🔄 Loading TBM network data...");
+
+        let cache = match Handle::current().block_on(NVTModels::initialize_cache(config)) {
+            Ok(data) => data,
+            Err(e) => {
+                NVTViews::network_error(&format!("{}", e));
+                return;
+            }
+        };
+        let mut network = cache.to_network_data();
+        network.service_date = date;
+
+        let Some(from) = Self::resolve_stop(from_query, &network) else {
+            NVTViews::invalid_stop(from_query);
+            return;
+        };
+
+        let now = NVTModels::get_current_timestamp();
+        let reachable = NVTModels::reachable_stops(&from.stop_id, max_minutes, now, config.walking_speed_kmh, &network, &network.gtfs);
+        NVTViews::show_isochrone(from, max_minutes, &reachable, &network);
+    }
+
+    /// Find a line by code or name, as in interactive line selection
+    fn resolve_line<'a>(query: &str, network: &'a NetworkData) -> Option<&'a Line> {
+        let query = query.trim();
+        network.lines.iter().find(|l| {
+            l.line_code.eq_ignore_ascii_case(query) || l.line_name.eq_ignore_ascii_case(query)
+        })
+    }
+
+    /// Print per-line on-time rates from an arrival log, optionally over the last `days` days only
+    fn handle_report(log_path: &Path, days: Option<u32>) {
+        match NVTModels::compute_on_time_rates(log_path, Self::log_cutoff(days)) {
+            Ok(rates) => NVTViews::show_reliability_report(&rates),
+            Err(e) => eprintln!("✗ {}", e),
+        }
+    }
+
+    /// Print per-line mean ETA errors from an arrival log, optionally over the last `days` days only
+    fn handle_accuracy_report(log_path: &Path, days: Option<u32>) {
+        match NVTModels::compute_eta_maes(log_path, Self::log_cutoff(days)) {
+            Ok(maes) => NVTViews::show_eta_accuracy_report(&maes),
+            Err(e) => eprintln!("✗ {}", e),
+        }
+    }
+
+    /// Timestamp `days` days ago, the start of a --days report window
+    fn log_cutoff(days: Option<u32>) -> Option<i64> {
+        days.map(|days| NVTModels::get_current_timestamp() - days as i64 * 86_400)
+    }
+
+    /// List stops around a coordinate given with --near
+    fn handle_near(near: &NearQuery, accessible_only: bool, config: &NVTConfig) {
+        println!("\n🔄 Loading TBM network data...");
+
+        let cache = match Handle::current().block_on(NVTModels::initialize_cache(config)) {
+            Ok(data) => data,
+            Err(e) => {
+                NVTViews::network_error(&format!("{}", e));
+                return;
+            }
+        };
+        let network = cache.to_network_data();
+
+        let mut nearby = NVTModels::nearest_stops(near.latitude, near.longitude, near.radius_m, &network);
+        if accessible_only {
+            nearby.retain(|(stop, _)| stop.wheelchair_accessible);
+        }
+        NVTViews::show_nearest_stops(&nearby, near, &network);
+
+        if !nearby.is_empty() {
+            let radius_km = near.radius_m / 1000.0;
+            let (min_lat, max_lat, min_lon, max_lon) = NVTModels::bounding_box(near.latitude, near.longitude, radius_km);
+            let mut in_box = NVTModels::get_stops_in_bbox(min_lat, max_lat, min_lon, max_lon, &network);
+            if accessible_only {
+                in_box.retain(|stop| stop.wheelchair_accessible);
+            }
+            NVTViews::show_stop_map_ascii(&in_box, near.latitude, near.longitude, radius_km, &network);
+        }
+    }
+
+    /// Export stops and arrivals to Parquet files in the given directory
+    fn handle_export_parquet(dir: &Path, config: &NVTConfig) {
+        println!("\n🔄 Loading TBM network data for export...");
+
+        let cache = match Handle::current().block_on(NVTModels::initialize_cache(config)) {
+            Ok(data) => data,
+            Err(e) => {
+                NVTViews::network_error(&format!("{}", e));
+                return;
+            }
+        };
+        let network = cache.to_network_data();
+
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            eprintln!("✗ Could not create export directory {:?}: {}", dir, e);
+            return;
+        }
+
+        let stops_path = dir.join("stops.parquet");
+        let arrivals_path = dir.join("arrivals.parquet");
+
+        let result = NVTModels::export_stops_parquet(&network, &stops_path)
+            .and_then(|_| NVTModels::export_arrivals_parquet(&network, &arrivals_path));
+
+        match result {
+            Ok(_) => NVTViews::show_export_complete(&[stops_path, arrivals_path]),
+            Err(e) => eprintln!("✗ Export failed: {}", e),
+        }
+    }
+
+    /// Write the stops and/or vehicles GeoJSON files that were asked for
+    fn handle_export_geojson(stops_path: Option<&Path>, vehicles_path: Option<&Path>, config: &NVTConfig) {
+        println!("\n🔄 Loading TBM network data for export...");
+
+        let cache = match Handle::current().block_on(NVTModels::initialize_cache(config)) {
+            Ok(data) => data,
+            Err(e) => {
+                NVTViews::network_error(&format!("{}", e));
+                return;
+            }
+        };
+        let network = cache.to_network_data();
+
+        let exports = [
+            (stops_path, NVTModels::export_stops_geojson as fn(&NetworkData) -> String),
+            (vehicles_path, NVTModels::export_vehicles_geojson),
+        ];
+
+        let mut written = Vec::new();
+        for (path, export) in exports {
+            let Some(path) = path else { continue };
+            if let Err(e) = std::fs::write(path, export(&network)) {
+                eprintln!("✗ Export failed: could not write {:?}: {}", path, e);
+                return;
+            }
+            written.push(path.to_path_buf());
+        }
+
+        NVTViews::show_export_complete(&written);
+    }
+
+    /// Write the arrivals of one stop, or of every stop, to a CSV file
+    fn handle_export_csv(path: &Path, stop_query: Option<&str>, config: &NVTConfig) {
+        println!("\n🔄 Loading TBM network data for export...");
+
+        let cache = match Handle::current().block_on(NVTModels::initialize_cache(config)) {
+            Ok(data) => data,
+            Err(e) => {
+                NVTViews::network_error(&format!("{}", e));
+                return;
+            }
+        };
+        let network = cache.to_network_data();
+
+        let stops: Vec<&Stop> = match stop_query {
+            Some(query) => match Self::resolve_stop(query, &network) {
+                Some(stop) => vec![stop],
+                None => {
+                    NVTViews::invalid_stop(query);
+                    return;
+                }
+            },
+            None => network.stops.iter().collect(),
+        };
+
+        let file = match std::fs::File::create(path) {
+            Ok(file) => file,
+            Err(e) => {
+                eprintln!("✗ Export failed: could not create {:?}: {}", path, e);
+                return;
+            }
+        };
+
+        match NVTModels::export_arrivals_csv(&stops, &network, io::BufWriter::new(file)) {
+            Ok(_) => NVTViews::show_export_complete(&[path.to_path_buf()]),
+            Err(e) => eprintln!("✗ Export failed: {}", e),
+        }
+    }
+
+    /// Select from a list of items
+    fn select_from_list<'a>(items: &[&'a Stop]) -> Option<&'a Stop> {
+        print!("\n➜ Enter number (1-{}): ", items.len());
+        io::stdout().flush().unwrap();
+
+        let input = Self::read_input();
+
+        match input.trim().parse::<usize>() {
+            Ok(num) if num > 0 && num <= items.len() => Some(items[num - 1]),
+            _ => {
+                println!("✗ Invalid selection. Please enter a number between 1 and {}", items.len());
+                None
+            }
+        }
+    }
+
+    /// Pick one platform of a station; 'a' shows the departures of every platform first
+    fn select_platform<'a>(platforms: &[&'a Stop], network: &NetworkData) -> Option<&'a Stop> {
+        loop {
+            print!("\n➜ Enter number (1-{}) or 'a' for all platforms: ", platforms.len());
+            io::stdout().flush().unwrap();
+
+            let input = Self::read_input();
+            if !input.trim().eq_ignore_ascii_case("a") {
+                return match input.trim().parse::<usize>() {
+                    Ok(num) if num > 0 && num <= platforms.len() => Some(platforms[num - 1]),
+                    _ => {
+                        println!("✗ Invalid selection. Please enter a number between 1 and {}", platforms.len());
+                        None
+                    }
+                };
+            }
+
+            for stop in platforms {
+                let vehicles = NVTModels::get_next_vehicles_for_stop(&stop.stop_id, network);
+                NVTViews::show_departure_board(stop, &vehicles, network);
+            }
+        }
+    }
+
+    /// Next 3 arrivals at the stop with the most vehicles per hour right now
+    fn handle_busiest_stop(network: &NetworkData) {
+        let Some((stop, per_hour)) = NVTModels::detect_high_frequency_stops(network, 0).into_iter().next() else {
+            println!("\n✗ No stop has enough upcoming arrivals to rank");
+            return;
+        };
+
+        let vehicles = NVTModels::get_next_vehicles_for_stop(&stop.stop_id, network);
+        println!("\n🚏 Busiest stop right now: ~{} vehicles/hour", per_hour);
+        NVTViews::show_departure_board(stop, &vehicles[..vehicles.len().min(3)], network);
+    }
+
+    /// Prompt for two stops and show the lines they share
+    fn handle_compare_stops(network: &NetworkData) {
+        let mut stops = Vec::new();
+        for label in ["first", "second"] {
+            println!("\n📍 Enter the {} stop", label);
+            let query = NVTViews::prompt_stop();
+            let Some(stop) = Self::resolve_stop(&query, network) else {
+                NVTViews::invalid_stop(&query);
+                return;
+            };
+            stops.push(stop);
+        }
+
+        let comparison = NVTModels::compare_stops(stops[0], stops[1], network);
+        NVTViews::show_stop_comparison(&comparison, stops[0], stops[1], network);
+    }
+
+    /// Read input from stdin with error handling
+    fn read_input() -> String {
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(_) => input,
+            Err(e) => {
+                eprintln!("⚠️  Error reading input: {}", e);
+                String::new()
+            }
+        }
+    }
+
+    /// Clear screen (cross-platform)
     fn clear_screen() {
         // ANSI escape sequence to clear screen and move cursor to top-left
         print!("\x1B[2J\x1B[1;1H");
@@ -441,11 +1815,6 @@ impl NVTControllers {
     // Helper Functions
     // ========================================================================
 
-    /// Check if real-time info is from scheduled data
-    pub fn is_scheduled(rt: &RealTimeInfo) -> bool {
-        rt.vehicle_id == "scheduled" || rt.vehicle_id == "fallback_trip_update"
-    }
-
     /// Calculate minutes until arrival
     pub fn minutes_until_arrival(timestamp: i64, now: i64) -> i64 {
         (timestamp - now) / 60
@@ -477,3 +1846,117 @@ impl NVTControllers {
         network.lines.iter().any(|l| l.line_ref == line_ref)
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// Quinconces, its river-side platform and Gambetta
+    fn network() -> NetworkData {
+        let mut cache = CachedNetworkData::new();
+        let stop = |id: &str, name: &str| (id.to_string(), name.to_string(), 44.84, -0.57, vec!["TBM:Line:C:LOC".to_string()]);
+        cache.stops_metadata = vec![
+            stop("quinc", "Quinconces"),
+            stop("quincfl", "Quinconces Fleuve"),
+            stop("quinc2", "Quinconces"),
+            stop("gambetta", "Gambetta"),
+        ];
+        cache.lines_metadata = vec![("TBM:Line:C:LOC".to_string(), "Tram C".to_string(), "C".to_string(), Vec::new())];
+        (cache.stop_to_lines, cache.line_to_stops) = NVTModels::build_line_indices(&cache.stops_metadata);
+        cache.to_network_data()
+    }
+
+    fn arrival(trip_id: &str, timestamp: i64, cancelled: bool) -> RealTimeInfo {
+        RealTimeInfo {
+            vehicle_id: format!("tram-{}", trip_id),
+            trip_id: trip_id.to_string(),
+            route_id: Some("C".to_string()),
+            direction_id: Some(0),
+            destination: None,
+            latitude: 44.84,
+            longitude: -0.57,
+            stop_id: Some("quinc".to_string()),
+            timestamp: Some(timestamp),
+            delay: Some(0),
+            occupancy: None,
+            cancelled,
+            speed_kmh: None,
+        }
+    }
+
+    #[test]
+    fn board_arrivals_are_the_rows_shown_without_cancelled_trips() {
+        let mut network = network();
+        let now = NVTModels::get_current_timestamp();
+        let stop = &mut network.stops[0];
+        stop.real_time = (0..12).map(|i| arrival(&format!("C-{}", i), now + 300 + i * 300, false)).collect();
+        stop.cancelled_arrivals = vec![arrival("C-x", now + 100, true), arrival("C-y", now + 700, true)];
+        let selected_stop = Some(stop.stop_id.clone());
+
+        let shown = NVTControllers::display_next_vehicles(&network, &None, &selected_stop, true);
+        let trips: Vec<&str> = shown.iter().map(|rt| rt.trip_id.as_str()).collect();
+        assert_eq!(trips, vec!["C-0", "C-1", "C-2", "C-3", "C-4", "C-5", "C-6", "C-7"]);
+
+        let shown = NVTControllers::display_next_vehicles(&network, &None, &selected_stop, false);
+        assert_eq!(shown.len(), 10);
+        assert!(shown.iter().all(|rt| !rt.cancelled));
+    }
+
+    /// Feed `keys` to a fresh prompt as the autocomplete loop does, returning the confirmed text
+    fn type_keys(keys: &[KeyCode], network: &NetworkData) -> Option<String> {
+        let mut prompt = StopAutocomplete::default();
+        keys.iter().find_map(|&key| {
+            let matches = prompt.matches(network);
+            prompt.handle_key(KeyEvent::from(key), &matches)
+        })
+    }
+
+    fn text(s: &str) -> Vec<KeyCode> {
+        s.chars().map(KeyCode::Char).collect()
+    }
+
+    #[test]
+    fn typing_quinc_then_enter_selects_quinconces() {
+        let keys = [text("Quinc"), vec![KeyCode::Enter]].concat();
+        assert_eq!(type_keys(&keys, &network()).as_deref(), Some("Quinconces"));
+    }
+
+    #[test]
+    fn matches_are_distinct_names_containing_the_input() {
+        let network = network();
+        let prompt = StopAutocomplete { input: "quinc".to_string(), selected: 0 };
+        assert_eq!(prompt.matches(&network), vec!["Quinconces", "Quinconces Fleuve"]);
+        assert!(StopAutocomplete::default().matches(&network).is_empty());
+    }
+
+    #[test]
+    fn arrow_down_moves_the_highlight_and_stops_at_the_last_match() {
+        let keys = [text("quinc"), vec![KeyCode::Down, KeyCode::Down, KeyCode::Enter]].concat();
+        assert_eq!(type_keys(&keys, &network()).as_deref(), Some("Quinconces Fleuve"));
+
+        let keys = [text("quinc"), vec![KeyCode::Down, KeyCode::Up, KeyCode::Enter]].concat();
+        assert_eq!(type_keys(&keys, &network()).as_deref(), Some("Quinconces"));
+    }
+
+    #[test]
+    fn tab_completes_and_typing_continues() {
+        let keys = [text("gamb"), vec![KeyCode::Tab], text(" "), vec![KeyCode::Backspace, KeyCode::Enter]].concat();
+        assert_eq!(type_keys(&keys, &network()).as_deref(), Some("Gambetta"));
+    }
+
+    #[test]
+    fn enter_without_match_confirms_the_typed_text() {
+        let keys = [text(" Victoire "), vec![KeyCode::Enter]].concat();
+        assert_eq!(type_keys(&keys, &network()).as_deref(), Some("Victoire"));
+    }
+
+    #[test]
+    fn escape_and_ctrl_c_abandon_the_prompt() {
+        let network = network();
+        assert_eq!(type_keys(&[KeyCode::Char('q'), KeyCode::Esc], &network).as_deref(), Some(""));
+
+        let mut prompt = StopAutocomplete::default();
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(prompt.handle_key(ctrl_c, &[]).as_deref(), Some(""));
+    }
+}
@@ -0,0 +1,87 @@
+// User favorites (pinned stops and lines) for TBM Next Vehicle
+// Kept in ~/.config/tbm_nvt/favorites.json next to config.toml: this is user data,
+// so clearing the network caches (--cache-clear) never touches it.
+
+use crate::nvt_models::{NVTError, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Favorites {
+    /// Pinned stop_ids, in the order they were added
+    pub stops: Vec<String>,
+    /// Pinned line_refs, in the order they were added
+    pub lines: Vec<String>,
+}
+
+impl Favorites {
+    pub fn path() -> PathBuf {
+        let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+        path.push("tbm_nvt");
+        path.push("favorites.json");
+        path
+    }
+
+    /// Load the favorites file, or start empty if there is none or it can't be read
+    pub fn load() -> Self {
+        let path = Self::path();
+        if !path.exists() {
+            return Favorites::default();
+        }
+
+        match fs::read_to_string(&path).map(|contents| serde_json::from_str::<Favorites>(&contents)) {
+            Ok(Ok(favorites)) => favorites,
+            Ok(Err(e)) => {
+                eprintln!("⚠️  Invalid favorites file {:?} ({}), starting empty", path, e);
+                Favorites::default()
+            }
+            Err(e) => {
+                eprintln!("⚠️  Could not read favorites {:?} ({}), starting empty", path, e);
+                Favorites::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| NVTError::file(format!("Failed to create {:?}", dir)).with_source(e))?;
+        }
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| NVTError::file("Failed to serialize favorites").with_source(e))?;
+        fs::write(&path, json)
+            .map_err(|e| NVTError::file("Failed to write favorites").with_source(e))
+    }
+
+    pub fn add_stop(&mut self, stop_id: &str) {
+        if !self.has_stop(stop_id) {
+            self.stops.push(stop_id.to_string());
+        }
+    }
+
+    pub fn remove_stop(&mut self, stop_id: &str) {
+        self.stops.retain(|s| s != stop_id);
+    }
+
+    pub fn has_stop(&self, stop_id: &str) -> bool {
+        self.stops.iter().any(|s| s == stop_id)
+    }
+
+    pub fn add_line(&mut self, line_ref: &str) {
+        if !self.has_line(line_ref) {
+            self.lines.push(line_ref.to_string());
+        }
+    }
+
+    pub fn remove_line(&mut self, line_ref: &str) {
+        self.lines.retain(|l| l != line_ref);
+    }
+
+    pub fn has_line(&self, line_ref: &str) -> bool {
+        self.lines.iter().any(|l| l == line_ref)
+    }
+}
@@ -8,19 +8,46 @@
 // - GTFS-RT Alerts: https://bdx.mecatran.com/utw/ws/gtfsfeed/alerts/bordeaux
 // - GTFS-RT Trip Updates: https://bdx.mecatran.com/utw/ws/gtfsfeed/realtime/bordeaux
 
-use reqwest::blocking;
+use lazy_static::lazy_static;
+use crate::nvt_config::NVTConfig;
+use crate::nvt_mqtt::NVTMqttPublisher;
+use crate::nvt_persistence::NVTPersistence;
+use crate::nvt_views::NVTViews;
+use unicode_normalization::UnicodeNormalization;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::cmp::Reverse;
 use gtfs_rt::FeedMessage;
 use prost::Message;
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Timelike, Utc, Weekday};
 use chrono_tz::Europe::Paris;
-use std::io::Read;
+use regex::RegexBuilder;
+use sha2::{Digest, Sha256};
+use std::io::{Read, Seek, Write};
 use std::io::Cursor;
 use zip::ZipArchive;
-use std::time::{SystemTime, UNIX_EPOCH};
-use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::path::{Path, PathBuf};
 use std::fs;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use petgraph::stable_graph::{NodeIndex, StableGraph};
+use petgraph::visit::NodeIndexable;
+use parquet::data_type::{BoolType, ByteArray, ByteArrayType, DoubleType, Int32Type, Int64Type};
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+
+/// Progress message on stdout, silenced in quiet mode (e.g. `--format json`)
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !$crate::nvt_models::NVTModels::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+pub(crate) use status;
 
 // ============================================================================
 // Data Structures
@@ -51,6 +78,13 @@ pub struct RealTimeInfo {
     pub stop_id: Option<String>,
     pub timestamp: Option<i64>,
     pub delay: Option<i32>,
+    /// GTFS-RT OccupancyStatus ordinal, see `NVTModels::occupancy_label`
+    pub occupancy: Option<u32>,
+    /// Trip reported as CANCELED by the trip updates feed
+    pub cancelled: bool,
+    /// Estimated from the last two GPS fixes when `show_speed` is enabled
+    #[serde(default)]
+    pub speed_kmh: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +96,49 @@ pub struct Stop {
     pub lines: Vec<String>,
     pub alerts: Vec<AlertInfo>,
     pub real_time: Vec<RealTimeInfo>,
+    /// Arrivals of cancelled trips, kept apart from `real_time` so views can flag them
+    pub cancelled_arrivals: Vec<RealTimeInfo>,
+    pub hub_score: f64,
+    /// Kind of location from the GTFS location_type (see `NVTModels::classify_stop_type`)
+    #[serde(default)]
+    pub stop_type: StopType,
+    /// GTFS wheelchair_boarding is 1 (see `NVTModels::stops_accessible`)
+    #[serde(default)]
+    pub wheelchair_accessible: bool,
+}
+
+/// Kind of stop location, from the GTFS location_type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StopType {
+    Platform,
+    Station,
+    Entrance,
+    GenericNode,
+    BoardingArea,
+    #[default]
+    Unknown,
+}
+
+impl StopType {
+    /// Map a GTFS location_type (0 stop/platform, 1 station, 2 entrance/exit, 3 generic node, 4 boarding area)
+    pub fn from_location_type(location_type: u8) -> Self {
+        match location_type {
+            0 => StopType::Platform,
+            1 => StopType::Station,
+            2 => StopType::Entrance,
+            3 => StopType::GenericNode,
+            4 => StopType::BoardingArea,
+            _ => StopType::Unknown,
+        }
+    }
+
+    /// Icon shown next to the stop name in stop lists
+    pub fn icon(self) -> &'static str {
+        match self {
+            StopType::Station => "🏢",
+            _ => "🚏",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,23 +150,560 @@ pub struct Line {
     pub alerts: Vec<AlertInfo>,
     pub real_time: Vec<RealTimeInfo>,
     pub color: String,
+    /// Only runs at night (see `NVTModels::is_night_service`)
+    pub is_night_service: bool,
+    /// Vehicle type from the GTFS route_type (see `NVTModels::line_type`)
+    pub line_type: LineType,
+    /// Name of the stop where direction 0 starts (see `NVTModels::detect_terminal_stops`)
+    pub terminus_a: Option<String>,
+    /// Name of the stop where direction 0 ends
+    pub terminus_b: Option<String>,
+    /// Some direction ends where it starts (see `NVTModels::is_circular_line`)
+    #[serde(default)]
+    pub is_circular: bool,
+}
+
+/// Vehicle type of a line, from the GTFS route_type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LineType {
+    Tram,
+    Bus,
+    Trolleybus,
+    Coach,
+    #[default]
+    Unknown,
+}
+
+impl LineType {
+    /// Map a GTFS route_type, basic (0, 3, 11) or extended (200-209 coach, 700-716 bus, 800 trolleybus, 900-906 tram)
+    pub fn from_route_type(route_type: u32) -> Self {
+        match route_type {
+            0 | 900..=906 => LineType::Tram,
+            3 | 700..=716 => LineType::Bus,
+            11 | 800 => LineType::Trolleybus,
+            200..=209 => LineType::Coach,
+            _ => LineType::Unknown,
+        }
+    }
+}
+
+/// Static GTFS departures per stop: stop_id -> [(route_id, direction_id, seconds_since_midnight, service_id, trip_id)]
+pub type ScheduledDepartures = HashMap<String, Vec<(String, u32, u32, String, String)>>;
+
+/// Unusually long scheduled wait: (last departure before the gap, gap length)
+pub type ServiceGap = (i64, Duration);
+
+/// Static GTFS stop order: route_id -> direction_id -> [stop_id]
+pub type StopSequences = HashMap<String, HashMap<u32, Vec<String>>>;
+
+/// Static GTFS route paths: shape_id -> [(lat, lon)] in shape_pt_sequence order
+pub type RouteShapes = HashMap<String, Vec<(f64, f64)>>;
+
+/// SIRI-Lite stop record: (stop_id, stop_name, latitude, longitude, line_refs)
+pub type StopMetadata = (String, String, f64, f64, Vec<String>);
+
+/// SIRI-Lite line record: (line_ref, line_name, line_code, [(direction_ref, place_name)])
+pub type LineMetadata = (String, String, String, Vec<(String, String)>);
+
+/// Position of a stop on a vehicle's trip relative to the vehicle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StopVisitStatus {
+    Departed,
+    Current,
+    Upcoming,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JourneyStop {
+    pub stop_id: String,
+    pub stop_name: String,
+    pub arrival_time: Option<i64>,
+    pub delay: Option<i32>,
+    pub status: StopVisitStatus,
+    pub is_terminus: bool,
+}
+
+/// How a journey leg is travelled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LegMode {
+    #[default]
+    Ride,
+    Walk,
+}
+
+/// One leg of a planned journey: a ride from boarding to alighting the same vehicle,
+/// or a walk between two nearby stops
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Leg {
+    #[serde(default)]
+    pub mode: LegMode,
+    pub board_stop_id: String,
+    pub alight_stop_id: String,
+    /// Empty for walk legs
+    pub route_id: String,
+    pub depart_time: i64,
+    pub arrive_time: i64,
+    /// Transfers made before boarding this leg (walks don't count)
+    pub transfers: u32,
+}
+
+/// Earliest-arrival itinerary between two stops, see `NVTModels::plan_journey`.
+/// Walks between nearby stops are legs of their own, with `LegMode::Walk`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Journey {
+    pub legs: Vec<Leg>,
+}
+
+impl Journey {
+    pub fn depart_time(&self) -> Option<i64> {
+        self.legs.first().map(|leg| leg.depart_time)
+    }
+
+    pub fn arrive_time(&self) -> Option<i64> {
+        self.legs.last().map(|leg| leg.arrive_time)
+    }
+
+    /// Vehicle changes, walks between stops not counted
+    pub fn transfers(&self) -> u32 {
+        self.legs.iter().filter(|leg| leg.mode == LegMode::Ride).count().saturating_sub(1) as u32
+    }
+}
+
+/// How the journey planner reached a stop
+#[derive(Debug, Clone, Copy)]
+enum JourneyHop<'a> {
+    Ride { from: &'a str, route_id: &'a str, direction_id: u32, depart: i64 },
+    Walk { from: &'a str },
+}
+
+impl<'a> JourneyHop<'a> {
+    fn from(&self) -> &'a str {
+        match self {
+            JourneyHop::Ride { from, .. } | JourneyHop::Walk { from } => from,
+        }
+    }
+}
+
+/// Earliest arrival time at each stop reached by `NVTModels::earliest_arrivals`,
+/// and how it was reached
+#[derive(Default)]
+struct JourneySearch<'a> {
+    arrivals: HashMap<&'a str, i64>,
+    previous: HashMap<&'a str, JourneyHop<'a>>,
+    /// Trip ridden to reach each stop reached by a ride
+    trips: HashMap<&'a str, String>,
+}
+
+/// Departure times per stop and route direction for the journey planner, loaded on demand.
+/// Real-time arrivals are used where available and the static timetable after the last of them.
+struct JourneyTimetable<'a> {
+    network: &'a NetworkData,
+    cache: &'a GTFSCache,
+    /// (service day, Unix time of the midnight its times are counted from)
+    service_days: Vec<(NaiveDate, i64)>,
+    departures: HashMap<String, HashMap<(String, u32), TripDepartures>>,
+}
+
+/// Departure times of a route direction at a stop, sorted, with the trip making each
+type TripDepartures = Vec<(i64, String)>;
+
+impl<'a> JourneyTimetable<'a> {
+    fn new(at_time: i64, network: &'a NetworkData, cache: &'a GTFSCache) -> Self {
+        let day = Utc.timestamp_opt(at_time, 0).single()
+            .map(|dt| dt.with_timezone(&Paris).date_naive())
+            .unwrap_or_else(|| NVTModels::service_date(None));
+        // A --date override picks which timetable runs, but times stay anchored to at_time's day
+        let service_day = network.service_date.unwrap_or(day);
+
+        // Yesterday's service covers trips past midnight, tomorrow's late-evening journeys
+        let service_days = [
+            day.pred_opt().zip(service_day.pred_opt()),
+            Some((day, service_day)),
+            day.succ_opt().zip(service_day.succ_opt()),
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|(calendar_day, service)| {
+            let midnight = Paris.from_local_datetime(&calendar_day.and_hms_opt(0, 0, 0)?).earliest()?;
+            Some((service, midnight.timestamp()))
+        })
+        .collect();
+
+        JourneyTimetable {
+            network,
+            cache,
+            service_days,
+            departures: HashMap::new(),
+        }
+    }
+
+    /// Departures of a route direction from a stop at or after `after`, earliest first
+    fn departures_from(&mut self, stop_id: &str, route_id: &str, direction_id: u32, after: i64) -> &[(i64, String)] {
+        if !self.departures.contains_key(stop_id) {
+            let departures = self.load_stop(stop_id);
+            self.departures.insert(stop_id.to_string(), departures);
+        }
+
+        let Some(times) = self.departures.get(stop_id)
+            .and_then(|by_route| by_route.get(&(route_id.to_string(), direction_id)))
+        else {
+            return &[];
+        };
+        &times[times.partition_point(|(t, _)| *t < after)..]
+    }
+
+    /// First departure of a route direction from a stop at or after `after`, and its trip
+    fn next_departure(&mut self, stop_id: &str, route_id: &str, direction_id: u32, after: i64) -> Option<(i64, String)> {
+        self.departures_from(stop_id, route_id, direction_id, after).first().cloned()
+    }
+
+    /// When `trip_id` serves a stop at or after `after`, None if it isn't known there
+    fn trip_time(&mut self, stop_id: &str, route_id: &str, direction_id: u32, trip_id: &str, after: i64) -> Option<i64> {
+        self.departures_from(stop_id, route_id, direction_id, after)
+            .iter()
+            .find(|(_, trip)| trip == trip_id)
+            .map(|(t, _)| *t)
+    }
+
+    fn load_stop(&self, stop_id: &str) -> HashMap<(String, u32), TripDepartures> {
+        let mut departures: HashMap<(String, u32), TripDepartures> = HashMap::new();
+
+        let live = NVTModels::get_stop_by_id(stop_id, self.network).into_iter().flat_map(|stop| &stop.real_time);
+        for rt in live.filter(|rt| !rt.cancelled && rt.vehicle_id != "static") {
+            if let (Some(route_id), Some(direction_id), Some(ts)) = (&rt.route_id, rt.direction_id, rt.timestamp) {
+                departures.entry((route_id.clone(), direction_id)).or_default().push((ts, rt.trip_id.clone()));
+            }
+        }
+
+        // Skip the timetable entries of trips the live data already covers,
+        // so a late vehicle isn't counted twice
+        let live_trips: HashSet<String> = departures.values().flatten().map(|(_, trip)| trip.clone()).collect();
+
+        for (service_day, midnight) in &self.service_days {
+            for (route_id, direction_id, seconds, _, trip_id) in NVTModels::static_departures_on(stop_id, *service_day, self.cache) {
+                if live_trips.contains(trip_id) {
+                    continue;
+                }
+                departures.entry((route_id.clone(), *direction_id))
+                    .or_default()
+                    .push((midnight + *seconds as i64, trip_id.clone()));
+            }
+        }
+
+        for times in departures.values_mut() {
+            times.sort_unstable();
+            times.dedup();
+        }
+        departures
+    }
+}
+
+/// A trip's arrival at one stop, as reported by the trip updates feed
+#[derive(Debug, Clone)]
+struct TripUpdateArrival {
+    trip_id: String,
+    route_id: Option<String>,
+    direction_id: Option<u32>,
+    delay: Option<i32>,
+    time: Option<i64>,
+    cancelled: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct NetworkData {
     pub stops: Vec<Stop>,
     pub lines: Vec<Line>,
+    pub gtfs: Arc<GTFSCache>,
+    pub display_lang: Option<String>,
+    /// Service day used for static timetables instead of today (--date)
+    pub service_date: Option<NaiveDate>,
+    /// stop_id -> position in `stops`, see `NVTModels::get_stop_by_id`
+    pub stop_index: HashMap<String, usize>,
+    /// line_ref -> stop_ids of the stops it serves
+    pub line_to_stops: HashMap<String, Vec<String>>,
+    /// Positions of `stops` for bounding-box queries, see `NVTModels::get_stops_in_bbox`
+    pub stop_grid: SpatialGrid,
+    /// See `CachedNetworkData::network_graph`
+    pub network_graph: Option<Arc<StableGraph<String, u32>>>,
+    /// See `CachedNetworkData::graph_diameter`
+    pub graph_diameter: Arc<OnceLock<Option<u32>>>,
+}
+
+/// Stop positions bucketed into square lat/lon cells, so that bounding-box
+/// queries only look at the cells they overlap instead of every stop
+#[derive(Debug, Clone)]
+pub struct SpatialGrid {
+    cell_size_deg: f64,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    positions: Vec<(f64, f64)>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size_deg: f64) -> Self {
+        SpatialGrid {
+            cell_size_deg,
+            cells: HashMap::new(),
+            positions: Vec::new(),
+        }
+    }
+
+    fn cell(&self, lat: f64, lon: f64) -> (i64, i64) {
+        ((lat / self.cell_size_deg).floor() as i64, (lon / self.cell_size_deg).floor() as i64)
+    }
+
+    /// Add a stop; its index is the number of stops inserted before it
+    pub fn insert(&mut self, stop: &Stop) {
+        let cell = self.cell(stop.latitude, stop.longitude);
+        self.cells.entry(cell).or_default().push(self.positions.len());
+        self.positions.push((stop.latitude, stop.longitude));
+    }
+
+    /// Indices of the stops inside the box, bounds included, in insertion order
+    pub fn query_bbox(&self, min_lat: f64, max_lat: f64, min_lon: f64, max_lon: f64) -> Vec<usize> {
+        let (min_row, min_col) = self.cell(min_lat, min_lon);
+        let (max_row, max_col) = self.cell(max_lat, max_lon);
+        let in_range = |&(row, col): &(i64, i64)| (min_row..=max_row).contains(&row) && (min_col..=max_col).contains(&col);
+
+        // A box wider than the occupied area is cheaper to answer from the occupied cells
+        let box_cells = (max_row - min_row + 1).saturating_mul(max_col - min_col + 1);
+        let candidates: Vec<usize> = if box_cells > self.cells.len() as i64 {
+            self.cells.iter()
+                .filter(|(cell, _)| in_range(cell))
+                .flat_map(|(_, indices)| indices.iter().copied())
+                .collect()
+        } else {
+            (min_row..=max_row)
+                .flat_map(|row| (min_col..=max_col).map(move |col| (row, col)))
+                .filter_map(|cell| self.cells.get(&cell))
+                .flatten()
+                .copied()
+                .collect()
+        };
+
+        let mut indices: Vec<usize> = candidates.into_iter()
+            .filter(|&idx| {
+                let (lat, lon) = self.positions[idx];
+                (min_lat..=max_lat).contains(&lat) && (min_lon..=max_lon).contains(&lon)
+            })
+            .collect();
+        indices.sort_unstable();
+        indices
+    }
+}
+
+/// Lines served by two stops, see `NVTModels::compare_stops`. Lines are line_refs.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StopComparison {
+    pub common_lines: Vec<String>,
+    pub only_a: Vec<String>,
+    pub only_b: Vec<String>,
+    pub distance_m: f64,
+}
+
+/// Route running far fewer vehicles than scheduled, see `NVTModels::detect_service_disruption`
+#[derive(Debug, Clone, Serialize)]
+pub struct DisruptionReport {
+    pub route_id: String,
+    /// Trips the static timetable has on the road right now
+    pub expected_vehicles: usize,
+    /// Distinct trips of the route in the vehicle positions feed
+    pub observed_vehicles: usize,
+    /// Share of the route's stops with a scheduled departure, 0.0–1.0
+    pub confidence: f32,
+}
+
+/// Headline figures of a network snapshot, see `NVTModels::network_summary`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NetworkSummary {
+    pub total_stops: usize,
+    pub total_lines: usize,
+    pub tram_lines: usize,
+    pub bus_lines: usize,
+    /// GPS-tracked vehicles
+    pub active_vehicles: usize,
+    pub active_alerts: usize,
+    /// Lines with at least one vehicle more than a minute early or late
+    pub lines_with_delays: usize,
+    /// Mean delay of the vehicles reporting one
+    pub avg_delay_seconds: f32,
+    /// Stops absent from both real-time feeds, see `NVTModels::stops_without_real_time`
+    pub stops_without_rt: usize,
+    /// (line_code, mean delay in seconds) of the five most delayed lines
+    pub most_delayed_lines: Vec<(String, f32)>,
+    /// Stops marked wheelchair accessible in the GTFS feed
+    pub accessible_stop_count: usize,
+    /// Stations made of several platform stops, see `NVTModels::group_stops_by_name`
+    pub group_stop_count: usize,
+    /// Percentage of vehicles on time or slightly early, see `NVTModels::service_reliability_index`
+    pub service_reliability_index: f32,
+    /// Most stops on a shortest path through the network, see `NVTModels::graph_diameter`
+    pub graph_diameter_stops: Option<u32>,
+    /// (stop_id, vehicles per hour) of the five busiest stops, see `NVTModels::detect_high_frequency_stops`
+    pub top_hubs: Vec<(String, u32)>,
+}
+
+/// Scheduled frequency of a line at its first stop, see `NVTModels::line_headways`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LineFrequency {
+    pub peak_headway_s: Option<u32>,
+    pub off_peak_headway_s: Option<u32>,
+    /// (first, last) departure in seconds since midnight, may exceed 24:00
+    pub service_span: Option<(u32, u32)>,
+}
+
+/// Distribution of the live delays reported for one route, see `NVTModels::compute_delay_statistics`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DelayStats {
+    pub mean_delay_s: f32,
+    pub median_delay_s: f32,
+    pub p90_delay_s: f32,
+    /// More than a minute late
+    pub delayed_count: usize,
+    /// More than a minute early
+    pub early_count: usize,
+    pub on_time_count: usize,
+}
+
+/// Stop/line incidence graph used for network analysis.
+/// The first `stop_count` nodes are stop IDs, the remaining nodes are line refs;
+/// every stop is linked (both ways) to each line serving it.
+#[derive(Debug, Clone, Default)]
+pub struct TransitGraph {
+    pub nodes: Vec<String>,
+    pub stop_count: usize,
+    pub adjacency: Vec<Vec<(usize, u32)>>,
+}
+
+impl TransitGraph {
+    pub fn from_stops(stops_data: &[StopMetadata]) -> Self {
+        let mut nodes: Vec<String> = stops_data.iter().map(|(id, ..)| id.clone()).collect();
+        let stop_count = nodes.len();
+        let mut line_index: HashMap<&str, usize> = HashMap::new();
+        let mut adjacency: Vec<Vec<(usize, u32)>> = vec![Vec::new(); stop_count];
+
+        for (stop_idx, (_, _, _, _, line_refs)) in stops_data.iter().enumerate() {
+            for line_ref in line_refs {
+                let line_idx = *line_index.entry(line_ref.as_str()).or_insert_with(|| {
+                    nodes.push(line_ref.clone());
+                    adjacency.push(Vec::new());
+                    nodes.len() - 1
+                });
+                adjacency[stop_idx].push((line_idx, 1));
+                adjacency[line_idx].push((stop_idx, 1));
+            }
+        }
+
+        TransitGraph { nodes, stop_count, adjacency }
+    }
+}
+
+/// A single column of values for Parquet export (`None` entries are written as nulls)
+enum ParquetColumn {
+    Text(Vec<Option<String>>),
+    Double(Vec<f64>),
+    Int32(Vec<Option<i32>>),
+    Int64(Vec<Option<i64>>),
+    Bool(Vec<bool>),
 }
 
 // ============================================================================
 // GTFS Cache Structure (15-day persistence)
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GTFSCache {
     pub routes: HashMap<String, String>,
     pub stops: Vec<(String, String, f64, f64)>,
+    /// stop_id -> language code -> translated stop name (from translations.txt)
+    #[serde(default)]
+    pub stop_name_translations: HashMap<String, HashMap<String, String>>,
+    /// stop_id -> (route_id, direction_id, departure_seconds_since_midnight), sorted
+    #[serde(default)]
+    pub scheduled_departures: ScheduledDepartures,
+    /// route_id -> direction_id -> ordered stop_ids of a representative trip
+    #[serde(default)]
+    pub stop_sequences: StopSequences,
+    /// shape_id -> ordered (lat, lon) points of the route path (from shapes.txt)
+    #[serde(default)]
+    pub shapes: RouteShapes,
+    /// route_id -> shape_id of the route's most detailed shape
+    #[serde(default)]
+    pub route_shapes: HashMap<String, String>,
+    /// service_id -> operating days (from calendar.txt and calendar_dates.txt)
+    #[serde(default)]
+    pub service_calendar: HashMap<String, ServiceCalendar>,
+    /// route_id -> GTFS route_type (from routes.txt)
+    #[serde(default)]
+    pub route_types: HashMap<String, u32>,
+    /// route_id -> route_short_name, the line code riders know (from routes.txt)
+    #[serde(default)]
+    pub route_short_names: HashMap<String, String>,
+    /// route_ids with a service day whose departures are all at night
+    #[serde(default)]
+    pub night_routes: HashSet<String>,
+    /// trip_id -> trip_headsign (from trips.txt)
+    #[serde(default)]
+    pub trip_headsigns: HashMap<String, String>,
+    /// stop_id -> GTFS location_type (from stops.txt)
+    #[serde(default)]
+    pub stop_location_types: HashMap<String, u8>,
+    /// stop_id -> GTFS wheelchair_boarding: 0 no information, 1 accessible, 2 not accessible
+    #[serde(default)]
+    pub stop_wheelchair: HashMap<String, u8>,
     pub cached_at: u64,
+    /// SHA-256 (hex) of the cache serialized with an empty checksum, see `GTFSCache::verify`
+    #[serde(default)]
+    pub checksum: String,
+}
+
+/// Subset of `GTFSCache` read by the shell completion helpers
+#[derive(Deserialize)]
+struct CachedIds {
+    #[serde(default)]
+    stops: Vec<(String, String, f64, f64)>,
+    #[serde(default)]
+    route_short_names: HashMap<String, String>,
+}
+
+/// Days a GTFS service_id runs on. Dates are YYYYMMDD as in the GTFS files.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServiceCalendar {
+    pub monday: bool,
+    pub tuesday: bool,
+    pub wednesday: bool,
+    pub thursday: bool,
+    pub friday: bool,
+    pub saturday: bool,
+    pub sunday: bool,
+    pub start_date: u32,
+    pub end_date: u32,
+    /// calendar_dates.txt exception_type 1
+    pub added_dates: Vec<u32>,
+    /// calendar_dates.txt exception_type 2
+    pub removed_dates: Vec<u32>,
+}
+
+impl ServiceCalendar {
+    pub fn runs_on(&self, date: NaiveDate) -> bool {
+        let day = NVTModels::gtfs_date(date);
+        if self.removed_dates.contains(&day) {
+            return false;
+        }
+        if self.added_dates.contains(&day) {
+            return true;
+        }
+
+        let weekday_active = match date.weekday() {
+            Weekday::Mon => self.monday,
+            Weekday::Tue => self.tuesday,
+            Weekday::Wed => self.wednesday,
+            Weekday::Thu => self.thursday,
+            Weekday::Fri => self.friday,
+            Weekday::Sat => self.saturday,
+            Weekday::Sun => self.sunday,
+        };
+        weekday_active && (self.start_date..=self.end_date).contains(&day)
+    }
 }
 
 impl GTFSCache {
@@ -110,15 +724,41 @@ impl GTFSCache {
         path
     }
 
-    pub fn save(&self) -> Result<()> {
-        let path = Self::cache_path();
-        let json = serde_json::to_string_pretty(self)
-            .map_err(|e| NVTError::FileError(format!("Failed to serialize cache: {}", e)))?;
+    /// The cache as JSON with an empty checksum field. Going through `serde_json::Value`
+    /// sorts object keys, so HashMap fields hash the same after a save/load round trip.
+    fn checksum_input(&self) -> Result<serde_json::Value> {
+        let mut value = serde_json::to_value(self)
+            .map_err(|e| NVTError::file("Failed to serialize cache").with_source(e))?;
+        value["checksum"] = serde_json::Value::String(String::new());
+        Ok(value)
+    }
 
-        fs::write(&path, json)
-            .map_err(|e| NVTError::FileError(format!("Failed to write cache: {}", e)))?;
+    fn sha256_hex(value: &serde_json::Value) -> String {
+        let digest = Sha256::digest(value.to_string().as_bytes());
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
 
-        println!("✓ GTFS cache saved to: {:?}", path);
+    /// Whether the stored checksum matches the content (false for files written before checksums)
+    pub fn verify(&self) -> bool {
+        !self.checksum.is_empty()
+            && self.checksum_input().is_ok_and(|value| Self::sha256_hex(&value) == self.checksum)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::cache_path();
+        let mut value = self.checksum_input()?;
+        value["checksum"] = serde_json::Value::String(Self::sha256_hex(&value));
+
+        // Compact: the stop_times table makes up most of the file
+        let file = fs::File::create(&path)
+            .map_err(|e| NVTError::file("Failed to write cache").with_source(e))?;
+        let mut writer = std::io::BufWriter::new(file);
+        serde_json::to_writer(&mut writer, &value)
+            .map_err(|e| NVTError::file("Failed to serialize cache").with_source(e))?;
+        writer.flush()
+            .map_err(|e| NVTError::file("Failed to write cache").with_source(e))?;
+
+        status!("✓ GTFS cache saved to: {:?}", path);
         Ok(())
     }
 
@@ -126,7 +766,7 @@ impl GTFSCache {
         let path = Self::cache_path();
 
         if !path.exists() {
-            println!("ℹ️  No GTFS cache found, will download fresh data");
+            status!("ℹ️  No GTFS cache found, will download fresh data");
             return None;
         }
 
@@ -134,32 +774,138 @@ impl GTFSCache {
             Ok(contents) => {
                 match serde_json::from_str::<GTFSCache>(&contents) {
                     Ok(cache) => {
-                        if cache.is_expired() {
-                            println!("⚠️  GTFS cache expired (>15 days old), refreshing...");
+                        if !cache.verify() {
+                            status!("⚠️  GTFS cache failed its integrity check (corrupted or older format), refreshing...");
+                            None
+                        } else if cache.is_expired() {
+                            status!("⚠️  GTFS cache expired (>15 days old), refreshing...");
+                            None
+                        } else if cache.stop_sequences.is_empty() {
+                            status!("⚠️  GTFS cache has no timetable data (older format), refreshing...");
                             None
                         } else {
                             let age_days = (SystemTime::now()
                                 .duration_since(UNIX_EPOCH)
                                 .unwrap_or_default()
                                 .as_secs().saturating_sub(cache.cached_at)) / 86400;
-                            println!("✓ GTFS cache loaded ({} days old)", age_days);
-                            println!("  • {} routes with colors", cache.routes.len());
-                            println!("  • {} stops cached", cache.stops.len());
+                            status!("✓ GTFS cache loaded ({} days old)", age_days);
+                            status!("  • {} routes with colors", cache.routes.len());
+                            status!("  • {} stops cached", cache.stops.len());
                             Some(cache)
                         }
                     }
                     Err(e) => {
-                        println!("⚠️  Failed to parse cache ({}), will refresh", e);
+                        status!("⚠️  Failed to parse cache ({}), will refresh", e);
                         None
                     }
                 }
             }
             Err(e) => {
-                println!("⚠️  Failed to read cache file ({}), will refresh", e);
+                status!("⚠️  Failed to read cache file ({}), will refresh", e);
+                None
+            }
+        }
+    }
+
+    pub fn clear() -> Result<()> {
+        let path = Self::cache_path();
+        if path.exists() {
+            fs::remove_file(&path)
+                .map_err(|e| NVTError::file("Failed to delete GTFS cache").with_source(e))?;
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// SIRI-Lite Cache Structure (last successful stops/lines responses)
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SiriCache {
+    pub stops: Vec<StopMetadata>,
+    pub lines: Vec<LineMetadata>,
+    pub cached_at: u64,
+    pub ttl_secs: u64,
+    /// ETags of the stops and lines responses, sent back as If-None-Match on refresh
+    #[serde(default)]
+    pub stops_etag: Option<String>,
+    #[serde(default)]
+    pub lines_etag: Option<String>,
+}
+
+impl SiriCache {
+    const DEFAULT_TTL_SECS: u64 = 86400;
+
+    pub fn new(
+        stops: Vec<StopMetadata>,
+        lines: Vec<LineMetadata>,
+    ) -> Self {
+        SiriCache {
+            stops,
+            lines,
+            cached_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            ttl_secs: Self::DEFAULT_TTL_SECS,
+            stops_etag: None,
+            lines_etag: None,
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        now.saturating_sub(self.cached_at) > self.ttl_secs
+    }
+
+    pub fn cache_path() -> PathBuf {
+        let mut path = dirs::cache_dir().unwrap_or_else(|| PathBuf::from("."));
+        path.push("tbm_nvt");
+        fs::create_dir_all(&path).ok();
+        path.push("siri_cache.json");
+        path
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::cache_path();
+        let json = serde_json::to_string(self)
+            .map_err(|e| NVTError::file("Failed to serialize SIRI cache").with_source(e))?;
+
+        fs::write(&path, json)
+            .map_err(|e| NVTError::file("Failed to write SIRI cache").with_source(e))?;
+
+        Ok(())
+    }
+
+    /// Load the SIRI cache if present and not expired
+    pub fn load() -> Option<Self> {
+        let contents = fs::read_to_string(Self::cache_path()).ok()?;
+
+        match serde_json::from_str::<SiriCache>(&contents) {
+            Ok(cache) if cache.is_expired() => {
+                status!("⚠️  SIRI cache expired (>{}h old), ignoring", cache.ttl_secs / 3600);
+                None
+            }
+            Ok(cache) => Some(cache),
+            Err(e) => {
+                status!("⚠️  Failed to parse SIRI cache ({}), ignoring", e);
                 None
             }
         }
     }
+
+    pub fn clear() -> Result<()> {
+        let path = Self::cache_path();
+        if path.exists() {
+            fs::remove_file(&path)
+                .map_err(|e| NVTError::file("Failed to delete SIRI cache").with_source(e))?;
+        }
+        Ok(())
+    }
 }
 
 // ============================================================================
@@ -168,23 +914,86 @@ impl GTFSCache {
 
 #[derive(Debug, Clone)]
 pub struct CachedNetworkData {
-    pub stops_metadata: Vec<(String, String, f64, f64, Vec<String>)>,
-    pub lines_metadata: Vec<(String, String, String, Vec<(String, String)>)>,
+    pub stops_metadata: Vec<StopMetadata>,
+    pub lines_metadata: Vec<LineMetadata>,
     pub line_colors: HashMap<String, String>,
+    pub hub_scores: HashMap<String, f64>,
+    /// stop_id -> line_refs serving it, rebuilt whenever the stops metadata changes
+    pub stop_to_lines: HashMap<String, Vec<String>>,
+    /// line_ref -> stop_ids it serves, rebuilt whenever the stops metadata changes
+    pub line_to_stops: HashMap<String, Vec<String>>,
+    pub gtfs: Arc<GTFSCache>,
+    /// Stop graph of the GTFS stop sequences, rebuilt whenever the GTFS feed is reloaded,
+    /// see `NVTModels::compute_network_graph`
+    pub network_graph: Option<Arc<StableGraph<String, u32>>>,
+    /// `NVTModels::graph_diameter` of `network_graph`, computed the first time the network
+    /// summary needs it and shared with the network data built from this cache.
+    /// Replaced by an empty one whenever the GTFS feed is reloaded.
+    pub graph_diameter: Arc<OnceLock<Option<u32>>>,
+    pub display_lang: Option<String>,
+    pub service_date: Option<NaiveDate>,
+    /// Alerts below this severity are left out of the network data (--min-severity)
+    pub min_severity: u32,
+    pub config: Arc<NVTConfig>,
+    pub arrival_logger: Option<Arc<Mutex<ArrivalLogger>>>,
+    /// Routes of the disruption reports already written to the arrival log
+    pub reported_disruptions: HashSet<String>,
+    /// Receives the arrivals of pinned stops after each refresh (--mqtt)
+    pub mqtt_publisher: Option<Arc<NVTMqttPublisher>>,
     pub last_static_update: u64,
+    /// ETags of the last SIRI-Lite stops and lines responses, see `NVTModels::refresh_static_data_incremental`
+    pub stops_etag: Option<String>,
+    pub lines_etag: Option<String>,
+    /// Static refreshes answered 304 Not Modified / with a full download, per endpoint
+    pub etag_hit_count: usize,
+    pub etag_miss_count: usize,
+    /// Static refreshes that found stops, lines and GTFS feed unchanged and rebuilt nothing
+    pub static_refresh_skipped_count: usize,
+    /// (line_ref, service date) -> (peak, off-peak) headway, filled in by the line frequency
+    /// table and emptied whenever the GTFS timetable is reloaded
+    pub headway_cache: HashMap<(String, NaiveDate), (Option<u32>, Option<u32>)>,
+    /// stop_id -> departures from the SIRI stop timetable of the monitored stop and line,
+    /// used instead of the GTFS static fallback, see `NVTModels::fetch_line_timetable_siri`
+    pub siri_timetables: HashMap<String, Vec<RealTimeInfo>>,
     pub alerts: Vec<AlertInfo>,
     pub real_time: Vec<RealTimeInfo>,
     pub trip_updates: Vec<gtfs_rt::TripUpdate>,
     pub last_dynamic_update: u64,
 }
 
+impl Default for CachedNetworkData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CachedNetworkData {
     pub fn new() -> Self {
         CachedNetworkData {
             stops_metadata: Vec::new(),
             lines_metadata: Vec::new(),
             line_colors: HashMap::new(),
+            hub_scores: HashMap::new(),
+            stop_to_lines: HashMap::new(),
+            line_to_stops: HashMap::new(),
+            gtfs: Arc::new(GTFSCache::default()),
+            network_graph: None,
+            graph_diameter: Arc::default(),
+            display_lang: None,
+            service_date: None,
+            min_severity: 0,
+            config: Arc::new(NVTConfig::default()),
+            arrival_logger: None,
+            reported_disruptions: HashSet::new(),
+            mqtt_publisher: None,
             last_static_update: 0,
+            stops_etag: None,
+            lines_etag: None,
+            etag_hit_count: 0,
+            etag_miss_count: 0,
+            static_refresh_skipped_count: 0,
+            headway_cache: HashMap::new(),
+            siri_timetables: HashMap::new(),
             alerts: Vec::new(),
             real_time: Vec::new(),
             trip_updates: Vec::new(),
@@ -209,127 +1018,574 @@ impl CachedNetworkData {
     }
 
     pub fn to_network_data(&self) -> NetworkData {
-        NVTModels::build_network_data(
+        let mut network = NVTModels::build_network_data(
             self.stops_metadata.clone(),
             self.lines_metadata.clone(),
-            self.alerts.clone(),
+            self.alerts.iter().filter(|a| a.severity >= self.min_severity).cloned().collect(),
             self.real_time.clone(),
             self.trip_updates.clone(),
             self.line_colors.clone(),
-        )
+            self.hub_scores.clone(),
+            &self.stop_to_lines,
+            &self.line_to_stops,
+            &self.config,
+        );
+        network.gtfs = Arc::clone(&self.gtfs);
+        network.network_graph = self.network_graph.clone();
+        network.graph_diameter = Arc::clone(&self.graph_diameter);
+        let terminals: Vec<(Option<String>, Option<String>)> = network.lines
+            .iter()
+            .map(|line| {
+                let (first, last) = NVTModels::detect_terminal_stops(&line.line_ref, 0, &network.gtfs);
+                let name = |stop_id: Option<String>| {
+                    NVTModels::get_stop_by_id(&stop_id?, &network).map(|s| s.stop_name.clone())
+                };
+                (name(first), name(last))
+            })
+            .collect();
+        let circular = NVTModels::detect_circular_lines(&network);
+        for (line, (terminus_a, terminus_b)) in network.lines.iter_mut().zip(terminals) {
+            let route_id = NVTModels::extract_line_id(&line.line_ref).unwrap_or(&line.line_ref);
+            line.is_night_service = NVTModels::is_night_service(route_id, &network.gtfs);
+            line.line_type = NVTModels::line_type(&line.line_ref, &network.gtfs);
+            line.is_circular = circular.contains(&line.line_ref);
+            if terminus_a.is_some() && terminus_b.is_some() {
+                line.terminus_a = terminus_a;
+                line.terminus_b = terminus_b;
+            }
+        }
+        for stop in network.stops.iter_mut() {
+            stop.stop_type = NVTModels::classify_stop_type(stop, &network.gtfs);
+            stop.wheelchair_accessible = network.gtfs.stop_wheelchair.get(&stop.stop_id) == Some(&1);
+        }
+        network.display_lang = self.display_lang.clone();
+        network.service_date = self.service_date;
+        NVTModels::fill_missing_destinations(&mut network, &self.trip_updates);
+        NVTModels::place_cancelled_trips(&mut network, &self.trip_updates);
+        NVTModels::apply_siri_timetables(&mut network, &self.siri_timetables);
+        NVTModels::apply_static_schedule_fallback(&mut network);
+        network
     }
 }
 
 // ============================================================================
-// Error Handling
+// Arrival Logger (JSONL history of observed arrivals)
 // ============================================================================
 
 #[derive(Debug)]
-pub enum NVTError {
-    NetworkError(String),
-    ParseError(String),
-    FileError(String),
+pub struct ArrivalLogger {
+    path: PathBuf,
+    file: fs::File,
 }
 
-impl std::fmt::Display for NVTError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            NVTError::NetworkError(e) => write!(f, "Network error: {}", e),
-            NVTError::ParseError(e) => write!(f, "Parse error: {}", e),
-            NVTError::FileError(e) => write!(f, "File error: {}", e),
-        }
+impl ArrivalLogger {
+    pub fn open(path: PathBuf) -> Result<Self> {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| NVTError::file(format!("Failed to open arrival log {:?}", path)).with_source(e))?;
+
+        Ok(ArrivalLogger { path, file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one arrival as a JSON line and flush immediately.
+    /// Rust ignores SIGPIPE, so a closed pipe surfaces here as a BrokenPipe error.
+    pub fn record(&mut self, stop_id: &str, route_id: &str, scheduled: i64, actual: i64, delay: i32) -> std::io::Result<()> {
+        self.write_record(serde_json::json!({
+            "ts": Utc::now().timestamp(),
+            "stop": stop_id,
+            "route": route_id,
+            "sched": scheduled,
+            "actual": actual,
+            "delay": delay,
+        }))
+    }
+
+    /// Append any JSON record as one line and flush immediately
+    pub fn write_record(&mut self, record: serde_json::Value) -> std::io::Result<()> {
+        writeln!(self.file, "{}", record)?;
+        self.file.flush()
     }
 }
 
-impl std::error::Error for NVTError {}
+/// The fields of an arrival log line used for reliability statistics
+#[derive(Debug, Deserialize)]
+struct LoggedArrival {
+    ts: i64,
+    #[serde(default)]
+    stop: String,
+    route: String,
+    #[serde(default)]
+    sched: i64,
+    delay: i32,
+}
 
-pub type Result<T> = std::result::Result<T, NVTError>;
+/// An ETA accuracy line of the arrival log (see `NVTModels::record_eta_accuracy`)
+#[derive(Debug, Deserialize)]
+struct LoggedEtaError {
+    ts: i64,
+    route: String,
+    eta_error: i64,
+}
+
+/// Last predicted arrival of a GPS-tracked vehicle at the stop it was heading to
+#[derive(Debug)]
+struct EtaPrediction {
+    stop_id: String,
+    route_id: String,
+    predicted: i64,
+}
 
 // ============================================================================
-// Main Implementation
+// Error Handling
 // ============================================================================
 
-pub struct NVTModels;
+/// Underlying error kept as the `source()` of an `NVTError`
+pub type BoxedError = Box<dyn std::error::Error + Send + Sync>;
 
-impl NVTModels {
-    const API_KEY: &'static str = "opendata-bordeaux-metropole-flux-gtfs-rt";
-    const BASE_URL: &'static str = "https://bdx.mecatran.com/utw/ws";
-    const STATIC_DATA_MAX_AGE: u64 = 3600;
-    const DYNAMIC_DATA_MAX_AGE: u64 = 30;
-    const REQUEST_TIMEOUT_SECS: u64 = 15;
-
-    pub fn initialize_cache() -> Result<CachedNetworkData> {
-        println!("🔄 Initializing network data cache...");
-        println!("   This may take a moment...");
-
-        let stops = Self::fetch_stops().map_err(|e| {
-            NVTError::NetworkError(format!("Failed to fetch stops: {}", e))
-        })?;
-        println!("   ✓ Loaded {} stops", stops.len());
-
-        let lines = Self::fetch_lines().map_err(|e| {
-            NVTError::NetworkError(format!("Failed to fetch lines: {}", e))
-        })?;
-        println!("   ✓ Loaded {} lines", lines.len());
-
-        let line_colors = Self::load_line_colors().map_err(|e| {
-            println!("   ⚠️  Warning: Could not load line colors ({})", e);
-            println!("   Continuing with default colors...");
-            e
-        }).unwrap_or_default();
-        println!("   ✓ Loaded {} line colors", line_colors.len());
+#[derive(Debug)]
+pub enum NVTError {
+    NetworkError { msg: String, source: Option<BoxedError> },
+    /// The server answered with a non-success HTTP status
+    HttpError { status: u16, msg: String, source: Option<BoxedError> },
+    ParseError { msg: String, source: Option<BoxedError> },
+    FileError { msg: String, source: Option<BoxedError> },
+}
 
-        let alerts = Self::fetch_alerts().unwrap_or_else(|e| {
-            println!("   ⚠️  Warning: Could not fetch alerts ({})", e);
-            Vec::new()
-        });
-        println!("   ✓ Loaded {} alerts", alerts.len());
+impl NVTError {
+    pub fn network(msg: impl Into<String>) -> Self {
+        NVTError::NetworkError { msg: msg.into(), source: None }
+    }
 
-        let real_time = Self::fetch_vehicle_positions().unwrap_or_else(|e| {
-            println!("   ⚠️  Warning: Could not fetch vehicle positions ({})", e);
-            Vec::new()
-        });
-        println!("   ✓ Loaded {} vehicle positions", real_time.len());
+    pub fn http(status: reqwest::StatusCode, msg: impl Into<String>) -> Self {
+        NVTError::HttpError { status: status.as_u16(), msg: msg.into(), source: None }
+    }
 
-        let trip_updates = Self::fetch_trip_updates().unwrap_or_else(|e| {
-            println!("   ⚠️  Warning: Could not fetch trip updates ({})", e);
-            Vec::new()
-        });
-        println!("   ✓ Loaded {} trip updates", trip_updates.len());
+    pub fn parse(msg: impl Into<String>) -> Self {
+        NVTError::ParseError { msg: msg.into(), source: None }
+    }
+
+    pub fn file(msg: impl Into<String>) -> Self {
+        NVTError::FileError { msg: msg.into(), source: None }
+    }
+
+    /// Keep the error that caused this one, reachable through `Error::source`
+    pub fn with_source(mut self, error: impl Into<BoxedError>) -> Self {
+        match &mut self {
+            NVTError::NetworkError { source, .. }
+            | NVTError::HttpError { source, .. }
+            | NVTError::ParseError { source, .. }
+            | NVTError::FileError { source, .. } => *source = Some(error.into()),
+        }
+        self
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            NVTError::NetworkError { msg, .. }
+            | NVTError::HttpError { msg, .. }
+            | NVTError::ParseError { msg, .. }
+            | NVTError::FileError { msg, .. } => msg,
+        }
+    }
+
+    /// Whether trying again may succeed: timeouts, connection failures and 5xx responses.
+    /// A 4xx response (e.g. a bad API key) will get the same answer every time.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            NVTError::HttpError { status, .. } => *status >= 500,
+            NVTError::NetworkError { source: Some(source), .. } => source
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(|e| e.is_timeout() || e.is_connect()),
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for NVTError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NVTError::NetworkError { msg, .. } | NVTError::HttpError { msg, .. } => write!(f, "Network error: {}", msg)?,
+            NVTError::ParseError { msg, .. } => write!(f, "Parse error: {}", msg)?,
+            NVTError::FileError { msg, .. } => write!(f, "File error: {}", msg)?,
+        }
+        // The underlying error is kept out of `msg`, so it is only printed once
+        if let Some(source) = std::error::Error::source(self) {
+            write!(f, ": {}", source)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for NVTError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NVTError::NetworkError { source, .. }
+            | NVTError::HttpError { source, .. }
+            | NVTError::ParseError { source, .. }
+            | NVTError::FileError { source, .. } => source.as_deref().map(|e| e as &(dyn std::error::Error + 'static)),
+        }
+    }
+}
+
+impl From<reqwest::Error> for NVTError {
+    fn from(e: reqwest::Error) -> Self {
+        NVTError::network("Request failed").with_source(e)
+    }
+}
+
+impl From<serde_json::Error> for NVTError {
+    fn from(e: serde_json::Error) -> Self {
+        NVTError::parse("Invalid JSON").with_source(e)
+    }
+}
+
+impl From<prost::DecodeError> for NVTError {
+    fn from(e: prost::DecodeError) -> Self {
+        NVTError::parse("Invalid protobuf message").with_source(e)
+    }
+}
+
+impl From<csv::Error> for NVTError {
+    fn from(e: csv::Error) -> Self {
+        NVTError::parse("Invalid CSV").with_source(e)
+    }
+}
+
+impl From<std::io::Error> for NVTError {
+    fn from(e: std::io::Error) -> Self {
+        NVTError::file("I/O error").with_source(e)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, NVTError>;
+
+/// Response to a request that may carry If-None-Match
+enum Revalidated<T> {
+    /// 304 Not Modified: the cached copy is still current
+    NotModified,
+    /// New content, with the response's ETag if it had one
+    Modified(T, Option<String>),
+}
+
+impl<T> Revalidated<T> {
+    /// Content and ETag of a request sent without an ETag, which can't be answered with a 304
+    fn into_modified(self) -> Result<(T, Option<String>)> {
+        match self {
+            Revalidated::Modified(content, etag) => Ok((content, etag)),
+            Revalidated::NotModified => Err(NVTError::network("Unexpected 304 Not Modified")),
+        }
+    }
+}
+
+// ============================================================================
+// Main Implementation
+// ============================================================================
+
+pub struct NVTModels;
+
+/// Shared async HTTP client, reused across all API requests.
+/// Built from the config by the first `initialize_cache`, see `NVTModels::http_client`.
+static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+lazy_static! {
+    /// Duration of each API fetch (retries included), labelled by endpoint
+    pub static ref FETCH_DURATION: prometheus::HistogramVec = prometheus::register_histogram_vec!(
+        "nvt_fetch_duration_seconds",
+        "Duration of TBM API fetches in seconds",
+        &["endpoint"]
+    ).expect("Failed to register fetch duration histogram");
+
+    /// stop_id -> StopPointRef as published by the stops discoveries so far, see `NVTModels::siri_stop_ref`
+    static ref SIRI_STOP_REFS: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+}
+
+/// Decompressed bytes received from the API since startup
+static API_BYTES_RECEIVED: AtomicU64 = AtomicU64::new(0);
+
+/// Alerts in the last full alerts feed before deduplication
+static RAW_ALERTS_RECEIVED: AtomicUsize = AtomicUsize::new(0);
+
+/// Suppresses progress messages so stdout only carries machine-readable output
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+impl NVTModels {
+    pub const FUZZY_STOP_THRESHOLD: f64 = 0.75;
+    pub const RETRY_BASE_DELAY_MS: u64 = 500;
+    /// Ask the API for gzip/brotli responses (decoded transparently by reqwest)
+    pub const COMPRESSION_ENABLED: bool = true;
+    /// Minimum time to change vehicle in a planned journey
+    pub const MIN_TRANSFER_SECS: i64 = 120;
+    /// Farthest two stops can be apart to walk between them in a planned journey
+    pub const MAX_WALK_TRANSFER_M: f64 = 250.0;
+    /// Arrivals within this many seconds of the schedule (early or late) count as on time
+    pub const ON_TIME_TOLERANCE_SECS: i32 = 60;
+    /// trip_id given to feed entities that don't carry one
+    pub const UNKNOWN_TRIP_ID: &'static str = "Unknown";
+    /// Error message of a key refused by the API, see `validate_api_key`
+    pub const INVALID_API_KEY_MSG: &'static str = "Invalid API key";
+    /// Fewest vehicles a route must expect to be checked by `detect_service_disruption`
+    pub const DISRUPTION_MIN_EXPECTED: usize = 3;
+    /// Share of a route's stops that must be in the timetable for `detect_service_disruption`
+    pub const DISRUPTION_MIN_CONFIDENCE: f32 = 0.8;
+    /// Mean delay at which the historical load indicator is full, see `compute_load_indicators`
+    pub const FULL_LOAD_DELAY_SECS: i32 = 300;
+    /// Shortest arrival window `detect_high_frequency_stops` extrapolates from, so that
+    /// two vehicles arriving together don't make a stop look like a hub
+    const HUB_MIN_WINDOW_SECS: i64 = 15 * 60;
+    /// Waits longer than this many median headways are reported as service gaps
+    pub const SERVICE_GAP_FACTOR: f64 = 1.5;
+    /// Night-service window: departures from 23:00 to 04:59
+    const NIGHT_START_SECS: u32 = 23 * 3600;
+    const NIGHT_END_SECS: u32 = 5 * 3600;
+    /// Journeys arriving later than this after departure are not considered
+    pub const JOURNEY_HORIZON_SECS: i64 = 4 * 3600;
+    /// Weekday rush hour and midday windows of the line frequency table, in seconds since midnight
+    pub const PEAK_WINDOW_SECS: (u32, u32) = (7 * 3600, 9 * 3600);
+    pub const OFF_PEAK_WINDOW_SECS: (u32, u32) = (10 * 3600, 16 * 3600);
+    /// Side of a `SpatialGrid` cell for stop lookups (about 1.1 km north-south)
+    const STOP_GRID_CELL_DEG: f64 = 0.01;
+
+    pub async fn initialize_cache(config: &NVTConfig) -> Result<CachedNetworkData> {
+        status!("🔄 Initializing network data cache...");
+        status!("   This may take a moment...");
+
+        if HTTP_CLIENT.get().is_none() {
+            let _ = HTTP_CLIENT.set(Self::build_http_client(config)?);
+            if let Some(proxy) = Self::active_proxy(config) {
+                status!("🔒 Using proxy: {}", Self::redact_proxy_url(&proxy));
+            }
+        }
+
+        // A rejected key fails fast instead of after every fetch timed out; when the API
+        // can't be reached, the cache database may still have stops and lines to show
+        status!("🔑 Validating API key...");
+        match Self::validate_api_key(config).await {
+            Ok(()) => status!("   ✓ API key accepted"),
+            Err(e) if Self::is_invalid_api_key(&e) => return Err(e),
+            Err(e) => status!("   ⚠️  Warning: {}", e),
+        }
+
+        let persisted = NVTPersistence::open()
+            .and_then(|conn| NVTPersistence::load_cache(&conn))
+            .unwrap_or_else(|e| {
+                status!("   ⚠️  Warning: Could not read cache database ({})", e);
+                None
+            })
+            .filter(|persisted| !persisted.needs_static_refresh(config.static_max_age_secs));
+
+        let from_database = persisted.is_some();
+        let (stops, lines, last_static_update, stops_etag, lines_etag) = match persisted {
+            Some(persisted) => {
+                status!("   ✓ Using stops and lines from the cache database");
+                (persisted.stops_metadata, persisted.lines_metadata, persisted.last_static_update, persisted.stops_etag, persisted.lines_etag)
+            }
+            None => {
+                let siri_cache = Self::fetch_siri_metadata(config).await?;
+                (siri_cache.stops, siri_cache.lines, siri_cache.cached_at, siri_cache.stops_etag, siri_cache.lines_etag)
+            }
+        };
+        status!("   ✓ Loaded {} stops", stops.len());
+        status!("   ✓ Loaded {} lines", lines.len());
+
+        let gtfs = Self::load_gtfs_data().await.map_err(|e| {
+            status!("   ⚠️  Warning: Could not load line colors ({})", e);
+            e
+        }).ok();
+        let line_colors = Self::load_line_colors(gtfs.as_ref());
+        let gtfs = gtfs.unwrap_or_default();
+
+        let (alerts, real_time, trip_updates) = tokio::join!(
+            Self::fetch_alerts(config),
+            Self::fetch_vehicle_positions(config),
+            Self::fetch_trip_updates(config),
+        );
+
+        let alerts = alerts.unwrap_or_else(|e| {
+            status!("   ⚠️  Warning: Could not fetch alerts ({})", e);
+            Vec::new()
+        });
+        status!("   ✓ Loaded {} alerts", alerts.len());
+
+        let real_time = real_time.unwrap_or_else(|e| {
+            status!("   ⚠️  Warning: Could not fetch vehicle positions ({})", e);
+            Vec::new()
+        });
+        status!("   ✓ Loaded {} vehicle positions", real_time.len());
+
+        let trip_updates = trip_updates.unwrap_or_else(|e| {
+            status!("   ⚠️  Warning: Could not fetch trip updates ({})", e);
+            Vec::new()
+        });
+        status!("   ✓ Loaded {} trip updates", trip_updates.len());
+
+        let hub_scores = Self::compute_transfer_hub_scores(&TransitGraph::from_stops(&stops));
+        let (stop_to_lines, line_to_stops) = Self::build_line_indices(&stops);
+        let network_graph = Self::compute_network_graph(&gtfs, Self::service_date(None));
 
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
             .as_secs();
 
-        println!("\n✓ Cache initialized successfully!");
-        println!("  • {} stops, {} lines", stops.len(), lines.len());
-        println!("  • {} vehicles tracked, {} alerts", real_time.len(), alerts.len());
+        status!("\n✓ Cache initialized successfully!");
+        status!("  • {} stops, {} lines", stops.len(), lines.len());
+        status!("  • {} vehicles tracked, {} alerts", real_time.len(), alerts.len());
+        status!("  • {} KB of API data received ({})",
+                 Self::api_bytes_received() / 1024,
+                 if Self::COMPRESSION_ENABLED { "gzip/brotli transfer enabled" } else { "uncompressed transfer" });
 
-        Ok(CachedNetworkData {
+        let cache = CachedNetworkData {
             stops_metadata: stops,
             lines_metadata: lines,
             line_colors,
-            last_static_update: now,
+            hub_scores,
+            stop_to_lines,
+            line_to_stops,
+            gtfs: Arc::new(gtfs),
+            network_graph: Some(Arc::new(network_graph)),
+            graph_diameter: Arc::default(),
+            display_lang: None,
+            service_date: None,
+            min_severity: 0,
+            config: Arc::new(config.clone()),
+            arrival_logger: None,
+            reported_disruptions: HashSet::new(),
+            mqtt_publisher: None,
+            last_static_update,
+            stops_etag,
+            lines_etag,
+            etag_hit_count: 0,
+            etag_miss_count: 0,
+            static_refresh_skipped_count: 0,
+            headway_cache: HashMap::new(),
+            siri_timetables: HashMap::new(),
             alerts,
             real_time,
             trip_updates,
             last_dynamic_update: now,
-        })
+        };
+
+        if !from_database {
+            Self::persist_static_data(&cache);
+        }
+
+        let violations = Self::validate_network_data(&cache.to_network_data());
+        if !violations.is_empty() {
+            status!("\n⚠️  {} data integrity issue(s) (run with --validate for the full list):", violations.len());
+            for violation in violations.iter().take(Self::VIOLATIONS_SHOWN) {
+                status!("   • {}", violation);
+            }
+        }
+
+        Ok(cache)
+    }
+
+    /// Integrity issues logged by `initialize_cache`; --validate lists all of them
+    const VIOLATIONS_SHOWN: usize = 5;
+
+    /// Invariants the built network data should hold, as one message per violation:
+    /// vehicles reference known stops, GPS vehicles have a position, line refs are unique
+    pub fn validate_network_data(network: &NetworkData) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        let mut line_refs: HashSet<&str> = HashSet::new();
+        for line in &network.lines {
+            if !line_refs.insert(&line.line_ref) {
+                violations.push(format!("Duplicate line_ref {} ({})", line.line_ref, line.line_code));
+            }
+
+            for rt in &line.real_time {
+                if let Some(stop_id) = &rt.stop_id
+                    && Self::get_stop_by_id(stop_id, network).is_none()
+                {
+                    violations.push(format!("Vehicle {} on line {} references unknown stop {}",
+                                            rt.vehicle_id, line.line_code, stop_id));
+                }
+            }
+        }
+
+        let mut checked: HashSet<&str> = HashSet::new();
+        let vehicles = network.lines.iter().flat_map(|l| &l.real_time)
+            .chain(network.stops.iter().flat_map(|s| &s.real_time));
+        for rt in vehicles {
+            if Self::is_scheduled(rt)
+                || !checked.insert(&rt.vehicle_id)
+            {
+                continue;
+            }
+            if rt.latitude == 0.0 && rt.longitude == 0.0 {
+                violations.push(format!("GPS vehicle {} has no position (0, 0)", rt.vehicle_id));
+            }
+        }
+
+        violations
+    }
+
+    /// Save stops/lines metadata to the cache database so restarts can skip SIRI discovery
+    fn persist_static_data(cache: &CachedNetworkData) {
+        if let Err(e) = NVTPersistence::open().and_then(|conn| NVTPersistence::save_cache(&conn, cache)) {
+            eprintln!("⚠️  Warning: Could not save cache database: {}", e);
+        }
+    }
+
+    /// Remove all persisted stops/lines metadata from the cache database
+    pub fn clear_persistence(conn: &rusqlite::Connection) -> Result<()> {
+        conn.execute_batch("DELETE FROM stops_meta; DELETE FROM lines_meta; DELETE FROM cache_meta; DELETE FROM etags;")
+            .map_err(|e| NVTError::file("Failed to clear cache database").with_source(e))
+    }
+
+    /// Fetch stops and lines from SIRI-Lite, falling back to the last successful response.
+    /// The returned cache's `cached_at` tells when the data was actually fetched.
+    async fn fetch_siri_metadata(config: &NVTConfig) -> Result<SiriCache> {
+        let siri_result = async {
+            let stops = Self::fetch_stops(config, None).await.and_then(Revalidated::into_modified)?;
+            let lines = Self::fetch_lines(config, None).await.and_then(Revalidated::into_modified)?;
+            Ok::<_, NVTError>((stops, lines))
+        }.await;
+
+        match siri_result {
+            Ok(((stops, stops_etag), (lines, lines_etag))) => {
+                let mut siri_cache = SiriCache::new(stops, lines);
+                siri_cache.stops_etag = stops_etag;
+                siri_cache.lines_etag = lines_etag;
+                if let Err(e) = siri_cache.save() {
+                    status!("   ⚠️  Warning: Could not save SIRI cache: {}", e);
+                }
+                Ok(siri_cache)
+            }
+            Err(e) => {
+                let siri_cache = SiriCache::load().ok_or(e)?;
+                Self::show_stale_siri_warning(&siri_cache);
+                Ok(siri_cache)
+            }
+        }
     }
 
-    pub fn refresh_dynamic_data(cache: &mut CachedNetworkData) -> Result<()> {
-        cache.alerts = Self::fetch_alerts().unwrap_or_else(|e| {
+    pub async fn refresh_dynamic_data(cache: &mut CachedNetworkData, config: &NVTConfig) -> Result<()> {
+        let (alerts, real_time, trip_updates) = tokio::join!(
+            Self::fetch_alerts(config),
+            Self::fetch_vehicle_positions(config),
+            Self::fetch_trip_updates(config),
+        );
+
+        // A failed fetch keeps the previous cycle's alerts
+        cache.alerts = Self::deduplicate_alerts(alerts.unwrap_or_else(|e| {
             eprintln!("⚠️  Warning: Could not fetch alerts ({})", e);
             cache.alerts.clone()
-        });
+        }));
 
-        cache.real_time = Self::fetch_vehicle_positions().unwrap_or_else(|e| {
+        cache.real_time = real_time.unwrap_or_else(|e| {
             eprintln!("⚠️  Warning: Could not fetch vehicle positions ({})", e);
             cache.real_time.clone()
         });
 
-        cache.trip_updates = Self::fetch_trip_updates().unwrap_or_else(|e| {
+        cache.trip_updates = trip_updates.unwrap_or_else(|e| {
             eprintln!("⚠️  Warning: Could not fetch trip updates ({})", e);
             cache.trip_updates.clone()
         });
@@ -342,63 +1598,437 @@ impl NVTModels {
         Ok(())
     }
 
-    pub fn refresh_static_data(cache: &mut CachedNetworkData) -> Result<()> {
-        println!("🔄 Refreshing static network data...");
-
-        cache.stops_metadata = Self::fetch_stops()?;
-        cache.lines_metadata = Self::fetch_lines()?;
-        cache.line_colors = Self::load_line_colors().unwrap_or_default();
+    /// Revalidate stops and lines with their ETags. When both are unchanged (304) and the
+    /// GTFS feed hasn't expired, nothing is reloaded or rebuilt and Ok(false) is returned;
+    /// otherwise the GTFS data and derived indices are reloaded and Ok(true) is returned.
+    pub async fn refresh_static_data_incremental(cache: &mut CachedNetworkData, config: &NVTConfig) -> Result<bool> {
+        status!("🔄 Refreshing static network data...");
+
+        // Unchanged responses (304) keep the cached stops/lines without re-parsing
+        let stops = Self::fetch_stops(config, cache.stops_etag.as_deref()).await?;
+        let lines = Self::fetch_lines(config, cache.lines_etag.as_deref()).await?;
+        let mut stops_changed = false;
+        let mut changed = false;
+
+        match stops {
+            Revalidated::NotModified => cache.etag_hit_count += 1,
+            Revalidated::Modified(stops, etag) => {
+                cache.etag_miss_count += 1;
+                cache.stops_metadata = stops;
+                cache.stops_etag = etag;
+                stops_changed = true;
+                changed = true;
+            }
+        }
+        match lines {
+            Revalidated::NotModified => cache.etag_hit_count += 1,
+            Revalidated::Modified(lines, etag) => {
+                cache.etag_miss_count += 1;
+                cache.lines_metadata = lines;
+                cache.lines_etag = etag;
+                changed = true;
+            }
+        }
 
-        cache.last_static_update = SystemTime::now()
+        let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
             .as_secs();
+        if !changed && !cache.gtfs.is_expired() {
+            cache.static_refresh_skipped_count += 1;
+            cache.last_static_update = now;
+            status!("✓ Static data unchanged");
+            return Ok(false);
+        }
+
+        if changed {
+            let mut siri_cache = SiriCache::new(cache.stops_metadata.clone(), cache.lines_metadata.clone());
+            siri_cache.stops_etag = cache.stops_etag.clone();
+            siri_cache.lines_etag = cache.lines_etag.clone();
+            if let Err(e) = siri_cache.save() {
+                eprintln!("⚠️  Warning: Could not save SIRI cache: {}", e);
+            }
+        }
+        let gtfs = Self::load_gtfs_data().await.ok();
+        cache.line_colors = Self::load_line_colors(gtfs.as_ref());
+        let gtfs = gtfs.unwrap_or_default();
+        cache.gtfs = Arc::new(gtfs);
+        cache.headway_cache.clear();
+        let network_graph = Self::compute_network_graph(&cache.gtfs, Self::service_date(cache.service_date));
+        cache.network_graph = Some(Arc::new(network_graph));
+        cache.graph_diameter = Arc::default();
+        if stops_changed {
+            cache.hub_scores = Self::compute_transfer_hub_scores(
+                &TransitGraph::from_stops(&cache.stops_metadata)
+            );
+            (cache.stop_to_lines, cache.line_to_stops) = Self::build_line_indices(&cache.stops_metadata);
+        }
+
+        cache.last_static_update = now;
+        Self::persist_static_data(cache);
+
+        status!("✓ Static data refreshed!");
+
+        Ok(true)
+    }
+
+    /// Warn that stops/lines come from the last successful SIRI-Lite response
+    fn show_stale_siri_warning(siri_cache: &SiriCache) {
+        let age_hours = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+            .saturating_sub(siri_cache.cached_at) / 3600;
+
+        status!("\n{}", "─".repeat(60));
+        status!("⚠️  SIRI-Lite API unavailable - using cached stops and lines");
+        status!("   Cached data is {} hour(s) old and may be slightly outdated", age_hours);
+        status!("{}", "─".repeat(60));
+    }
+
+    /// Stop IDs of the GTFS cache file, sorted, for shell completion.
+    /// Empty without a cache; never touches the network.
+    pub fn list_cached_stop_ids() -> Vec<String> {
+        let mut stop_ids: Vec<String> = Self::read_cached_ids()
+            .map(|ids| ids.stops.iter().map(|(stop_id, ..)| Self::normalize_stop_id(stop_id)).collect())
+            .unwrap_or_default();
+        stop_ids.sort();
+        stop_ids.dedup();
+        stop_ids
+    }
+
+    /// Line codes (route_short_name) of the GTFS cache file, sorted, for shell completion.
+    /// Empty without a cache; never touches the network.
+    pub fn list_cached_line_codes() -> Vec<String> {
+        let mut codes: Vec<String> = Self::read_cached_ids()
+            .map(|ids| ids.route_short_names.into_values().collect())
+            .unwrap_or_default();
+        codes.sort();
+        codes.dedup();
+        codes
+    }
 
-        println!("✓ Static data refreshed!");
+    /// Only the stop and route fields of the GTFS cache file, skipping the timetables
+    fn read_cached_ids() -> Option<CachedIds> {
+        let contents = fs::read_to_string(GTFSCache::cache_path()).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Delete the GTFS cache file only; the next start downloads the GTFS feed again
+    pub fn invalidate_gtfs_cache() -> Result<()> {
+        GTFSCache::clear()
+    }
 
+    /// Delete the GTFS and SIRI-Lite caches so the next start downloads fresh data
+    pub fn clear_caches() -> Result<()> {
+        GTFSCache::clear()?;
+        SiriCache::clear()?;
+        Self::clear_persistence(&NVTPersistence::open()?)?;
         Ok(())
     }
 
-    pub fn smart_refresh(cache: &mut CachedNetworkData) -> Result<()> {
-        Self::refresh_dynamic_data(cache)?;
+    /// HTTP client honoring the proxy settings of `config`.
+    /// Without a `proxy_url`, reqwest picks up HTTP_PROXY / HTTPS_PROXY itself.
+    pub fn build_http_client(config: &NVTConfig) -> Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder()
+            .gzip(Self::COMPRESSION_ENABLED)
+            .brotli(Self::COMPRESSION_ENABLED);
+
+        if let Some(url) = &config.proxy_url {
+            let proxy = reqwest::Proxy::all(url)
+                .map_err(|e| NVTError::network(format!("Invalid proxy URL {}", Self::redact_proxy_url(url))).with_source(e))?;
+            builder = builder.proxy(proxy);
+        }
+        if config.proxy_no_verify_ssl {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        builder.build()
+            .map_err(|e| NVTError::network("Failed to create HTTP client").with_source(e))
+    }
+
+    /// The shared client, or one with default settings if no cache was initialized yet
+    fn http_client() -> &'static reqwest::Client {
+        HTTP_CLIENT.get_or_init(|| {
+            Self::build_http_client(&NVTConfig::default()).expect("Failed to create HTTP client")
+        })
+    }
+
+    /// Proxy the API requests go through: the configured one, else the environment's
+    fn active_proxy(config: &NVTConfig) -> Option<String> {
+        config.proxy_url.clone().or_else(|| {
+            ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"]
+                .iter()
+                .find_map(|name| std::env::var(name).ok().filter(|v| !v.is_empty()))
+        })
+    }
+
+    /// Proxy URL with any user:password hidden, for logs and error messages
+    fn redact_proxy_url(url: &str) -> String {
+        match (url.find("://"), url.rfind('@')) {
+            (Some(scheme_end), Some(at)) if at > scheme_end => {
+                format!("{}***{}", &url[..scheme_end + 3], &url[at..])
+            }
+            _ => url.to_string(),
+        }
+    }
+
+    pub async fn smart_refresh(cache: &mut CachedNetworkData, config: &NVTConfig) -> Result<()> {
+        Self::log_passed_arrivals(cache);
+        let predictions = Self::snapshot_eta_predictions(cache);
+        let previous_positions = config.show_speed.then(|| cache.real_time.clone());
+        Self::refresh_dynamic_data(cache, config).await?;
+        Self::log_eta_accuracy(cache, predictions);
+        if let Some(previous) = previous_positions {
+            Self::apply_speed_estimates(&mut cache.real_time, &previous);
+        }
+        Self::publish_mqtt_arrivals(cache);
+        Self::log_new_disruptions(cache);
 
-        if cache.needs_static_refresh(Self::STATIC_DATA_MAX_AGE) {
-            Self::refresh_static_data(cache)?;
+        if cache.needs_static_refresh(config.static_max_age_secs) {
+            Self::refresh_static_data_incremental(cache, config).await?;
         }
 
         Ok(())
     }
 
-    fn fetch_stops() -> Result<Vec<(String, String, f64, f64, Vec<String>)>> {
-        let url = format!(
-            "{}/siri/2.0/bordeaux/stoppoints-discovery.json?AccountKey={}",
-            Self::BASE_URL,
-            Self::API_KEY
-        );
+    /// Send the arrivals at the publisher's stops to the MQTT broker; failures are only reported
+    fn publish_mqtt_arrivals(cache: &CachedNetworkData) {
+        let Some(publisher) = &cache.mqtt_publisher else {
+            return;
+        };
+        if publisher.stop_ids().is_empty() {
+            return;
+        }
 
-        let client = blocking::Client::builder()
-            .timeout(std::time::Duration::from_secs(Self::REQUEST_TIMEOUT_SECS))
-            .build()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to create HTTP client: {}", e)))?;
+        let network = cache.to_network_data();
+        for stop_id in publisher.stop_ids() {
+            let arrivals = Self::get_next_vehicles_for_stop(stop_id, &network);
+            if let Err(e) = publisher.publish_arrivals(stop_id, &arrivals) {
+                eprintln!("⚠️  {}", e);
+            }
+        }
+    }
 
-        let response = client.get(&url)
-            .send()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to fetch stops: {}. Check your internet connection.", e)))?;
+    /// Fill `speed_kmh` for vehicles that were in the previous snapshot. A vehicle
+    /// without a new GPS fix keeps its previous estimate.
+    fn apply_speed_estimates(real_time: &mut [RealTimeInfo], previous: &[RealTimeInfo]) {
+        let previous: HashMap<&str, &RealTimeInfo> = previous.iter()
+            .filter(|rt| !Self::is_scheduled(rt))
+            .map(|rt| (rt.vehicle_id.as_str(), rt))
+            .collect();
 
-        if !response.status().is_success() {
-            return Err(NVTError::NetworkError(format!("API returned error: {}", response.status())));
+        for rt in real_time.iter_mut() {
+            let Some(old) = previous.get(rt.vehicle_id.as_str()) else {
+                continue;
+            };
+            let (Some(old_ts), Some(new_ts)) = (old.timestamp, rt.timestamp) else {
+                continue;
+            };
+            if (rt.latitude == 0.0 && rt.longitude == 0.0) || (old.latitude == 0.0 && old.longitude == 0.0) {
+                continue;
+            }
+
+            rt.speed_kmh = if new_ts == old_ts {
+                old.speed_kmh
+            } else {
+                Self::estimate_speed_kmh((old.latitude, old.longitude, old_ts), (rt.latitude, rt.longitude, new_ts))
+            };
+        }
+    }
+
+    /// Average speed between two (lat, lon, timestamp) GPS fixes, clamped to 0-120 km/h.
+    /// None when the fixes are out of order or more than two minutes apart.
+    pub fn estimate_speed_kmh(old: (f64, f64, i64), new: (f64, f64, i64)) -> Option<f32> {
+        const MAX_SPEED_KMH: f32 = 120.0;
+        const MAX_INTERVAL_SECS: i64 = 120;
+
+        let elapsed = new.2 - old.2;
+        if elapsed <= 0 || elapsed > MAX_INTERVAL_SECS {
+            return None;
+        }
+
+        let distance_m = Self::haversine_m(old.0, old.1, new.0, new.1);
+        Some(((distance_m / elapsed as f64 * 3.6) as f32).clamp(0.0, MAX_SPEED_KMH))
+    }
+
+    /// Record delayed arrivals whose time passed since the last dynamic refresh,
+    /// before the refresh replaces them. Each arrival is logged once.
+    fn log_passed_arrivals(cache: &mut CachedNetworkData) {
+        let Some(logger) = cache.arrival_logger.clone() else {
+            return;
+        };
+        let mut logger = match logger.lock() {
+            Ok(logger) => logger,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        let now = Utc::now().timestamp();
+        let since = cache.last_dynamic_update as i64;
+
+        for trip_update in &cache.trip_updates {
+            let route_id = trip_update.trip.route_id.as_deref().unwrap_or("unknown");
+
+            for stu in &trip_update.stop_time_update {
+                let event = stu.arrival.as_ref().or(stu.departure.as_ref());
+                let (Some(stop_id), Some(actual), Some(delay)) = (
+                    stu.stop_id.as_deref(),
+                    event.and_then(|e| e.time),
+                    event.and_then(|e| e.delay),
+                ) else {
+                    continue;
+                };
+
+                if actual < since || actual >= now {
+                    continue;
+                }
+
+                let stop_id = Self::normalize_stop_id(stop_id);
+                if let Err(e) = logger.record(&stop_id, route_id, actual - delay as i64, actual, delay) {
+                    eprintln!("⚠️  Arrival logging to {:?} stopped: {}", logger.path(), e);
+                    drop(logger);
+                    cache.arrival_logger = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Append the disruption reports of routes that weren't disrupted at the previous refresh
+    /// to the arrival log
+    fn log_new_disruptions(cache: &mut CachedNetworkData) {
+        let Some(logger) = cache.arrival_logger.clone() else {
+            return;
+        };
+        let reports = Self::detect_service_disruption(cache);
+        let mut logger = match logger.lock() {
+            Ok(logger) => logger,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        for report in reports.iter().filter(|r| !cache.reported_disruptions.contains(&r.route_id)) {
+            let record = serde_json::json!({
+                "ts": Utc::now().timestamp(),
+                "route": report.route_id,
+                "disruption": true,
+                "expected": report.expected_vehicles,
+                "observed": report.observed_vehicles,
+                "confidence": report.confidence,
+            });
+            if let Err(e) = logger.write_record(record) {
+                eprintln!("⚠️  Arrival logging to {:?} stopped: {}", logger.path(), e);
+                drop(logger);
+                cache.arrival_logger = None;
+                return;
+            }
+        }
+        cache.reported_disruptions = reports.into_iter().map(|r| r.route_id).collect();
+    }
+
+    /// Predicted arrival of each GPS-tracked vehicle at its current stop, by vehicle_id,
+    /// taken from the trip updates before a refresh replaces them. Empty when not logging.
+    fn snapshot_eta_predictions(cache: &CachedNetworkData) -> HashMap<String, EtaPrediction> {
+        if cache.arrival_logger.is_none() {
+            return HashMap::new();
+        }
+
+        let trip_updates: HashMap<&str, &gtfs_rt::TripUpdate> = cache.trip_updates.iter()
+            .filter_map(|tu| tu.trip.trip_id.as_deref().map(|trip_id| (trip_id, tu)))
+            .collect();
+
+        cache.real_time.iter()
+            .filter(|rt| !Self::is_scheduled(rt))
+            .filter_map(|rt| {
+                let stop_id = rt.stop_id.as_ref()?;
+                let route_id = rt.route_id.as_ref()?;
+                let predicted = trip_updates.get(rt.trip_id.as_str())?
+                    .stop_time_update.iter()
+                    .find(|stu| stu.stop_id.as_deref().map(Self::normalize_stop_id).as_ref() == Some(stop_id))
+                    .and_then(|stu| stu.arrival.as_ref().or(stu.departure.as_ref()))
+                    .and_then(|event| event.time)?;
+
+                Some((rt.vehicle_id.clone(), EtaPrediction {
+                    stop_id: stop_id.clone(),
+                    route_id: route_id.clone(),
+                    predicted,
+                }))
+            })
+            .collect()
+    }
+
+    /// Log the ETA error of vehicles that passed the stop they were heading to at the previous
+    /// refresh: their stop_id changed, or they left the feed. The actual time is the first GPS
+    /// fix confirming it (the refresh time for vehicles no longer reported).
+    fn log_eta_accuracy(cache: &mut CachedNetworkData, predictions: HashMap<String, EtaPrediction>) {
+        let Some(logger) = cache.arrival_logger.clone() else {
+            return;
+        };
+        let mut logger = match logger.lock() {
+            Ok(logger) => logger,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        let current: HashMap<&str, &RealTimeInfo> = cache.real_time.iter()
+            .map(|rt| (rt.vehicle_id.as_str(), rt))
+            .collect();
+
+        for (vehicle_id, prediction) in &predictions {
+            let actual = match current.get(vehicle_id.as_str()) {
+                Some(rt) if rt.stop_id.as_ref() == Some(&prediction.stop_id) => continue,
+                Some(rt) => rt.timestamp.unwrap_or(cache.last_dynamic_update as i64),
+                None => cache.last_dynamic_update as i64,
+            };
+
+            if let Err(e) = Self::record_eta_accuracy(prediction.predicted, actual, &prediction.route_id, &prediction.stop_id, &mut logger) {
+                eprintln!("⚠️  Arrival logging to {:?} stopped: {}", logger.path(), e);
+                drop(logger);
+                cache.arrival_logger = None;
+                return;
+            }
         }
+    }
+
+    /// Append one ETA error (actual minus predicted arrival, in seconds) to the arrival log
+    pub fn record_eta_accuracy(
+        predicted_ts: i64,
+        actual_ts: i64,
+        route_id: &str,
+        stop_id: &str,
+        logger: &mut ArrivalLogger,
+    ) -> std::io::Result<()> {
+        logger.write_record(serde_json::json!({
+            "ts": Utc::now().timestamp(),
+            "stop": stop_id,
+            "route": route_id,
+            "predicted": predicted_ts,
+            "actual": actual_ts,
+            "eta_error": actual_ts - predicted_ts,
+        }))
+    }
 
-        let body = response.text()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to read response: {}", e)))?;
+    async fn fetch_stops(config: &NVTConfig, etag: Option<&str>) -> Result<Revalidated<Vec<StopMetadata>>> {
+        let _timer = FETCH_DURATION.with_label_values(&["stops"]).start_timer();
+        Self::fetch_with_retry(|| Self::fetch_stops_once(config, etag), config.max_retry_attempts, Self::RETRY_BASE_DELAY_MS).await
+    }
+
+    async fn fetch_stops_once(config: &NVTConfig, etag: Option<&str>) -> Result<Revalidated<Vec<StopMetadata>>> {
+        let url = format!(
+            "{}/siri/2.0/bordeaux/stoppoints-discovery.json?AccountKey={}",
+            config.api_base_url,
+            config.api_key
+        );
+
+        let Revalidated::Modified(body, etag) = Self::fetch_discovery(&url, etag, "stops", config).await? else {
+            return Ok(Revalidated::NotModified);
+        };
 
-        let json: serde_json::Value = serde_json::from_str(&body)
-            .map_err(|e| NVTError::ParseError(format!("Invalid JSON response: {}", e)))?;
+        let json: serde_json::Value = serde_json::from_slice(&body)?;
 
         let stop_points = json["Siri"]["StopPointsDelivery"]["AnnotatedStopPointRef"]
             .as_array()
-            .ok_or_else(|| NVTError::ParseError("Missing or invalid stop points data in API response".to_string()))?;
+            .ok_or_else(|| NVTError::parse("Missing or invalid stop points data in API response"))?;
 
+        let mut stop_refs = HashMap::new();
         let stops: Vec<_> = stop_points
             .iter()
             .filter_map(|stop| {
@@ -416,46 +2046,40 @@ impl NVTModels {
                     })
                     .unwrap_or_default();
 
+                stop_refs.insert(stop_id.clone(), full_id.to_string());
                 Some((stop_id, stop_name, latitude, longitude, lines))
             })
             .collect();
 
         if stops.is_empty() {
-            return Err(NVTError::ParseError("No valid stops found in API response".to_string()));
+            return Err(NVTError::parse("No valid stops found in API response"));
         }
 
-        Ok(stops)
+        SIRI_STOP_REFS.lock().unwrap_or_else(|e| e.into_inner()).extend(stop_refs);
+        Ok(Revalidated::Modified(stops, etag))
+    }
+
+    async fn fetch_lines(config: &NVTConfig, etag: Option<&str>) -> Result<Revalidated<Vec<LineMetadata>>> {
+        let _timer = FETCH_DURATION.with_label_values(&["lines"]).start_timer();
+        Self::fetch_with_retry(|| Self::fetch_lines_once(config, etag), config.max_retry_attempts, Self::RETRY_BASE_DELAY_MS).await
     }
 
-    fn fetch_lines() -> Result<Vec<(String, String, String, Vec<(String, String)>)>> {
+    async fn fetch_lines_once(config: &NVTConfig, etag: Option<&str>) -> Result<Revalidated<Vec<LineMetadata>>> {
         let url = format!(
             "{}/siri/2.0/bordeaux/lines-discovery.json?AccountKey={}",
-            Self::BASE_URL,
-            Self::API_KEY
+            config.api_base_url,
+            config.api_key
         );
 
-        let client = blocking::Client::builder()
-            .timeout(std::time::Duration::from_secs(Self::REQUEST_TIMEOUT_SECS))
-            .build()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to create HTTP client: {}", e)))?;
-
-        let response = client.get(&url)
-            .send()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to fetch lines: {}. Check your internet connection.", e)))?;
-
-        if !response.status().is_success() {
-            return Err(NVTError::NetworkError(format!("API returned error: {}", response.status())));
-        }
-
-        let body = response.text()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to read response: {}", e)))?;
+        let Revalidated::Modified(body, etag) = Self::fetch_discovery(&url, etag, "lines", config).await? else {
+            return Ok(Revalidated::NotModified);
+        };
 
-        let json: serde_json::Value = serde_json::from_str(&body)
-            .map_err(|e| NVTError::ParseError(format!("Invalid JSON response: {}", e)))?;
+        let json: serde_json::Value = serde_json::from_slice(&body)?;
 
         let line_refs = json["Siri"]["LinesDelivery"]["AnnotatedLineRef"]
             .as_array()
-            .ok_or_else(|| NVTError::ParseError("Missing or invalid lines data in API response".to_string()))?;
+            .ok_or_else(|| NVTError::parse("Missing or invalid lines data in API response"))?;
 
         let lines: Vec<_> = line_refs
             .iter()
@@ -481,33 +2105,432 @@ impl NVTModels {
             .collect();
 
         if lines.is_empty() {
-            return Err(NVTError::ParseError("No valid lines found in API response".to_string()));
+            return Err(NVTError::parse("No valid lines found in API response"));
         }
 
-        Ok(lines)
+        Ok(Revalidated::Modified(lines, etag))
     }
 
-    fn fetch_alerts() -> Result<Vec<AlertInfo>> {
-        let url = format!(
-            "{}/gtfsfeed/alerts/bordeaux?apiKey={}",
-            Self::BASE_URL,
-            Self::API_KEY
-        );
-
-        let client = blocking::Client::builder()
-            .timeout(std::time::Duration::from_secs(Self::REQUEST_TIMEOUT_SECS))
-            .build()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to create HTTP client: {}", e)))?;
+    /// GET a SIRI-Lite discovery endpoint, revalidating with If-None-Match when an ETag is known
+    async fn fetch_discovery(url: &str, etag: Option<&str>, context: &str, config: &NVTConfig) -> Result<Revalidated<Vec<u8>>> {
+        let mut request = Self::http_client().get(url).timeout(config.request_timeout());
+        if let Some(etag) = etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
 
-        let response = client.get(&url)
+        let response = request
             .send()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to fetch alerts: {}", e)))?;
-
-        let body = response.bytes()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to read alerts response: {}", e)))?;
-
-        let feed = FeedMessage::decode(&*body)
-            .map_err(|e| NVTError::ParseError(format!("Failed to decode alerts feed: {}", e)))?;
+            .await
+            .map_err(|e| NVTError::network(format!("Failed to fetch {} (check your internet connection)", context)).with_source(e))?;
+
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(Revalidated::NotModified);
+        }
+        if !response.status().is_success() {
+            return Err(NVTError::http(response.status(), format!("Failed to fetch {}: API returned error: {}", context, response.status())));
+        }
+
+        let etag = response.headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+        let body = Self::read_response_body(response, context).await?;
+        Ok(Revalidated::Modified(body, etag))
+    }
+
+    async fn fetch_alerts(config: &NVTConfig) -> Result<Vec<AlertInfo>> {
+        let url = format!(
+            "{}/gtfsfeed/alerts/bordeaux?apiKey={}",
+            config.api_base_url,
+            config.api_key
+        );
+
+        let mut alerts = Self::fetch_alerts_from_url(&url, config).await?;
+        // SIRI-SX copies of GTFS-RT alerts are merged by deduplicate_alerts below
+        if config.use_siri_alerts {
+            match Self::fetch_service_alerts_siri(config).await {
+                Ok(siri_alerts) => alerts.extend(siri_alerts),
+                Err(e) => eprintln!("⚠️  Warning: Could not fetch SIRI-SX alerts ({})", e),
+            }
+        }
+        RAW_ALERTS_RECEIVED.store(alerts.len(), Ordering::Relaxed);
+        let mut alerts = Self::deduplicate_alerts(alerts);
+        Self::rank_alerts_by_severity(&mut alerts);
+        Ok(alerts)
+    }
+
+    /// Check that the configured API accepts the configured key with a minimal lines
+    /// discovery request, without reading the response body
+    pub async fn validate_api_key(config: &NVTConfig) -> Result<()> {
+        Self::validate_api_key_at(&config.api_base_url, &config.api_key).await
+    }
+
+    /// `validate_api_key` against another API root, e.g. a test server
+    pub async fn validate_api_key_at(base_url: &str, api_key: &str) -> Result<()> {
+        const VALIDATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+        let url = format!(
+            "{}/siri/2.0/bordeaux/lines-discovery.json?AccountKey={}&MaximumStopAreas=1",
+            base_url.trim_end_matches('/'),
+            api_key
+        );
+        let response = Self::http_client().get(&url)
+            .timeout(VALIDATION_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| NVTError::network("API unreachable").with_source(e))?;
+
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            status @ (reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN) => Err(NVTError::http(
+                status,
+                format!("{}: HTTP {}", Self::INVALID_API_KEY_MSG, status.as_u16()),
+            )),
+            status => Err(NVTError::http(status, format!("API returned error: {}", status))),
+        }
+    }
+
+    /// Whether an error is the API rejecting the key (HTTP 401 or 403)
+    pub fn is_invalid_api_key(error: &NVTError) -> bool {
+        matches!(error, NVTError::HttpError { status: 401 | 403, .. })
+    }
+
+    /// Fetch the situations published on the SIRI Situation Exchange endpoint, which can
+    /// carry more detail than the GTFS-RT alerts feed. Alert ids are prefixed with "siri:".
+    pub async fn fetch_service_alerts_siri(config: &NVTConfig) -> Result<Vec<AlertInfo>> {
+        let _timer = FETCH_DURATION.with_label_values(&["siri_alerts"]).start_timer();
+        Self::fetch_with_retry(|| Self::fetch_service_alerts_siri_once(config), config.max_retry_attempts, Self::RETRY_BASE_DELAY_MS).await
+    }
+
+    async fn fetch_service_alerts_siri_once(config: &NVTConfig) -> Result<Vec<AlertInfo>> {
+        let url = format!(
+            "{}/siri/2.0/bordeaux/general-message.json?AccountKey={}",
+            config.api_base_url.trim_end_matches('/'),
+            config.api_key
+        );
+
+        let response = Self::http_client().get(&url)
+            .timeout(config.request_timeout())
+            .send()
+            .await
+            .map_err(|e| NVTError::network("Failed to fetch SIRI-SX alerts").with_source(e))?;
+
+        if !response.status().is_success() {
+            return Err(NVTError::http(response.status(), format!("API returned error: {}", response.status())));
+        }
+
+        let body = Self::read_response_body(response, "SIRI-SX alerts").await?;
+        let json: serde_json::Value = serde_json::from_slice(&body)?;
+
+        Self::parse_siri_situations(&json)
+    }
+
+    /// Map each `PtSituationElement` of a SIRI-SX delivery to an `AlertInfo`
+    fn parse_siri_situations(json: &serde_json::Value) -> Result<Vec<AlertInfo>> {
+        let delivery = &json["Siri"]["ServiceDelivery"];
+        if delivery.is_null() {
+            return Err(NVTError::parse("Missing ServiceDelivery in SIRI-SX response"));
+        }
+
+        let situations = Self::siri_list(&delivery["SituationExchangeDelivery"])
+            .into_iter()
+            .flat_map(|exchange| Self::siri_list(&exchange["Situations"]["PtSituationElement"]));
+
+        let alerts = situations
+            .enumerate()
+            .map(|(idx, situation)| {
+                let id = Self::siri_text(&situation["SituationNumber"])
+                    .unwrap_or_else(|| idx.to_string());
+                let text = Self::siri_text(&situation["Summary"])
+                    .unwrap_or_else(|| "No title".to_string());
+                let description = Self::siri_text(&situation["Description"])
+                    .or_else(|| Self::siri_text(&situation["Detail"]))
+                    .unwrap_or_else(|| "No description available".to_string());
+                let url = Self::siri_list(&situation["InfoLinks"]["InfoLink"])
+                    .into_iter()
+                    .find_map(|link| Self::siri_text(&link["Uri"]));
+
+                let affects = &situation["Affects"];
+                let route_ids = Self::siri_list(&affects["Networks"]["AffectedNetwork"])
+                    .into_iter()
+                    .flat_map(|network| Self::siri_list(&network["AffectedLine"]))
+                    .filter_map(|line| Self::siri_text(&line["LineRef"]))
+                    .map(|line_ref| Self::extract_line_id(&line_ref).map(String::from).unwrap_or(line_ref))
+                    .collect();
+                let stop_ids = Self::siri_list(&affects["StopPoints"]["AffectedStopPoint"])
+                    .into_iter()
+                    .filter_map(|stop| Self::extract_stop_id(&Self::siri_text(&stop["StopPointRef"])?))
+                    .collect();
+
+                let period = Self::siri_list(&situation["ValidityPeriod"]).into_iter().next();
+                let timestamp = |field: &str| {
+                    let time = Self::siri_text(&period?[field])?;
+                    DateTime::parse_from_rfc3339(&time).ok().map(|t| t.timestamp())
+                };
+
+                // SIRI SeverityEnumeration mapped onto the GTFS-RT SeverityLevel scale
+                let severity = match Self::siri_text(&situation["Severity"]).as_deref() {
+                    Some("verySlight" | "slight") => 2,
+                    Some("normal") => 3,
+                    Some("severe" | "verySevere") => 4,
+                    _ => 0,
+                };
+
+                AlertInfo {
+                    id: format!("siri:{}", id),
+                    text,
+                    description,
+                    url,
+                    route_ids,
+                    stop_ids,
+                    active_period_start: timestamp("StartTime"),
+                    active_period_end: timestamp("EndTime"),
+                    severity,
+                }
+            })
+            .collect();
+
+        Ok(alerts)
+    }
+
+    /// SIRI JSON repeats elements as an array, or inlines a single one as an object
+    fn siri_list(value: &serde_json::Value) -> Vec<&serde_json::Value> {
+        match value {
+            serde_json::Value::Array(items) => items.iter().collect(),
+            serde_json::Value::Null => Vec::new(),
+            single => vec![single],
+        }
+    }
+
+    /// Text of a SIRI field: a plain string, `{"value": ...}`, or the first of a list of those
+    fn siri_text(value: &serde_json::Value) -> Option<String> {
+        match value {
+            serde_json::Value::String(text) => Some(text.clone()),
+            serde_json::Value::Array(items) => items.first().and_then(Self::siri_text),
+            serde_json::Value::Object(_) => value["value"].as_str().map(String::from),
+            _ => None,
+        }
+        .filter(|text| !text.trim().is_empty())
+    }
+
+    /// Scheduled departures of a line at a stop from the SIRI-Lite stop timetable, from
+    /// `at_time` on. Entries use vehicle_id "static" like the GTFS timetable fallback.
+    /// Stops the endpoint doesn't cover (404) have no departures.
+    pub async fn fetch_line_timetable_siri(
+        line_ref: &str,
+        stop_id: &str,
+        at_time: i64,
+        config: &NVTConfig,
+    ) -> Result<Vec<RealTimeInfo>> {
+        let url = format!("{}/siri/2.0/bordeaux/stop-timetable.json", config.api_base_url);
+        let monitoring_ref = Self::siri_stop_ref(stop_id);
+
+        let response = Self::http_client().get(&url)
+            .query(&[("LineRef", line_ref), ("MonitoringRef", &monitoring_ref), ("AccountKey", &config.api_key)])
+            .timeout(config.request_timeout())
+            .send()
+            .await
+            .map_err(|e| NVTError::network("Failed to fetch stop timetable").with_source(e))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            log::debug!("No SIRI stop timetable for line {} at stop {}", line_ref, stop_id);
+            return Ok(Vec::new());
+        }
+        if !response.status().is_success() {
+            return Err(NVTError::http(response.status(), format!("API returned error: {}", response.status())));
+        }
+
+        let body = Self::read_response_body(response, "stop timetable").await?;
+        let json: serde_json::Value = serde_json::from_slice(&body)?;
+
+        Ok(Self::parse_siri_stop_timetable(&json, stop_id, at_time))
+    }
+
+    /// StopPointRef of a stop as the SIRI-Lite stops discovery published it, for the
+    /// endpoints taking a stop ref. The stop_id itself when stops came from a cache.
+    pub fn siri_stop_ref(stop_id: &str) -> String {
+        SIRI_STOP_REFS.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&Self::normalize_stop_id(stop_id))
+            .cloned()
+            .unwrap_or_else(|| stop_id.to_string())
+    }
+
+    /// Map the `TimetabledStopVisit`s of a SIRI stop timetable delivery to arrivals at `stop_id`
+    fn parse_siri_stop_timetable(json: &serde_json::Value, stop_id: &str, at_time: i64) -> Vec<RealTimeInfo> {
+        let visits = Self::siri_list(&json["Siri"]["ServiceDelivery"]["StopTimetableDelivery"])
+            .into_iter()
+            .flat_map(|delivery| Self::siri_list(&delivery["TimetabledStopVisit"]));
+
+        let mut departures: Vec<RealTimeInfo> = visits
+            .filter_map(|visit| {
+                let journey = &visit["TargetedVehicleJourney"];
+                let call = &journey["TargetedCall"];
+                let time = Self::siri_text(&call["AimedDepartureTime"])
+                    .or_else(|| Self::siri_text(&call["AimedArrivalTime"]))?;
+                let timestamp = DateTime::parse_from_rfc3339(&time).ok()?.timestamp();
+                if timestamp < at_time {
+                    return None;
+                }
+
+                let route_id = Self::siri_text(&journey["LineRef"])
+                    .map(|line_ref| Self::extract_line_id(&line_ref).map(String::from).unwrap_or(line_ref));
+                let direction_id = Self::siri_text(&journey["DirectionRef"]).and_then(|d| d.parse().ok());
+                let trip_id = Self::siri_text(&journey["FramedVehicleJourneyRef"]["DatedVehicleJourneyRef"])
+                    .unwrap_or_else(|| format!("siri:{}:{}", route_id.as_deref().unwrap_or_default(), timestamp));
+
+                Some(RealTimeInfo {
+                    vehicle_id: "static".to_string(),
+                    trip_id,
+                    route_id,
+                    direction_id,
+                    destination: Self::siri_text(&journey["DestinationName"]),
+                    latitude: 0.0,
+                    longitude: 0.0,
+                    stop_id: Some(Self::normalize_stop_id(stop_id)),
+                    timestamp: Some(timestamp),
+                    delay: None,
+                    occupancy: None,
+                    cancelled: false,
+                    speed_kmh: None,
+                })
+            })
+            .collect();
+
+        departures.sort_by_key(|rt| rt.timestamp);
+        departures
+    }
+
+    /// Most severe alerts first, then by start time (alerts without one last)
+    pub fn rank_alerts_by_severity(alerts: &mut [AlertInfo]) {
+        alerts.sort_by_key(|a| (std::cmp::Reverse(a.severity), a.active_period_start.unwrap_or(i64::MAX)));
+    }
+
+    /// Label of a GTFS-RT SeverityLevel (2 INFO, 3 WARNING, 4 SEVERE; 1 or missing is unknown)
+    pub fn severity_label(severity: u32) -> &'static str {
+        match severity {
+            2 => "ℹ️ Info",
+            3 => "⚠️ Warning",
+            4 => "🚨 Severe",
+            _ => "❔ Unknown",
+        }
+    }
+
+    /// Fetch alerts affecting one stop using the feed's stopId filter.
+    /// Falls back to client-side filtering if the endpoint ignores the parameter.
+    pub async fn fetch_alerts_for_stop(stop_id: &str, config: &NVTConfig) -> Result<Vec<AlertInfo>> {
+        let url = Self::api_url(
+            &config.api_base_url,
+            "gtfsfeed/alerts/bordeaux",
+            &[("apiKey", &config.api_key), ("stopId", stop_id)],
+        )?;
+
+        let alerts = Self::fetch_alerts_from_url(url.as_str(), config).await?;
+        Ok(Self::filter_targeted_alerts(alerts, "stopId", |alert| {
+            alert.stop_ids.iter().any(|id| id == stop_id)
+        }))
+    }
+
+    /// Fetch alerts affecting one route using the feed's routeId filter.
+    /// Falls back to client-side filtering if the endpoint ignores the parameter.
+    pub async fn fetch_alerts_for_route(route_id: &str, config: &NVTConfig) -> Result<Vec<AlertInfo>> {
+        let url = Self::api_url(
+            &config.api_base_url,
+            "gtfsfeed/alerts/bordeaux",
+            &[("apiKey", &config.api_key), ("routeId", route_id)],
+        )?;
+
+        let alerts = Self::fetch_alerts_from_url(url.as_str(), config).await?;
+        Ok(Self::filter_targeted_alerts(alerts, "routeId", |alert| {
+            alert.route_ids.iter().any(|id| id == route_id)
+        }))
+    }
+
+    /// `{base_url}/{path}` with `params` percent-encoded into the query string
+    fn api_url(base_url: &str, path: &str, params: &[(&str, &str)]) -> Result<reqwest::Url> {
+        let url = format!("{}/{}", base_url.trim_end_matches('/'), path);
+        reqwest::Url::parse_with_params(&url, params)
+            .map_err(|e| NVTError::network(format!("Invalid API URL {}", url)).with_source(e))
+    }
+
+    /// A filtered response should only contain matching alerts; if some entities
+    /// don't match, the endpoint returned the full feed and we filter ourselves.
+    fn filter_targeted_alerts<F>(alerts: Vec<AlertInfo>, param: &str, matches: F) -> Vec<AlertInfo>
+    where
+        F: Fn(&AlertInfo) -> bool,
+    {
+        let total = alerts.len();
+        let filtered: Vec<AlertInfo> = alerts.into_iter().filter(|alert| matches(alert)).collect();
+
+        if filtered.len() < total {
+            log::debug!("Alerts endpoint ignored {} filter ({} of {} entities match), filtered locally",
+                        param, filtered.len(), total);
+        }
+
+        filtered
+    }
+
+    /// Insert freshly fetched alerts into the cache, replacing entries with the same ID
+    pub fn merge_alerts(cache: &mut CachedNetworkData, alerts: Vec<AlertInfo>) {
+        for alert in alerts {
+            match cache.alerts.iter_mut().find(|a| a.id == alert.id) {
+                Some(existing) => *existing = alert,
+                None => cache.alerts.push(alert),
+            }
+        }
+        // Targeted feeds may repeat a cached disruption under another id
+        cache.alerts = Self::deduplicate_alerts(std::mem::take(&mut cache.alerts));
+        Self::rank_alerts_by_severity(&mut cache.alerts);
+    }
+
+    /// Merge alerts the feed publishes several times under different ids (e.g. once per
+    /// affected stop): entries with the same text, route_ids in any order and start time
+    /// are one alert. The copy with the most stop_ids is kept, at the first copy's position.
+    pub fn deduplicate_alerts(alerts: Vec<AlertInfo>) -> Vec<AlertInfo> {
+        let mut deduplicated: Vec<AlertInfo> = Vec::with_capacity(alerts.len());
+        let mut index_by_key: HashMap<(String, Vec<String>, Option<i64>), usize> = HashMap::new();
+
+        for alert in alerts {
+            let mut route_ids = alert.route_ids.clone();
+            route_ids.sort();
+            let key = (alert.text.clone(), route_ids, alert.active_period_start);
+
+            match index_by_key.get(&key) {
+                Some(&idx) => {
+                    if alert.stop_ids.len() > deduplicated[idx].stop_ids.len() {
+                        deduplicated[idx] = alert;
+                    }
+                }
+                None => {
+                    index_by_key.insert(key, deduplicated.len());
+                    deduplicated.push(alert);
+                }
+            }
+        }
+
+        deduplicated
+    }
+
+    async fn fetch_alerts_from_url(url: &str, config: &NVTConfig) -> Result<Vec<AlertInfo>> {
+        let _timer = FETCH_DURATION.with_label_values(&["alerts"]).start_timer();
+        Self::fetch_with_retry(|| Self::fetch_alerts_from_url_once(url, config), config.max_retry_attempts, Self::RETRY_BASE_DELAY_MS).await
+    }
+
+    async fn fetch_alerts_from_url_once(url: &str, config: &NVTConfig) -> Result<Vec<AlertInfo>> {
+        let response = Self::http_client().get(url)
+            .timeout(config.request_timeout())
+            .send()
+            .await
+            .map_err(|e| NVTError::network("Failed to fetch alerts").with_source(e))?;
+
+        if !response.status().is_success() {
+            return Err(NVTError::http(response.status(), format!("API returned error: {}", response.status())));
+        }
+
+        let body = Self::read_response_body(response, "alerts").await?;
+
+        let feed = FeedMessage::decode(body.as_slice())
+            .map_err(|e| NVTError::parse("Failed to decode alerts feed").with_source(e))?;
 
         let alerts = feed
             .entity
@@ -536,8 +2559,8 @@ impl NVTModels {
                             route_ids.push(route_id);
                         }
                         if let Some(stop_id) = informed_entity.stop_id {
-                            // Use raw stop_id directly for alerts
-                            stop_ids.push(stop_id);
+                            // Use raw stop_id directly for alerts, only normalized
+                            stop_ids.push(Self::normalize_stop_id(&stop_id));
                         }
                     }
 
@@ -571,27 +2594,32 @@ impl NVTModels {
         Ok(alerts)
     }
 
-    fn fetch_vehicle_positions() -> Result<Vec<RealTimeInfo>> {
+    async fn fetch_vehicle_positions(config: &NVTConfig) -> Result<Vec<RealTimeInfo>> {
+        let _timer = FETCH_DURATION.with_label_values(&["vehicles"]).start_timer();
+        Self::fetch_with_retry(|| Self::fetch_vehicle_positions_once(config), config.max_retry_attempts, Self::RETRY_BASE_DELAY_MS).await
+    }
+
+    async fn fetch_vehicle_positions_once(config: &NVTConfig) -> Result<Vec<RealTimeInfo>> {
         let url = format!(
             "{}/gtfsfeed/vehicles/bordeaux?apiKey={}",
-            Self::BASE_URL,
-            Self::API_KEY
+            config.api_base_url,
+            config.api_key
         );
 
-        let client = blocking::Client::builder()
-            .timeout(std::time::Duration::from_secs(Self::REQUEST_TIMEOUT_SECS))
-            .build()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to create HTTP client: {}", e)))?;
-
-        let response = client.get(&url)
+        let response = Self::http_client().get(&url)
+            .timeout(config.request_timeout())
             .send()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to fetch vehicle positions: {}", e)))?;
+            .await
+            .map_err(|e| NVTError::network("Failed to fetch vehicle positions").with_source(e))?;
 
-        let body = response.bytes()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to read vehicles response: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(NVTError::http(response.status(), format!("API returned error: {}", response.status())));
+        }
+
+        let body = Self::read_response_body(response, "vehicles").await?;
 
-        let feed = FeedMessage::decode(&*body)
-            .map_err(|e| NVTError::ParseError(format!("Failed to decode vehicles feed: {}", e)))?;
+        let feed = FeedMessage::decode(body.as_slice())
+            .map_err(|e| NVTError::parse("Failed to decode vehicles feed").with_source(e))?;
 
         let real_time: Vec<RealTimeInfo> = feed
             .entity
@@ -608,7 +2636,7 @@ impl NVTModels {
                         .trip
                         .as_ref()
                         .and_then(|t| t.trip_id.clone())
-                        .unwrap_or_else(|| "Unknown".to_string());
+                        .unwrap_or_else(|| Self::UNKNOWN_TRIP_ID.to_string());
 
                     let route_id = vehicle
                         .trip
@@ -631,9 +2659,11 @@ impl NVTModels {
                         .map(|p| (p.latitude as f64, p.longitude as f64))
                         .unwrap_or((0.0, 0.0));
 
-                    // Use raw stop_id - no extraction needed for vehicles
-                    let stop_id = vehicle.stop_id.clone();
+                    // Raw stop_id needs no extraction for vehicles, only normalization
+                    let stop_id = vehicle.stop_id.as_deref().map(Self::normalize_stop_id);
                     let timestamp = vehicle.timestamp.map(|ts| ts as i64);
+                    let occupancy = vehicle.occupancy_status
+                        .and_then(|status| u32::try_from(status).ok());
 
                     RealTimeInfo {
                         vehicle_id,
@@ -646,6 +2676,9 @@ impl NVTModels {
                         stop_id,
                         timestamp,
                         delay: None,
+                        occupancy,
+                        cancelled: false,
+                        speed_kmh: None,
                     }
                 })
             })
@@ -654,27 +2687,32 @@ impl NVTModels {
         Ok(real_time)
     }
 
-    fn fetch_trip_updates() -> Result<Vec<gtfs_rt::TripUpdate>> {
+    async fn fetch_trip_updates(config: &NVTConfig) -> Result<Vec<gtfs_rt::TripUpdate>> {
+        let _timer = FETCH_DURATION.with_label_values(&["trip_updates"]).start_timer();
+        Self::fetch_with_retry(|| Self::fetch_trip_updates_once(config), config.max_retry_attempts, Self::RETRY_BASE_DELAY_MS).await
+    }
+
+    async fn fetch_trip_updates_once(config: &NVTConfig) -> Result<Vec<gtfs_rt::TripUpdate>> {
         let url = format!(
             "{}/gtfsfeed/realtime/bordeaux?apiKey={}",
-            Self::BASE_URL,
-            Self::API_KEY
+            config.api_base_url,
+            config.api_key
         );
 
-        let client = blocking::Client::builder()
-            .timeout(std::time::Duration::from_secs(Self::REQUEST_TIMEOUT_SECS))
-            .build()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to create HTTP client: {}", e)))?;
-
-        let response = client.get(&url)
+        let response = Self::http_client().get(&url)
+            .timeout(config.request_timeout())
             .send()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to fetch trip updates: {}", e)))?;
+            .await
+            .map_err(|e| NVTError::network("Failed to fetch trip updates").with_source(e))?;
 
-        let body = response.bytes()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to read trip updates response: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(NVTError::http(response.status(), format!("API returned error: {}", response.status())));
+        }
+
+        let body = Self::read_response_body(response, "trip updates").await?;
 
-        let feed = FeedMessage::decode(&*body)
-            .map_err(|e| NVTError::ParseError(format!("Failed to decode trip updates feed: {}", e)))?;
+        let feed = FeedMessage::decode(body.as_slice())
+            .map_err(|e| NVTError::parse("Failed to decode trip updates feed").with_source(e))?;
 
         let updates = feed
             .entity
@@ -685,42 +2723,156 @@ impl NVTModels {
         Ok(updates)
     }
 
-    fn download_and_read_routes() -> Result<HashMap<String, String>> {
-        if let Some(cache) = GTFSCache::load() {
-            return Ok(cache.routes);
+    /// Run `f` up to `max_attempts` times while it fails with a transient error (timeout,
+    /// connection failure or 5xx, see `NVTError::is_transient`), sleeping `base_delay_ms`
+    /// (doubled after each attempt, ±10% jitter) in between. Other errors, such as a 401
+    /// or a parse error, are returned immediately since retrying won't fix them.
+    async fn fetch_with_retry<F, Fut, T>(f: F, max_attempts: u32, base_delay_ms: u64) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        let mut delay_ms = base_delay_ms;
+        let mut attempt = 1;
+
+        loop {
+            match f().await {
+                Ok(value) => return Ok(value),
+                Err(e) if e.is_transient() && attempt < max_attempts => {
+                    let jittered_ms = Self::jitter_delay_ms(delay_ms);
+                    eprintln!("⚠️  Attempt {}/{} failed ({}), retrying in {} ms...",
+                              attempt, max_attempts, e, jittered_ms);
+                    tokio::time::sleep(std::time::Duration::from_millis(jittered_ms)).await;
+                    delay_ms = delay_ms.saturating_mul(2);
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
         }
+    }
 
-        println!("📥 Downloading fresh GTFS data (this may take a moment)...");
-        let gtfs_url = "https://transport.data.gouv.fr/resources/83024/download";
+    /// Spread `delay_ms` by up to ±10% so concurrent retries don't fire in lockstep
+    fn jitter_delay_ms(delay_ms: u64) -> u64 {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .subsec_nanos();
+        let factor = 0.9 + (nanos % 2001) as f64 / 10_000.0;
+        (delay_ms as f64 * factor).round() as u64
+    }
 
-        let client = blocking::Client::builder()
-            .timeout(std::time::Duration::from_secs(60))
-            .build()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to create HTTP client: {}", e)))?;
+    /// Read a response body. gzip/brotli decoding is done by the HTTP client
+    /// when `COMPRESSION_ENABLED` is set; the decoded size is added to the traffic counter.
+    async fn read_response_body(response: reqwest::Response, context: &str) -> Result<Vec<u8>> {
+        let body = response.bytes()
+            .await
+            .map_err(|e| NVTError::network(format!("Failed to read {} response", context)).with_source(e))?;
+
+        API_BYTES_RECEIVED.fetch_add(body.len() as u64, Ordering::Relaxed);
+        Ok(body.to_vec())
+    }
+
+    pub fn set_quiet(quiet: bool) {
+        QUIET.store(quiet, Ordering::Relaxed);
+    }
 
-        let response = client.get(gtfs_url)
+    pub fn is_quiet() -> bool {
+        QUIET.load(Ordering::Relaxed)
+    }
+
+    /// Decompressed bytes received from the API since startup
+    pub fn api_bytes_received() -> u64 {
+        API_BYTES_RECEIVED.load(Ordering::Relaxed)
+    }
+
+    /// Alerts in the last full alerts feed, before deduplication
+    pub fn raw_alerts_received() -> usize {
+        RAW_ALERTS_RECEIVED.load(Ordering::Relaxed)
+    }
+
+    /// Download a large file chunk by chunk, drawing a progress bar (byte count only when
+    /// the server sends no Content-Length)
+    pub async fn download_with_progress(url: &str, client: &reqwest::Client) -> Result<Vec<u8>> {
+        let quiet = Self::is_quiet();
+        let mut total_size = 0;
+        let body = Self::download_reporting_progress(url, client, |current, total| {
+            total_size = total;
+            if !quiet {
+                NVTViews::show_progress(current, total, "📥 GTFS");
+            }
+        })
+        .await?;
+
+        // A full bar ends its line by itself; the byte count needs a newline
+        if total_size == 0 && !quiet {
+            println!();
+        }
+        Ok(body)
+    }
+
+    /// Download `url` chunk by chunk, calling `report(bytes_so_far, content_length)` at the
+    /// start, about every 1% (or 256 KB when the length is unknown) and once complete
+    async fn download_reporting_progress(
+        url: &str,
+        client: &reqwest::Client,
+        mut report: impl FnMut(usize, usize),
+    ) -> Result<Vec<u8>> {
+        // Redraw every 1% of the file, or every 256 KB when its size is unknown
+        const UNKNOWN_SIZE_STEP: usize = 256 * 1024;
+
+        let mut response = client.get(url)
+            .timeout(std::time::Duration::from_secs(60))
             .send()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to download GTFS: {}. Check your internet connection.", e)))?;
+            .await
+            .map_err(|e| NVTError::network("Failed to download GTFS (check your internet connection)").with_source(e))?;
 
         if !response.status().is_success() {
-            return Err(NVTError::NetworkError(format!("GTFS download failed with status: {}", response.status())));
+            return Err(NVTError::http(response.status(), format!("GTFS download failed with status: {}", response.status())));
+        }
+
+        let total = response.content_length().map_or(0, |len| len as usize);
+        let step = if total > 0 { (total / 100).max(1) } else { UNKNOWN_SIZE_STEP };
+
+        let mut body = Vec::with_capacity(total);
+        let mut next_report = step;
+        report(0, total);
+        while let Some(chunk) = response.chunk()
+            .await
+            .map_err(|e| NVTError::network("Failed to read GTFS zip").with_source(e))?
+        {
+            body.extend_from_slice(&chunk);
+            if body.len() >= next_report && body.len() < total {
+                report(body.len(), total);
+                next_report = body.len() + step;
+            }
         }
 
-        let zip_bytes = response.bytes()
-            .map_err(|e| NVTError::NetworkError(format!("Failed to read GTFS zip: {}", e)))?;
+        report(if total > 0 { total } else { body.len() }, total);
+        Ok(body)
+    }
+
+    async fn download_and_read_routes() -> Result<GTFSCache> {
+        if let Some(cache) = GTFSCache::load() {
+            return Ok(cache);
+        }
 
-        println!("✓ Downloaded {} KB, extracting...", zip_bytes.len() / 1024);
+        status!("📥 Downloading fresh GTFS data (this may take a moment)...");
+        let gtfs_url = "https://transport.data.gouv.fr/resources/83024/download";
+
+        let zip_bytes = Self::download_with_progress(gtfs_url, Self::http_client()).await?;
+
+        status!("✓ Downloaded {} KB, extracting...", zip_bytes.len() / 1024);
 
         let cursor = Cursor::new(zip_bytes);
         let mut archive = ZipArchive::new(cursor)
-            .map_err(|e| NVTError::ParseError(format!("Failed to open GTFS zip archive: {}", e)))?;
+            .map_err(|e| NVTError::parse("Failed to open GTFS zip archive").with_source(e))?;
 
         let mut routes_file = archive.by_name("routes.txt")
-            .map_err(|e| NVTError::FileError(format!("routes.txt not found in GTFS archive: {}", e)))?;
+            .map_err(|e| NVTError::file("routes.txt not found in GTFS archive").with_source(e))?;
 
         let mut routes_contents = String::new();
         routes_file.read_to_string(&mut routes_contents)
-            .map_err(|e| NVTError::FileError(format!("Failed to read routes.txt: {}", e)))?;
+            .map_err(|e| NVTError::file("Failed to read routes.txt").with_source(e))?;
 
         drop(routes_file);
 
@@ -733,16 +2885,46 @@ impl NVTModels {
             Err(_) => None,
         };
 
+        let translations_contents = match archive.by_name("translations.txt") {
+            Ok(mut file) => {
+                let mut contents = String::new();
+                file.read_to_string(&mut contents).ok();
+                Some(contents)
+            }
+            Err(_) => None,
+        };
+
+        let (scheduled_departures, stop_sequences) = Self::parse_stop_times(&mut archive);
+        let night_routes = Self::find_night_routes(&scheduled_departures);
+        let (shapes, route_shapes) = Self::parse_shapes(&mut archive);
+        let trip_headsigns = Self::parse_trip_headsigns(&mut archive);
+        let service_calendar = Self::parse_service_calendar(&mut archive);
+
         let mut color_map = HashMap::new();
+        let mut route_types = HashMap::new();
         let mut rdr = csv::Reader::from_reader(routes_contents.as_bytes());
+        let route_type_idx = rdr.headers().ok().and_then(|h| h.iter().position(|name| name == "route_type"));
+        let short_name_idx = rdr.headers().ok().and_then(|h| h.iter().position(|name| name == "route_short_name"));
+        let mut route_short_names = HashMap::new();
 
         for result in rdr.records() {
             match result {
                 Ok(record) => {
-                    if let (Some(route_id), Some(route_color)) = (record.get(0), record.get(5)) {
-                        if !route_color.is_empty() && route_color.len() == 6 {
-                            color_map.insert(route_id.to_string(), route_color.to_string());
-                        }
+                    if let (Some(route_id), Some(route_color)) =
+                        (record.get(0), record.get(5).and_then(Self::normalize_hex_color)) {
+                        color_map.insert(route_id.to_string(), route_color);
+                    }
+                    if let (Some(route_id), Some(route_type)) = (
+                        record.get(0),
+                        route_type_idx.and_then(|i| record.get(i)).and_then(|t| t.parse::<u32>().ok()),
+                    ) {
+                        route_types.insert(route_id.to_string(), route_type);
+                    }
+                    if let (Some(route_id), Some(short_name)) = (
+                        record.get(0),
+                        short_name_idx.and_then(|i| record.get(i)).filter(|name| !name.trim().is_empty()),
+                    ) {
+                        route_short_names.insert(route_id.to_string(), short_name.trim().to_string());
                     }
                 }
                 Err(e) => {
@@ -752,11 +2934,28 @@ impl NVTModels {
         }
 
         let mut stops_data = Vec::new();
+        let mut stop_location_types = HashMap::new();
+        let mut stop_wheelchair = HashMap::new();
         if let Some(contents) = stops_contents {
             let mut stops_rdr = csv::Reader::from_reader(contents.as_bytes());
+            let headers = stops_rdr.headers().cloned().unwrap_or_default();
+            let location_type_idx = headers.iter().position(|h| h == "location_type");
+            let wheelchair_idx = headers.iter().position(|h| h == "wheelchair_boarding");
 
             for result in stops_rdr.records() {
                 if let Ok(record) = result {
+                    if let (Some(stop_id), Some(location_type)) = (
+                        record.get(0),
+                        location_type_idx.and_then(|i| record.get(i)).and_then(|t| t.parse::<u8>().ok()),
+                    ) {
+                        stop_location_types.insert(Self::normalize_stop_id(stop_id), location_type);
+                    }
+                    if let (Some(stop_id), Some(wheelchair)) = (
+                        record.get(0),
+                        wheelchair_idx.and_then(|i| record.get(i)).and_then(|w| w.parse::<u8>().ok()),
+                    ) {
+                        stop_wheelchair.insert(Self::normalize_stop_id(stop_id), wheelchair);
+                    }
                     if let (Some(stop_id), Some(stop_name), Some(lat_str), Some(lon_str)) =
                         (record.get(0), record.get(2), record.get(4), record.get(5)) {
                         if let (Ok(lat), Ok(lon)) = (lat_str.parse::<f64>(), lon_str.parse::<f64>()) {
@@ -772,9 +2971,26 @@ impl NVTModels {
             }
         }
 
+        let stop_name_translations = translations_contents
+            .map(|contents| Self::parse_stop_name_translations(&contents, &stops_data))
+            .unwrap_or_default();
+
         let cache = GTFSCache {
             routes: color_map.clone(),
             stops: stops_data,
+            stop_name_translations,
+            scheduled_departures,
+            stop_sequences,
+            shapes,
+            route_shapes,
+            service_calendar,
+            route_types,
+            route_short_names,
+            night_routes,
+            trip_headsigns,
+            stop_location_types,
+            stop_wheelchair,
+            checksum: String::new(),
             cached_at: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap_or_default()
@@ -785,329 +3001,2803 @@ impl NVTModels {
             eprintln!("⚠️  Warning: Could not save GTFS cache: {}", e);
         }
 
-        println!("✓ Loaded {} route colors", color_map.len());
-        println!("✓ Cached {} stops for future use", cache.stops.len());
+        status!("✓ Loaded {} route colors", color_map.len());
+        status!("✓ Cached {} stops for future use", cache.stops.len());
+        if !cache.stop_name_translations.is_empty() {
+            status!("✓ Loaded translated names for {} stops", cache.stop_name_translations.len());
+        }
+        if !cache.scheduled_departures.is_empty() {
+            status!("✓ Loaded static timetable for {} stops", cache.scheduled_departures.len());
+        }
+        if !cache.shapes.is_empty() {
+            status!("✓ Loaded {} route shapes", cache.shapes.len());
+        }
+        if !cache.service_calendar.is_empty() {
+            status!("✓ Loaded {} service calendars", cache.service_calendar.len());
+        }
 
-        Ok(color_map)
+        Ok(cache)
     }
 
-    fn load_line_colors() -> Result<HashMap<String, String>> {
-        Self::download_and_read_routes()
-    }
+    /// Join trips.txt and stop_times.txt into per-stop static departures and,
+    /// for every route direction, the stop sequence of its longest trip.
+    /// Trips of every service day are merged; identical departures are deduplicated.
+    fn parse_stop_times<R: Read + Seek>(archive: &mut ZipArchive<R>) -> (ScheduledDepartures, StopSequences) {
+        let mut departures = ScheduledDepartures::new();
+        let mut sequences = StopSequences::new();
+
+        // trip_id -> (route_id, direction_id, service_id)
+        let mut trips: HashMap<String, (String, u32, String)> = HashMap::new();
+        match archive.by_name("trips.txt") {
+            Ok(file) => {
+                let mut rdr = csv::Reader::from_reader(file);
+                let headers = rdr.headers().cloned().unwrap_or_default();
+                let column = |name: &str| headers.iter().position(|h| h == name);
+                let (Some(trip_idx), Some(route_idx)) = (column("trip_id"), column("route_id")) else {
+                    eprintln!("⚠️  Warning: trips.txt is missing trip_id/route_id columns");
+                    return (departures, sequences);
+                };
+                let direction_idx = column("direction_id");
+                let service_idx = column("service_id");
+
+                for record in rdr.records().flatten() {
+                    if let (Some(trip_id), Some(route_id)) = (record.get(trip_idx), record.get(route_idx)) {
+                        let direction_id = direction_idx
+                            .and_then(|i| record.get(i))
+                            .and_then(|d| d.parse::<u32>().ok())
+                            .unwrap_or(0);
+                        let service_id = service_idx.and_then(|i| record.get(i)).unwrap_or_default();
+                        trips.insert(trip_id.to_string(), (route_id.to_string(), direction_id, service_id.to_string()));
+                    }
+                }
+            }
+            Err(_) => return (departures, sequences),
+        }
 
-    /// Build complete network data with all associations - OPTIMIZED
-    pub fn build_network_data(
-        stops_data: Vec<(String, String, f64, f64, Vec<String>)>,
-        lines_data: Vec<(String, String, String, Vec<(String, String)>)>,
-        alerts: Vec<AlertInfo>,
-        real_time: Vec<RealTimeInfo>,
-        trip_updates: Vec<gtfs_rt::TripUpdate>,
-        line_color_map: HashMap<String, String>,
-    ) -> NetworkData {
-        let line_destinations_map: HashMap<String, Vec<(String, String)>> = lines_data
-            .iter()
-            .filter_map(|(ref_, _, _, destinations)| {
-                let line_id = Self::extract_line_id(ref_)?;
-                Some((line_id.to_string(), destinations.clone()))
-            })
-            .collect();
+        let file = match archive.by_name("stop_times.txt") {
+            Ok(file) => file,
+            Err(_) => return (departures, sequences),
+        };
+        let mut rdr = csv::Reader::from_reader(file);
+        let headers = rdr.headers().cloned().unwrap_or_default();
+        let column = |name: &str| headers.iter().position(|h| h == name);
+        let (Some(trip_idx), Some(stop_idx)) = (column("trip_id"), column("stop_id")) else {
+            eprintln!("⚠️  Warning: stop_times.txt is missing trip_id/stop_id columns");
+            return (departures, sequences);
+        };
+        let departure_idx = column("departure_time");
+        let arrival_idx = column("arrival_time");
+        let sequence_idx = column("stop_sequence");
+
+        // trip_id -> [(stop_sequence, stop_id)]
+        let mut trip_stops: HashMap<&str, Vec<(u32, String)>> = HashMap::new();
+
+        for record in rdr.records().flatten() {
+            let Some((trip_id, (route_id, direction_id, service_id))) = record.get(trip_idx)
+                .and_then(|t| trips.get_key_value(t)) else {
+                continue;
+            };
+            let Some(stop_id) = record.get(stop_idx).map(Self::normalize_stop_id) else {
+                continue;
+            };
+
+            let seconds = departure_idx
+                .and_then(|i| record.get(i))
+                .and_then(Self::parse_gtfs_time)
+                .or_else(|| arrival_idx.and_then(|i| record.get(i)).and_then(Self::parse_gtfs_time));
+            if let Some(seconds) = seconds {
+                departures
+                    .entry(stop_id.clone())
+                    .or_default()
+                    .push((route_id.clone(), *direction_id, seconds, service_id.clone(), trip_id.clone()));
+            }
 
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs() as i64;
+            let sequence = sequence_idx
+                .and_then(|i| record.get(i))
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(u32::MAX);
+            trip_stops.entry(trip_id.as_str()).or_default().push((sequence, stop_id));
+        }
 
-        // Allow arrivals up to 2 minutes in the past (grace period for vehicles at stop)
-        let grace_period = 120; // seconds
-        let cutoff_time = now - grace_period;
+        for stop_departures in departures.values_mut() {
+            stop_departures.sort_by(|a, b| (a.2, &a.0, a.1, &a.3, &a.4).cmp(&(b.2, &b.0, b.1, &b.3, &b.4)));
+            stop_departures.dedup();
+        }
 
-        let mut trip_updates_by_stop: HashMap<String, Vec<(String, Option<String>, Option<u32>, Option<i32>, Option<i64>)>> = HashMap::new();
+        for (trip_id, mut stops) in trip_stops {
+            let (route_id, direction_id, _) = &trips[trip_id];
+            let by_direction = sequences.entry(route_id.clone()).or_default();
+            if by_direction.get(direction_id).is_some_and(|seq| seq.len() >= stops.len()) {
+                continue;
+            }
+            stops.sort_by_key(|(sequence, _)| *sequence);
+            by_direction.insert(*direction_id, stops.into_iter().map(|(_, stop_id)| stop_id).collect());
+        }
 
-        for trip_update in &trip_updates {
-            let trip_id = trip_update.trip.trip_id.clone().unwrap_or_else(|| "Unknown".to_string());
-            let route_id = trip_update.trip.route_id.clone();
-            let direction_id = trip_update.trip.direction_id;
+        (departures, sequences)
+    }
 
-            for stu in &trip_update.stop_time_update {
-                if let Some(stop_id_raw) = &stu.stop_id {
-                    let delay = stu.arrival.as_ref().and_then(|a| a.delay)
-                        .or_else(|| stu.departure.as_ref().and_then(|d| d.delay));
-                    let time = stu.arrival.as_ref().and_then(|a| a.time)
-                        .or_else(|| stu.departure.as_ref().and_then(|d| d.time))
-                        .map(|t| t as i64);
-
-                    if let Some(arrival_time) = time {
-                        // Include arrivals within grace period OR in the future
-                        if arrival_time >= cutoff_time {
-                            let data = (
-                                trip_id.clone(),
-                                route_id.clone(),
-                                direction_id,
-                                delay,
-                                time,
-                            );
-
-                            // Index by raw stop_id (e.g., "5220")
-                            trip_updates_by_stop
-                                .entry(stop_id_raw.clone())
-                                .or_insert_with(Vec::new)
-                                .push(data.clone());
-
-                            // ALSO index by extracted stop_id (in case SIRI uses different format)
-                            if let Some(extracted) = Self::extract_stop_id(stop_id_raw) {
-                                if extracted != *stop_id_raw {
-                                    trip_updates_by_stop
-                                        .entry(extracted)
-                                        .or_insert_with(Vec::new)
-                                        .push(data);
-                                }
-                            }
-                        }
-                    }
+    /// Read shapes.txt into ordered point lists and link each route to a shape via trips.txt.
+    /// When a route uses several shapes, the one with the most points is kept.
+    fn parse_shapes<R: Read + Seek>(archive: &mut ZipArchive<R>) -> (RouteShapes, HashMap<String, String>) {
+        let mut shapes = RouteShapes::new();
+        let mut route_shapes: HashMap<String, String> = HashMap::new();
+
+        match archive.by_name("shapes.txt") {
+            Ok(file) => {
+                let mut rdr = csv::Reader::from_reader(file);
+                let headers = rdr.headers().cloned().unwrap_or_default();
+                let column = |name: &str| headers.iter().position(|h| h == name);
+                let (Some(id_idx), Some(lat_idx), Some(lon_idx), Some(seq_idx)) =
+                    (column("shape_id"), column("shape_pt_lat"), column("shape_pt_lon"), column("shape_pt_sequence"))
+                else {
+                    eprintln!("⚠️  Warning: shapes.txt is missing required columns");
+                    return (shapes, route_shapes);
+                };
+
+                let mut points: HashMap<String, Vec<(u32, f64, f64)>> = HashMap::new();
+                for record in rdr.records().flatten() {
+                    let (Some(shape_id), Some(lat), Some(lon), Some(sequence)) = (
+                        record.get(id_idx),
+                        record.get(lat_idx).and_then(|v| v.parse::<f64>().ok()),
+                        record.get(lon_idx).and_then(|v| v.parse::<f64>().ok()),
+                        record.get(seq_idx).and_then(|v| v.parse::<u32>().ok()),
+                    ) else {
+                        continue;
+                    };
+                    points.entry(shape_id.to_string()).or_default().push((sequence, lat, lon));
+                }
+
+                for (shape_id, mut shape_points) in points {
+                    shape_points.sort_by_key(|(sequence, _, _)| *sequence);
+                    shapes.insert(shape_id, shape_points.into_iter().map(|(_, lat, lon)| (lat, lon)).collect());
                 }
             }
+            Err(_) => return (shapes, route_shapes),
         }
 
-        let stops: Vec<Stop> = stops_data
-            .into_iter()
-            .map(|(id, name, lat, lon, line_refs)| {
-                let mut stop_rt: Vec<RealTimeInfo> = real_time
-                    .iter()
-                    .filter(|rt| {
-                        rt.stop_id
-                            .as_ref()
-                            .map(|sid| sid == &id)
-                            .unwrap_or(false)
-                    })
-                    .cloned()
-                    .collect();
+        if let Ok(file) = archive.by_name("trips.txt") {
+            let mut rdr = csv::Reader::from_reader(file);
+            let headers = rdr.headers().cloned().unwrap_or_default();
+            let column = |name: &str| headers.iter().position(|h| h == name);
+            let (Some(route_idx), Some(shape_idx)) = (column("route_id"), column("shape_id")) else {
+                return (shapes, route_shapes);
+            };
+
+            for record in rdr.records().flatten() {
+                let (Some(route_id), Some(shape_id)) = (record.get(route_idx), record.get(shape_idx)) else {
+                    continue;
+                };
+                let Some(len) = shapes.get(shape_id).map(Vec::len) else {
+                    continue;
+                };
+                let current_len = route_shapes.get(route_id)
+                    .and_then(|id| shapes.get(id))
+                    .map_or(0, Vec::len);
+                if len > current_len {
+                    route_shapes.insert(route_id.to_string(), shape_id.to_string());
+                }
+            }
+        }
 
-                // Add trip updates (scheduled arrivals)
-                if let Some(scheduled_arrivals) = trip_updates_by_stop.get(&id) {
-                    for (trip_id, route_id, direction_id, delay, time) in scheduled_arrivals {
-                        let destination = route_id.as_ref().and_then(|rid| {
-                            line_destinations_map.get(rid).and_then(|destinations| {
-                                direction_id.and_then(|dir_id| {
-                                    destinations.iter()
-                                        .find(|(dir_ref, _)| dir_ref == &dir_id.to_string())
-                                        .map(|(_, place)| place.clone())
-                                })
-                            })
-                        });
+        (shapes, route_shapes)
+    }
 
-                        stop_rt.push(RealTimeInfo {
-                            vehicle_id: "scheduled".to_string(),
-                            trip_id: trip_id.clone(),
-                            route_id: route_id.clone(),
-                            direction_id: *direction_id,
-                            destination,
-                            latitude: lat,
-                            longitude: lon,
-                            stop_id: Some(id.clone()),
-                            timestamp: *time,
-                            delay: *delay,
-                        });
-                    }
-                }
+    /// Read the non-empty trip_headsign of each trip in trips.txt
+    fn parse_trip_headsigns<R: Read + Seek>(archive: &mut ZipArchive<R>) -> HashMap<String, String> {
+        let mut headsigns = HashMap::new();
+        let Ok(file) = archive.by_name("trips.txt") else {
+            return headsigns;
+        };
 
-                // Keep arrivals within grace period OR future arrivals
-                stop_rt.retain(|rt| {
-                    if let Some(ts) = rt.timestamp {
-                        ts >= cutoff_time
-                    } else {
-                        true
+        let mut rdr = csv::Reader::from_reader(file);
+        let headers = rdr.headers().cloned().unwrap_or_default();
+        let column = |name: &str| headers.iter().position(|h| h == name);
+        let (Some(trip_idx), Some(headsign_idx)) = (column("trip_id"), column("trip_headsign")) else {
+            return headsigns;
+        };
+
+        for record in rdr.records().flatten() {
+            if let (Some(trip_id), Some(headsign)) = (record.get(trip_idx), record.get(headsign_idx))
+                && !headsign.is_empty()
+            {
+                headsigns.insert(trip_id.to_string(), headsign.to_string());
+            }
+        }
+
+        headsigns
+    }
+
+    /// Read calendar.txt weekly patterns and merge calendar_dates.txt exceptions.
+    /// Services listed only in calendar_dates.txt get an empty weekly pattern.
+    fn parse_service_calendar<R: Read + Seek>(archive: &mut ZipArchive<R>) -> HashMap<String, ServiceCalendar> {
+        let mut calendar: HashMap<String, ServiceCalendar> = HashMap::new();
+
+        if let Ok(file) = archive.by_name("calendar.txt") {
+            let mut rdr = csv::Reader::from_reader(file);
+            let headers = rdr.headers().cloned().unwrap_or_default();
+            let column = |name: &str| headers.iter().position(|h| h == name);
+
+            if let Some(service_idx) = column("service_id") {
+                let day_idx = ["monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday"].map(column);
+                let start_idx = column("start_date");
+                let end_idx = column("end_date");
+
+                for record in rdr.records().flatten() {
+                    let Some(service_id) = record.get(service_idx) else {
+                        continue;
+                    };
+                    let days = day_idx.map(|idx| idx.and_then(|i| record.get(i)) == Some("1"));
+                    let date = |idx: Option<usize>| idx.and_then(|i| record.get(i)).and_then(|v| v.parse::<u32>().ok());
+
+                    calendar.insert(service_id.to_string(), ServiceCalendar {
+                        monday: days[0],
+                        tuesday: days[1],
+                        wednesday: days[2],
+                        thursday: days[3],
+                        friday: days[4],
+                        saturday: days[5],
+                        sunday: days[6],
+                        start_date: date(start_idx).unwrap_or(0),
+                        end_date: date(end_idx).unwrap_or(u32::MAX),
+                        ..Default::default()
+                    });
+                }
+            } else {
+                eprintln!("⚠️  Warning: calendar.txt is missing the service_id column");
+            }
+        }
+
+        if let Ok(file) = archive.by_name("calendar_dates.txt") {
+            let mut rdr = csv::Reader::from_reader(file);
+            let headers = rdr.headers().cloned().unwrap_or_default();
+            let column = |name: &str| headers.iter().position(|h| h == name);
+            let (Some(service_idx), Some(date_idx), Some(type_idx)) =
+                (column("service_id"), column("date"), column("exception_type"))
+            else {
+                eprintln!("⚠️  Warning: calendar_dates.txt is missing required columns");
+                return calendar;
+            };
+
+            for record in rdr.records().flatten() {
+                let (Some(service_id), Some(date)) = (
+                    record.get(service_idx),
+                    record.get(date_idx).and_then(|v| v.parse::<u32>().ok()),
+                ) else {
+                    continue;
+                };
+                let service = calendar.entry(service_id.to_string()).or_default();
+                match record.get(type_idx) {
+                    Some("1") => service.added_dates.push(date),
+                    Some("2") => service.removed_dates.push(date),
+                    _ => {}
+                }
+            }
+        }
+
+        calendar
+    }
+
+    /// Date as the YYYYMMDD number used by GTFS calendar files
+    pub fn gtfs_date(date: NaiveDate) -> u32 {
+        date.year() as u32 * 10_000 + date.month() * 100 + date.day()
+    }
+
+    /// Service day to use for static timetables: the override, or today in Bordeaux
+    pub fn service_date(date_override: Option<NaiveDate>) -> NaiveDate {
+        date_override.unwrap_or_else(|| Utc::now().with_timezone(&Paris).date_naive())
+    }
+
+    /// service_ids running on `date`, with calendar_dates.txt exceptions applied
+    pub fn active_service_ids(date: NaiveDate, cache: &GTFSCache) -> HashSet<String> {
+        cache
+            .service_calendar
+            .iter()
+            .filter(|(_, service)| service.runs_on(date))
+            .map(|(service_id, _)| service_id.clone())
+            .collect()
+    }
+
+    /// Parse a GTFS "HH:MM:SS" time (hours may exceed 23) into seconds since midnight
+    fn parse_gtfs_time(value: &str) -> Option<u32> {
+        let mut parts = value.trim().split(':').map(|p| p.parse::<u32>().ok());
+        let (Some(Some(h)), Some(Some(m)), Some(Some(s))) = (parts.next(), parts.next(), parts.next()) else {
+            return None;
+        };
+        Some(h * 3600 + m * 60 + s)
+    }
+
+    /// Entry from the trip updates feed or a timetable rather than the vehicle positions
+    /// feed. The timestamp of the latter is the time of the GPS fix, not a predicted arrival.
+    pub fn is_scheduled(rt: &RealTimeInfo) -> bool {
+        matches!(rt.vehicle_id.as_str(), "scheduled" | "fallback_trip_update" | "static")
+    }
+
+    /// A GPS-tracked entry whose last fix is more than `threshold_secs` old
+    /// (`vehicle_staleness_secs`). Timetable-derived entries never go stale.
+    pub fn is_stale_vehicle(rt: &RealTimeInfo, now: i64, threshold_secs: u64) -> bool {
+        match rt.timestamp {
+            Some(fix_time) if !Self::is_scheduled(rt) => now.saturating_sub(fix_time) > threshold_secs as i64,
+            _ => false,
+        }
+    }
+
+    /// Collapse entries describing the same (trip_id, stop_id) into one.
+    /// A GPS-tracked entry merged with a "scheduled" trip-update entry keeps the trip
+    /// update's predicted arrival time and delay, and the vehicle's id, position,
+    /// occupancy and speed. First-seen order is kept.
+    pub fn deduplicate_arrivals(arrivals: Vec<RealTimeInfo>) -> Vec<RealTimeInfo> {
+        let mut deduplicated: Vec<RealTimeInfo> = Vec::with_capacity(arrivals.len());
+        let mut index_by_key: HashMap<(String, Option<String>), usize> = HashMap::new();
+
+        for arrival in arrivals {
+            let key = (arrival.trip_id.clone(), arrival.stop_id.clone());
+            let Some(&idx) = index_by_key.get(&key) else {
+                index_by_key.insert(key, deduplicated.len());
+                deduplicated.push(arrival);
+                continue;
+            };
+
+            let existing = &mut deduplicated[idx];
+            let (gps, mut merged) = match (existing.vehicle_id == "scheduled", arrival.vehicle_id == "scheduled") {
+                (true, false) => (arrival, existing.clone()),
+                (false, true) => (existing.clone(), arrival),
+                // Two entries of the same kind: keep the first one
+                _ => continue,
+            };
+
+            // The GPS timestamp is the time of the fix, not a predicted arrival
+            merged.timestamp = merged.timestamp.or(gps.timestamp);
+            merged.delay = merged.delay.or(gps.delay);
+            merged.vehicle_id = gps.vehicle_id;
+            merged.latitude = gps.latitude;
+            merged.longitude = gps.longitude;
+            merged.occupancy = gps.occupancy.or(merged.occupancy);
+            merged.speed_kmh = gps.speed_kmh;
+            merged.route_id = merged.route_id.or(gps.route_id);
+            merged.direction_id = merged.direction_id.or(gps.direction_id);
+            merged.destination = gps.destination.or(merged.destination);
+            merged.cancelled |= gps.cancelled;
+            *existing = merged;
+        }
+
+        deduplicated
+    }
+
+    fn is_cancelled(trip_update: &gtfs_rt::TripUpdate) -> bool {
+        trip_update.trip.schedule_relationship
+            == Some(gtfs_rt::trip_descriptor::ScheduleRelationship::Canceled as i32)
+    }
+
+    /// IDs of the trips the trip updates feed reports as cancelled
+    pub fn get_cancelled_trips(cache: &CachedNetworkData) -> Vec<String> {
+        cache
+            .trip_updates
+            .iter()
+            .filter(|tu| Self::is_cancelled(tu))
+            .filter_map(|tu| tu.trip.trip_id.clone())
+            .collect()
+    }
+
+    /// GTFS-RT OccupancyStatus name (e.g. "MANY_SEATS_AVAILABLE") for machine-readable output
+    pub fn occupancy_status_name(occupancy: u32) -> Option<&'static str> {
+        gtfs_rt::vehicle_position::OccupancyStatus::from_i32(occupancy as i32)
+            .map(|status| status.as_str_name())
+    }
+
+    /// Human-readable label for a GTFS-RT OccupancyStatus ordinal
+    pub fn occupancy_label(occupancy: u32) -> &'static str {
+        match occupancy {
+            0 => "🟢 Empty",
+            1 => "🟢 Many seats",
+            2 => "🟡 Few seats",
+            3 => "🟡 Standing room",
+            4 => "🔴 Crowded",
+            5 => "🔴 Full",
+            6 => "🔴 Not accepting passengers",
+            _ => "⚪ No occupancy data",
+        }
+    }
+
+    /// Vehicle type of a line from its GTFS route_type, Unknown if the feed doesn't list it
+    pub fn line_type(line_ref: &str, cache: &GTFSCache) -> LineType {
+        let route_id = Self::extract_line_id(line_ref).unwrap_or(line_ref);
+        cache.route_types.get(route_id)
+            .map_or(LineType::Unknown, |&route_type| LineType::from_route_type(route_type))
+    }
+
+    /// Kind of stop from its GTFS location_type, Unknown if the feed doesn't list it
+    pub fn classify_stop_type(stop: &Stop, cache: &GTFSCache) -> StopType {
+        cache.stop_location_types.get(&stop.stop_id)
+            .map_or(StopType::Unknown, |&location_type| StopType::from_location_type(location_type))
+    }
+
+    /// Tram lines for grouping in line lists. Lines of unknown type (no GTFS data)
+    /// fall back to the single-letter code convention of TBM trams.
+    pub fn is_tram_line(line: &Line) -> bool {
+        match line.line_type {
+            LineType::Tram => true,
+            LineType::Unknown => line.line_code.len() == 1 && line.line_code.chars().all(|c| c.is_alphabetic()),
+            _ => false,
+        }
+    }
+
+    /// Whether a route only runs at night ("Lianes de Nuit"), from the static GTFS timetable
+    pub fn is_night_service(route_id: &str, cache: &GTFSCache) -> bool {
+        cache.night_routes.contains(route_id)
+    }
+
+    /// Routes with at least one service day on which every departure is between 23:00 and 04:59.
+    /// Times past midnight (e.g. 25:30:00) count as the early hours of the next day.
+    pub fn find_night_routes(departures: &ScheduledDepartures) -> HashSet<String> {
+        // (route_id, service_id) -> no daytime departure seen so far
+        let mut night_only: HashMap<(&str, &str), bool> = HashMap::new();
+        for (route_id, _, secs, service_id, _) in departures.values().flatten() {
+            let time_of_day = secs % 86_400;
+            let at_night = !(Self::NIGHT_END_SECS..Self::NIGHT_START_SECS).contains(&time_of_day);
+            *night_only.entry((route_id.as_str(), service_id.as_str())).or_insert(true) &= at_night;
+        }
+
+        night_only
+            .into_iter()
+            .filter(|(_, night)| *night)
+            .map(|((route_id, _), _)| route_id.to_string())
+            .collect()
+    }
+
+    /// Ordered stop_ids served by a line in one direction, from the static GTFS trips
+    pub fn get_stop_sequence(line_ref: &str, direction_id: u32, cache: &GTFSCache) -> Vec<String> {
+        let route_id = Self::extract_line_id(line_ref).unwrap_or(line_ref);
+        cache
+            .stop_sequences
+            .get(route_id)
+            .and_then(|by_direction| by_direction.get(&direction_id))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Destination of a trip update's trip: the line's destination for its direction, else the
+    /// GTFS trip_headsign, else the name of the last stop of the static stop sequence
+    pub fn infer_destination_from_trip_update(update: &gtfs_rt::TripUpdate, network: &NetworkData) -> Option<String> {
+        let route_id = update.trip.route_id.as_deref();
+        let direction_id = update.trip.direction_id;
+
+        let from_line = route_id.zip(direction_id).and_then(|(route_id, direction_id)| {
+            network.lines.iter()
+                .find(|l| Self::extract_line_id(&l.line_ref) == Some(route_id))?
+                .destinations.iter()
+                .find(|(dir_ref, _)| *dir_ref == direction_id.to_string())
+                .map(|(_, place)| place.clone())
+        });
+
+        from_line
+            .or_else(|| network.gtfs.trip_headsigns.get(update.trip.trip_id.as_deref()?).cloned())
+            .or_else(|| {
+                let sequence = Self::get_stop_sequence(route_id?, direction_id.unwrap_or(0), &network.gtfs);
+                Self::get_stop_by_id(sequence.last()?, network).map(|s| s.stop_name.clone())
+            })
+    }
+
+    /// Give trip-update arrivals without a destination the one inferred from their trip
+    fn fill_missing_destinations(network: &mut NetworkData, trip_updates: &[gtfs_rt::TripUpdate]) {
+        let updates_by_trip: HashMap<&str, &gtfs_rt::TripUpdate> = trip_updates.iter()
+            .filter_map(|tu| tu.trip.trip_id.as_deref().map(|trip_id| (trip_id, tu)))
+            .collect();
+
+        let mut inferred: HashMap<&str, Option<String>> = HashMap::new();
+        let mut fills = Vec::new();
+        for (stop_idx, stop) in network.stops.iter().enumerate() {
+            for (rt_idx, rt) in stop.real_time.iter().enumerate() {
+                if rt.destination.is_some() {
+                    continue;
+                }
+                let Some(update) = updates_by_trip.get(rt.trip_id.as_str()) else {
+                    continue;
+                };
+                let destination = inferred
+                    .entry(rt.trip_id.as_str())
+                    .or_insert_with(|| Self::infer_destination_from_trip_update(update, network));
+                if let Some(destination) = destination {
+                    fills.push((stop_idx, rt_idx, destination.clone()));
+                }
+            }
+        }
+
+        for (stop_idx, rt_idx, destination) in fills {
+            network.stops[stop_idx].real_time[rt_idx].destination = Some(destination);
+        }
+    }
+
+    /// A CANCELED trip may come without any stop time update; list it as cancelled at
+    /// every stop of its route direction's static stop sequence instead of dropping it
+    fn place_cancelled_trips(network: &mut NetworkData, trip_updates: &[gtfs_rt::TripUpdate]) {
+        let unplaced = trip_updates.iter().filter(|tu| {
+            Self::is_cancelled(tu) && tu.stop_time_update.iter().all(|stu| stu.stop_id.is_none())
+        });
+
+        for update in unplaced {
+            let (Some(trip_id), Some(route_id)) = (&update.trip.trip_id, &update.trip.route_id) else {
+                continue;
+            };
+            let direction_id = update.trip.direction_id;
+            let destination = Self::infer_destination_from_trip_update(update, network);
+
+            for stop_id in Self::get_stop_sequence(route_id, direction_id.unwrap_or(0), &network.gtfs) {
+                let Some(&idx) = network.stop_index.get(&stop_id) else {
+                    continue;
+                };
+                let stop = &mut network.stops[idx];
+                stop.cancelled_arrivals.push(RealTimeInfo {
+                    vehicle_id: "scheduled".to_string(),
+                    trip_id: trip_id.clone(),
+                    route_id: Some(route_id.clone()),
+                    direction_id,
+                    destination: destination.clone(),
+                    latitude: stop.latitude,
+                    longitude: stop.longitude,
+                    stop_id: Some(stop_id),
+                    timestamp: None,
+                    delay: None,
+                    occupancy: None,
+                    cancelled: true,
+                    speed_kmh: None,
+                });
+            }
+        }
+    }
+
+    /// First and last stop_id of a line direction, from the stop_times.txt sequence.
+    /// (None, None) when the GTFS timetable isn't cached.
+    pub fn detect_terminal_stops(line_ref: &str, direction_id: u32, cache: &GTFSCache) -> (Option<String>, Option<String>) {
+        let sequence = Self::get_stop_sequence(line_ref, direction_id, cache);
+        (sequence.first().cloned(), sequence.last().cloned())
+    }
+
+    /// Whether the static stop sequence of either direction ends at the stop it starts from
+    pub fn is_circular_line(line_ref: &str, cache: &GTFSCache) -> bool {
+        (0..=1).any(|direction_id| {
+            let sequence = Self::get_stop_sequence(line_ref, direction_id, cache);
+            sequence.len() > 1 && sequence.first() == sequence.last()
+        })
+    }
+
+    /// line_refs of the circular lines, see `is_circular_line`
+    pub fn detect_circular_lines(network: &NetworkData) -> Vec<String> {
+        network.lines
+            .iter()
+            .filter(|line| Self::is_circular_line(&line.line_ref, &network.gtfs))
+            .map(|line| line.line_ref.clone())
+            .collect()
+    }
+
+    /// Geographic path of a line as ordered (lat, lon) points, from the static GTFS shapes
+    pub fn get_line_shape<'a>(line_ref: &str, cache: &'a GTFSCache) -> Option<&'a [(f64, f64)]> {
+        let route_id = Self::extract_line_id(line_ref).unwrap_or(line_ref);
+        cache
+            .route_shapes
+            .get(route_id)
+            .and_then(|shape_id| cache.shapes.get(shape_id))
+            .map(Vec::as_slice)
+    }
+
+    /// Total length of a shape in meters
+    pub fn shape_length_m(shape: &[(f64, f64)]) -> f64 {
+        shape
+            .windows(2)
+            .map(|w| Self::haversine_m(w[0].0, w[0].1, w[1].0, w[1].1))
+            .sum()
+    }
+
+    /// Median gap between consecutive scheduled departures of a route direction at a stop,
+    /// between 07:00 and 21:00 on the given service day.
+    pub fn compute_headway_seconds(
+        stop_id: &str,
+        route_id: &str,
+        direction_id: u32,
+        date: NaiveDate,
+        cache: &GTFSCache,
+    ) -> Option<u32> {
+        const DAY_WINDOW_SECS: (u32, u32) = (7 * 3600, 21 * 3600);
+
+        Self::compute_headway_seconds_between(stop_id, route_id, direction_id, date, DAY_WINDOW_SECS, cache)
+    }
+
+    /// Median gap between consecutive scheduled departures within a (start, end) window
+    /// of seconds since midnight, see `compute_headway_seconds`
+    pub fn compute_headway_seconds_between(
+        stop_id: &str,
+        route_id: &str,
+        direction_id: u32,
+        date: NaiveDate,
+        (start_secs, end_secs): (u32, u32),
+        cache: &GTFSCache,
+    ) -> Option<u32> {
+        let mut times: Vec<u32> = Self::get_static_schedule(stop_id, date, cache)
+            .into_iter()
+            .filter(|(rid, dir, secs)| {
+                rid == route_id && *dir == direction_id && (start_secs..=end_secs).contains(secs)
+            })
+            .map(|(_, _, secs)| secs)
+            .collect();
+        times.sort_unstable();
+        times.dedup();
+
+        let mut gaps: Vec<u32> = times.windows(2).map(|w| w[1] - w[0]).collect();
+        if gaps.is_empty() {
+            return None;
+        }
+        gaps.sort_unstable();
+        Some(gaps[gaps.len() / 2])
+    }
+
+    /// (peak, off-peak) headway of a line in direction 0, measured at its first stop
+    pub fn line_headways(line_ref: &str, date: NaiveDate, cache: &GTFSCache) -> (Option<u32>, Option<u32>) {
+        let route_id = Self::extract_line_id(line_ref).unwrap_or(line_ref);
+        let sequence = Self::get_stop_sequence(line_ref, 0, cache);
+        let Some(first_stop) = sequence.first() else {
+            return (None, None);
+        };
+
+        let headway = |window| Self::compute_headway_seconds_between(first_stop, route_id, 0, date, window, cache);
+        (headway(Self::PEAK_WINDOW_SECS), headway(Self::OFF_PEAK_WINDOW_SECS))
+    }
+
+    /// (line_code, peak headway in seconds) of every line serving a stop, the more frequent
+    /// direction counting, most frequent first. None when the timetable has no peak
+    /// departures of the line at this stop.
+    pub fn list_lines_at_stop_with_frequency(
+        stop_id: &str,
+        network: &NetworkData,
+        cache: &GTFSCache,
+    ) -> Vec<(String, Option<u32>)> {
+        let Some(stop) = Self::get_stop_by_id(stop_id, network) else {
+            return Vec::new();
+        };
+        let date = Self::service_date(network.service_date);
+
+        let mut lines: Vec<(String, Option<u32>)> = stop.lines
+            .iter()
+            .filter_map(|line_ref| network.lines.iter().find(|l| &l.line_ref == line_ref))
+            .map(|line| {
+                let route_id = Self::extract_line_id(&line.line_ref).unwrap_or(&line.line_ref);
+                let headway = [0, 1]
+                    .into_iter()
+                    .filter_map(|direction_id| Self::compute_headway_seconds_between(
+                        &stop.stop_id, route_id, direction_id, date, Self::PEAK_WINDOW_SECS, cache,
+                    ))
+                    .min();
+                (line.line_code.clone(), headway)
+            })
+            .collect();
+
+        // Lines without a peak headway last, then by code like the line list
+        lines.sort_by(|a, b| {
+            (a.1.is_none(), a.1, a.0.len(), &a.0).cmp(&(b.1.is_none(), b.1, b.0.len(), &b.0))
+        });
+        lines
+    }
+
+    /// First and last scheduled departure of a line in direction 0 from its first stop
+    pub fn line_service_span(line_ref: &str, date: NaiveDate, cache: &GTFSCache) -> Option<(u32, u32)> {
+        let route_id = Self::extract_line_id(line_ref).unwrap_or(line_ref);
+        let sequence = Self::get_stop_sequence(line_ref, 0, cache);
+        let times: Vec<u32> = Self::get_static_schedule(sequence.first()?, date, cache)
+            .into_iter()
+            .filter(|(rid, dir, _)| rid == route_id && *dir == 0)
+            .map(|(_, _, secs)| secs)
+            .collect();
+
+        Some((*times.iter().min()?, *times.iter().max()?))
+    }
+
+    /// Unix time of the last scheduled departure of a route (any direction) from a stop
+    /// on today's service day, None if it doesn't run there today
+    pub fn get_last_service(stop_id: &str, route_id: &str, cache: &GTFSCache) -> Option<i64> {
+        Self::scheduled_departures_today(stop_id, route_id, cache).last().copied()
+    }
+
+    /// Whether the last departure of the day from a stop is within `within_minutes` from now
+    pub fn is_last_service_soon(stop_id: &str, route_id: &str, within_minutes: u32, cache: &GTFSCache) -> bool {
+        let now = Self::get_current_timestamp();
+        Self::get_last_service(stop_id, route_id, cache)
+            .is_some_and(|last| last >= now && last - now <= within_minutes as i64 * 60)
+    }
+
+    /// Scheduled departures of a route from a stop still to come today
+    pub fn remaining_departures_today(stop_id: &str, route_id: &str, now: i64, cache: &GTFSCache) -> usize {
+        Self::scheduled_departures_today(stop_id, route_id, cache)
+            .into_iter()
+            .filter(|ts| *ts >= now)
+            .count()
+    }
+
+    /// Unix times of today's scheduled departures of a route from a stop, all directions, in order
+    fn scheduled_departures_today(stop_id: &str, route_id: &str, cache: &GTFSCache) -> Vec<i64> {
+        let day = Self::service_date(None);
+        let Some(midnight) = day.and_hms_opt(0, 0, 0).and_then(|dt| Paris.from_local_datetime(&dt).earliest()) else {
+            return Vec::new();
+        };
+
+        let mut times: Vec<i64> = Self::get_static_schedule(stop_id, day, cache)
+            .into_iter()
+            .filter(|(rid, _, _)| rid == route_id)
+            .map(|(_, _, secs)| midnight.timestamp() + secs as i64)
+            .collect();
+        times.sort_unstable();
+        times.dedup();
+        times
+    }
+
+    /// Longest wait between consecutive scheduled departures of a route direction at a stop
+    /// after `after_time`, if it exceeds `SERVICE_GAP_FACTOR` times the median headway
+    pub fn get_service_gap(
+        stop_id: &str,
+        route_id: &str,
+        direction_id: u32,
+        after_time: i64,
+        cache: &GTFSCache,
+    ) -> Option<Duration> {
+        Self::find_service_gaps(stop_id, route_id, direction_id, after_time, cache)
+            .into_iter()
+            .map(|(_, gap)| gap)
+            .max()
+    }
+
+    /// Every unusually long wait (see `get_service_gap`) ending after `after_time`, on the
+    /// Paris service day of `after_time`, in time order
+    pub fn find_service_gaps(
+        stop_id: &str,
+        route_id: &str,
+        direction_id: u32,
+        after_time: i64,
+        cache: &GTFSCache,
+    ) -> Vec<ServiceGap> {
+        let Some(day) = Utc.timestamp_opt(after_time, 0).single().map(|dt| dt.with_timezone(&Paris).date_naive()) else {
+            return Vec::new();
+        };
+        let Some(midnight) = day.and_hms_opt(0, 0, 0).and_then(|dt| Paris.from_local_datetime(&dt).earliest()) else {
+            return Vec::new();
+        };
+        let Some(headway) = Self::compute_headway_seconds(stop_id, route_id, direction_id, day, cache) else {
+            return Vec::new();
+        };
+        let threshold = (headway as f64 * Self::SERVICE_GAP_FACTOR) as i64;
+
+        let mut times: Vec<i64> = Self::get_static_schedule(stop_id, day, cache)
+            .into_iter()
+            .filter(|(rid, dir, _)| rid == route_id && *dir == direction_id)
+            .map(|(_, _, secs)| midnight.timestamp() + secs as i64)
+            .collect();
+        times.sort_unstable();
+        times.dedup();
+
+        times
+            .windows(2)
+            .filter(|w| w[1] > after_time && w[1] - w[0] > threshold)
+            .map(|w| (w[0], Duration::from_secs((w[1] - w[0]) as u64)))
+            .collect()
+    }
+
+    /// Static GTFS departures at a stop on a service day as (route_id, direction_id, seconds since midnight).
+    /// Feeds without calendar data are not filtered.
+    pub fn get_static_schedule(stop_id: &str, date: NaiveDate, cache: &GTFSCache) -> Vec<(String, u32, u32)> {
+        Self::static_departures_on(stop_id, date, cache)
+            .into_iter()
+            .map(|(route_id, direction_id, seconds, _, _)| (route_id.clone(), *direction_id, *seconds))
+            .collect()
+    }
+
+    /// Entries of `scheduled_departures` at a stop whose service runs on `date`
+    fn static_departures_on<'a>(
+        stop_id: &str,
+        date: NaiveDate,
+        cache: &'a GTFSCache,
+    ) -> Vec<&'a (String, u32, u32, String, String)> {
+        let active_services = Self::active_service_ids(date, cache);
+        let filter_by_service = !cache.service_calendar.is_empty();
+
+        cache
+            .scheduled_departures
+            .get(&Self::normalize_stop_id(stop_id))
+            .into_iter()
+            .flatten()
+            .filter(|(_, _, _, service_id, _)| !filter_by_service || active_services.contains(service_id))
+            .collect()
+    }
+
+    /// Give stops without any GTFS-RT arrival their upcoming SIRI stop timetable departures,
+    /// which take precedence over the GTFS static timetable
+    fn apply_siri_timetables(network: &mut NetworkData, timetables: &HashMap<String, Vec<RealTimeInfo>>) {
+        let now = Utc::now().timestamp();
+        for stop in network.stops.iter_mut().filter(|s| s.real_time.is_empty()) {
+            if let Some(departures) = timetables.get(&stop.stop_id) {
+                stop.real_time = departures
+                    .iter()
+                    .filter(|rt| rt.timestamp.is_some_and(|ts| ts >= now))
+                    .map(|rt| RealTimeInfo { latitude: stop.latitude, longitude: stop.longitude, ..rt.clone() })
+                    .collect();
+            }
+        }
+    }
+
+    /// Give stops without any GTFS-RT arrival their upcoming static timetable departures.
+    /// Entries use vehicle_id "static" so views can tell them apart from live data.
+    fn apply_static_schedule_fallback(network: &mut NetworkData) {
+        const STATIC_WINDOW_SECS: i64 = 2 * 3600;
+        const MAX_STATIC_ARRIVALS: usize = 10;
+
+        let NetworkData { stops, lines, gtfs, service_date, .. } = network;
+        if gtfs.scheduled_departures.is_empty() {
+            return;
+        }
+
+        let now = Utc::now().timestamp();
+        let today = Utc::now().with_timezone(&Paris).date_naive();
+        // A --date override picks which timetable runs, but times stay anchored to today
+        let service_today = Self::service_date(*service_date);
+        // Trips running past midnight belong to yesterday's service day (times >= 24:00:00)
+        let service_days: Vec<(NaiveDate, i64)> = [(today.pred_opt(), service_today.pred_opt()), (Some(today), Some(service_today))]
+            .into_iter()
+            .filter_map(|(day, service_day)| {
+                let midnight = Paris.from_local_datetime(&day?.and_hms_opt(0, 0, 0)?).earliest()?;
+                Some((service_day?, midnight.timestamp()))
+            })
+            .collect();
+
+        for stop in stops.iter_mut().filter(|s| s.real_time.is_empty()) {
+            let mut upcoming: Vec<RealTimeInfo> = service_days
+                .iter()
+                .flat_map(|(service_day, midnight)| {
+                    Self::get_static_schedule(&stop.stop_id, *service_day, gtfs)
+                        .into_iter()
+                        .map(move |(route_id, direction_id, seconds)| (route_id, direction_id, midnight + seconds as i64))
+                })
+                .filter(|(_, _, ts)| *ts >= now - 120 && *ts <= now + STATIC_WINDOW_SECS)
+                .map(|(route_id, direction_id, ts)| {
+                    let destination = lines
+                        .iter()
+                        .find(|l| Self::extract_line_id(&l.line_ref) == Some(route_id.as_str()))
+                        .and_then(|l| {
+                            l.destinations.iter()
+                                .find(|(dir_ref, _)| dir_ref == &direction_id.to_string())
+                                .map(|(_, place)| place.clone())
+                        });
+
+                    RealTimeInfo {
+                        vehicle_id: "static".to_string(),
+                        trip_id: format!("static:{}:{}:{}", route_id, direction_id, ts),
+                        route_id: Some(route_id),
+                        direction_id: Some(direction_id),
+                        destination,
+                        latitude: stop.latitude,
+                        longitude: stop.longitude,
+                        stop_id: Some(stop.stop_id.clone()),
+                        timestamp: Some(ts),
+                        delay: None,
+                        occupancy: None,
+                        cancelled: false,
+                        speed_kmh: None,
                     }
+                })
+                .collect();
+
+            upcoming.sort_by_key(|rt| rt.timestamp);
+            upcoming.truncate(MAX_STATIC_ARRIVALS);
+            stop.real_time = upcoming;
+        }
+    }
+
+    /// Extract stop_name translations from a GTFS translations.txt file, keyed by
+    /// normalized stop_id. Rows reference stops either by record_id or by the original field_value.
+    fn parse_stop_name_translations(
+        contents: &str,
+        stops: &[(String, String, f64, f64)],
+    ) -> HashMap<String, HashMap<String, String>> {
+        let mut translations: HashMap<String, HashMap<String, String>> = HashMap::new();
+        let mut rdr = csv::Reader::from_reader(contents.as_bytes());
+
+        let headers = match rdr.headers() {
+            Ok(headers) => headers.clone(),
+            Err(e) => {
+                eprintln!("⚠️  Warning: Could not read translations.txt header: {}", e);
+                return translations;
+            }
+        };
+        let column = |name: &str| headers.iter().position(|h| h == name);
+        let (Some(table_idx), Some(field_idx), Some(lang_idx), Some(translation_idx)) =
+            (column("table_name"), column("field_name"), column("language"), column("translation"))
+        else {
+            return translations;
+        };
+        let record_id_idx = column("record_id");
+        let field_value_idx = column("field_value");
+
+        for record in rdr.records().flatten() {
+            if record.get(table_idx) != Some("stops") || record.get(field_idx) != Some("stop_name") {
+                continue;
+            }
+            let (Some(lang), Some(translation)) = (record.get(lang_idx), record.get(translation_idx)) else {
+                continue;
+            };
+            let lang = lang.to_lowercase();
+
+            let record_id = record_id_idx.and_then(|i| record.get(i)).filter(|id| !id.is_empty());
+            if let Some(stop_id) = record_id {
+                translations.entry(Self::normalize_stop_id(stop_id)).or_default()
+                    .insert(lang, translation.to_string());
+            } else if let Some(original) = field_value_idx.and_then(|i| record.get(i)) {
+                for (stop_id, _, _, _) in stops.iter().filter(|s| s.1 == original) {
+                    translations.entry(Self::normalize_stop_id(stop_id)).or_default()
+                        .insert(lang.clone(), translation.to_string());
+                }
+            }
+        }
+
+        translations
+    }
+
+    async fn load_gtfs_data() -> Result<GTFSCache> {
+        Self::download_and_read_routes().await
+    }
+
+    /// Line colors from the GTFS routes, or from the bundled `assets/line_colors.toml`
+    /// when the routes couldn't be loaded (`gtfs` is None or has no routes)
+    fn load_line_colors(gtfs: Option<&GTFSCache>) -> HashMap<String, String> {
+        if let Some(gtfs) = gtfs.filter(|gtfs| !gtfs.routes.is_empty()) {
+            status!("   ✓ Loaded {} line colors from GTFS", gtfs.routes.len());
+            return gtfs.routes.clone();
+        }
+
+        let colors = Self::load_line_colors_manual();
+        if colors.is_empty() {
+            status!("   Continuing with default colors...");
+        } else {
+            status!("   ✓ Loaded {} line colors from the bundled list", colors.len());
+        }
+        colors
+    }
+
+    /// Curated colors of the main TBM lines by line code, bundled with the binary.
+    /// Empty if the bundled file is invalid.
+    pub fn load_line_colors_manual() -> HashMap<String, String> {
+        const LINE_COLORS_TOML: &str = include_str!("../assets/line_colors.toml");
+
+        match toml::from_str::<HashMap<String, String>>(LINE_COLORS_TOML) {
+            Ok(colors) => colors,
+            Err(e) => {
+                status!("   ⚠️  Warning: Invalid bundled line colors ({})", e);
+                HashMap::new()
+            }
+        }
+    }
+
+    /// Stop name in the requested language, falling back to the original name
+    pub fn get_stop_name<'a>(stop: &'a Stop, lang: &str, cache: &'a GTFSCache) -> &'a str {
+        cache.stop_name_translations
+            .get(&stop.stop_id)
+            .and_then(|names| names.get(&lang.to_lowercase()))
+            .map(String::as_str)
+            .unwrap_or(&stop.stop_name)
+    }
+
+    /// Build complete network data with all associations - OPTIMIZED
+    #[allow(clippy::too_many_arguments)]
+    pub fn build_network_data(
+        stops_data: Vec<StopMetadata>,
+        lines_data: Vec<LineMetadata>,
+        alerts: Vec<AlertInfo>,
+        real_time: Vec<RealTimeInfo>,
+        trip_updates: Vec<gtfs_rt::TripUpdate>,
+        line_color_map: HashMap<String, String>,
+        hub_scores: HashMap<String, f64>,
+        stop_to_lines: &HashMap<String, Vec<String>>,
+        line_to_stops: &HashMap<String, Vec<String>>,
+        config: &NVTConfig,
+    ) -> NetworkData {
+        let line_destinations_map: HashMap<String, Vec<(String, String)>> = lines_data
+            .iter()
+            .filter_map(|(ref_, _, _, destinations)| {
+                let line_id = Self::extract_line_id(ref_)?;
+                Some((line_id.to_string(), destinations.clone()))
+            })
+            .collect();
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+
+        // Allow arrivals up to 2 minutes in the past (grace period for vehicles at stop)
+        let grace_period = 120; // seconds
+        let cutoff_time = now - grace_period;
+
+        let cancelled_trips: HashSet<&str> = trip_updates
+            .iter()
+            .filter(|tu| Self::is_cancelled(tu))
+            .filter_map(|tu| tu.trip.trip_id.as_deref())
+            .collect();
+
+        // Occupancy is only reported on vehicle positions; share it with the trip's scheduled arrivals
+        let occupancy_by_trip: HashMap<&str, u32> = real_time
+            .iter()
+            .filter_map(|rt| rt.occupancy.map(|o| (rt.trip_id.as_str(), o)))
+            .collect();
+
+        // Group vehicles and alerts once instead of scanning them for every stop and line
+        let mut real_time_by_stop: HashMap<&str, Vec<&RealTimeInfo>> = HashMap::new();
+        let mut real_time_by_route: HashMap<&str, Vec<&RealTimeInfo>> = HashMap::new();
+        for rt in &real_time {
+            if let Some(stop_id) = &rt.stop_id {
+                real_time_by_stop.entry(stop_id.as_str()).or_default().push(rt);
+            }
+            if let Some(route_id) = &rt.route_id {
+                real_time_by_route.entry(route_id.as_str()).or_default().push(rt);
+            }
+        }
+
+        // Alerts whose period ended more than a minute ago are still in some feeds
+        let mut alerts: Vec<AlertInfo> = alerts
+            .into_iter()
+            .filter(|alert| alert.active_period_end.is_none_or(|end| end >= now - 60))
+            .collect();
+        // Stop and line alerts keep this order
+        Self::rank_alerts_by_severity(&mut alerts);
+
+        // Alert positions in `alerts` per stop_id and per route_id, each alert listed once
+        let mut alerts_by_stop: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut alerts_by_route: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (idx, alert) in alerts.iter().enumerate() {
+            for (ids, index) in [(&alert.stop_ids, &mut alerts_by_stop), (&alert.route_ids, &mut alerts_by_route)] {
+                for id in ids {
+                    let positions = index.entry(id.as_str()).or_default();
+                    if positions.last() != Some(&idx) {
+                        positions.push(idx);
+                    }
+                }
+            }
+        }
+
+        let mut trip_updates_by_stop: HashMap<String, Vec<TripUpdateArrival>> = HashMap::new();
+
+        for trip_update in &trip_updates {
+            let trip_id = trip_update.trip.trip_id.clone().unwrap_or_else(|| Self::UNKNOWN_TRIP_ID.to_string());
+            let cancelled = Self::is_cancelled(trip_update);
+
+            for stu in &trip_update.stop_time_update {
+                let Some(stop_id_raw) = &stu.stop_id else {
+                    continue;
+                };
+                let delay = stu.arrival.as_ref().and_then(|a| a.delay)
+                    .or_else(|| stu.departure.as_ref().and_then(|d| d.delay));
+                let time = stu.arrival.as_ref().and_then(|a| a.time)
+                    .or_else(|| stu.departure.as_ref().and_then(|d| d.time));
+
+                // Include arrivals within grace period OR in the future. Cancelled trips
+                // often come without times and are kept so they can be listed as cancelled.
+                let keep = match time {
+                    Some(arrival_time) => arrival_time >= cutoff_time,
+                    None => cancelled,
+                };
+                if !keep {
+                    continue;
+                }
+
+                let data = TripUpdateArrival {
+                    trip_id: trip_id.clone(),
+                    route_id: trip_update.trip.route_id.clone(),
+                    direction_id: trip_update.trip.direction_id,
+                    delay,
+                    time,
+                    cancelled,
+                };
+
+                // Index by normalized raw stop_id (e.g., "5220")
+                let stop_id = Self::normalize_stop_id(stop_id_raw);
+
+                // ALSO index by extracted stop_id (in case SIRI uses different format)
+                if let Some(extracted) = Self::extract_stop_id(stop_id_raw)
+                    && extracted != stop_id
+                {
+                    trip_updates_by_stop.entry(extracted).or_default().push(data.clone());
+                }
+                trip_updates_by_stop.entry(stop_id).or_default().push(data);
+            }
+        }
+
+        let stops: Vec<Stop> = stops_data
+            .into_iter()
+            .map(|(id, name, lat, lon, line_refs)| {
+                // SIRI cache files written before normalization may hold raw IDs
+                let id = Self::normalize_stop_id(&id);
+                let mut stop_rt: Vec<RealTimeInfo> = real_time_by_stop
+                    .get(id.as_str())
+                    .into_iter()
+                    .flatten()
+                    .filter(|rt| !Self::is_stale_vehicle(rt, now, config.vehicle_staleness_secs))
+                    .map(|rt| (*rt).clone())
+                    .collect();
+
+                // Add trip updates (scheduled arrivals)
+                if let Some(scheduled_arrivals) = trip_updates_by_stop.get(&id) {
+                    for arrival in scheduled_arrivals {
+                        let destination = arrival.route_id.as_ref().and_then(|rid| {
+                            line_destinations_map.get(rid).and_then(|destinations| {
+                                arrival.direction_id.and_then(|dir_id| {
+                                    destinations.iter()
+                                        .find(|(dir_ref, _)| dir_ref == &dir_id.to_string())
+                                        .map(|(_, place)| place.clone())
+                                })
+                            })
+                        });
+
+                        stop_rt.push(RealTimeInfo {
+                            vehicle_id: "scheduled".to_string(),
+                            trip_id: arrival.trip_id.clone(),
+                            route_id: arrival.route_id.clone(),
+                            direction_id: arrival.direction_id,
+                            destination,
+                            latitude: lat,
+                            longitude: lon,
+                            stop_id: Some(id.clone()),
+                            timestamp: arrival.time,
+                            delay: arrival.delay,
+                            occupancy: occupancy_by_trip.get(arrival.trip_id.as_str()).copied(),
+                            cancelled: arrival.cancelled,
+                            speed_kmh: None,
+                        });
+                    }
+                }
+
+                // Keep predicted arrivals within grace period OR in the future. GPS entries
+                // were already filtered by the age of their fix (is_stale_vehicle).
+                stop_rt.retain(|rt| !Self::is_scheduled(rt) || rt.timestamp.is_none_or(|ts| ts >= cutoff_time));
+
+                // GPS position and trip update may both describe the same trip at this stop
+                let mut stop_rt = Self::deduplicate_arrivals(stop_rt);
+
+                // A GPS vehicle still reporting on a cancelled trip is cancelled too
+                for rt in stop_rt.iter_mut() {
+                    rt.cancelled |= cancelled_trips.contains(rt.trip_id.as_str());
+                }
+
+                // Cancelled trips are listed separately instead of as normal arrivals
+                let (mut cancelled_arrivals, mut stop_rt): (Vec<RealTimeInfo>, Vec<RealTimeInfo>) =
+                    stop_rt.into_iter().partition(|rt| rt.cancelled);
+                cancelled_arrivals.sort_by_key(|rt| rt.timestamp.unwrap_or(i64::MAX));
+
+                // Sort by timestamp
+                stop_rt.sort_by_key(|rt| rt.timestamp.unwrap_or(i64::MAX));
+
+                // Limit to next N arrivals to avoid overwhelming UI
+                stop_rt.truncate(config.max_arrivals_per_stop);
+
+                let stop_alerts: Vec<AlertInfo> = alerts_by_stop
+                    .get(id.as_str())
+                    .into_iter()
+                    .flatten()
+                    .map(|&idx| alerts[idx].clone())
+                    .collect();
+
+                let hub_score = hub_scores.get(&id).copied().unwrap_or(0.0);
+                let lines = stop_to_lines.get(&id).cloned().unwrap_or(line_refs);
+
+                Stop {
+                    stop_id: id,
+                    stop_name: name,
+                    latitude: lat,
+                    longitude: lon,
+                    lines,
+                    alerts: stop_alerts,
+                    real_time: stop_rt,
+                    cancelled_arrivals,
+                    hub_score,
+                    stop_type: StopType::Unknown,
+                    wheelchair_accessible: false,
+                }
+            })
+            .collect();
+
+        let lines: Vec<Line> = lines_data
+            .into_iter()
+            .map(|(ref_, name, code, destinations)| {
+                let line_id = Self::extract_line_id(&ref_).unwrap_or("");
+                // GTFS colors are keyed by route_id, the bundled fallback ones by line code
+                let color = line_color_map
+                    .get(line_id)
+                    .or_else(|| line_color_map.get(&code))
+                    .cloned()
+                    .unwrap_or_else(|| "808080".to_string());
+
+                let mut alert_positions: Vec<usize> = [code.as_str(), line_id]
+                    .iter()
+                    .filter_map(|route| alerts_by_route.get(route))
+                    .flatten()
+                    .copied()
+                    .collect();
+                alert_positions.sort_unstable();
+                alert_positions.dedup();
+                let line_alerts: Vec<AlertInfo> = alert_positions.into_iter().map(|idx| alerts[idx].clone()).collect();
+
+                let mut line_rt: Vec<RealTimeInfo> = real_time_by_route
+                    .get(line_id)
+                    .into_iter()
+                    .flatten()
+                    .filter(|rt| !Self::is_stale_vehicle(rt, now, config.vehicle_staleness_secs))
+                    .filter(|rt| !Self::is_scheduled(rt) || rt.timestamp.is_none_or(|ts| ts >= cutoff_time))
+                    .map(|rt| (*rt).clone())
+                    .collect();
+
+                line_rt.sort_by_key(|rt| rt.timestamp.unwrap_or(i64::MAX));
+
+                // Until the GTFS terminals are known (see `to_network_data`), direction 1 heads
+                // back to where direction 0 starts
+                let destination_for = |dir_ref: &str| destinations.iter()
+                    .find(|(d, _)| d == dir_ref)
+                    .map(|(_, place)| place.clone());
+                let (terminus_a, terminus_b) = (destination_for("1"), destination_for("0"));
+
+                Line {
+                    line_ref: ref_,
+                    line_name: name,
+                    line_code: code,
+                    destinations,
+                    alerts: line_alerts,
+                    real_time: line_rt,
+                    color,
+                    is_night_service: false,
+                    line_type: LineType::Unknown,
+                    terminus_a,
+                    terminus_b,
+                    is_circular: false,
+                }
+            })
+            .collect();
+
+        let stop_index = stops.iter()
+            .enumerate()
+            .map(|(idx, stop)| (stop.stop_id.clone(), idx))
+            .collect();
+
+        let mut stop_grid = SpatialGrid::new(Self::STOP_GRID_CELL_DEG);
+        for stop in &stops {
+            stop_grid.insert(stop);
+        }
+
+        NetworkData {
+            stops,
+            lines,
+            gtfs: Arc::default(),
+            display_lang: None,
+            service_date: None,
+            stop_index,
+            line_to_stops: line_to_stops.clone(),
+            stop_grid,
+            network_graph: None,
+            graph_diameter: Arc::default(),
+        }
+    }
+
+    /// stop_id -> line_refs and line_ref -> stop_ids indices of the SIRI stop metadata
+    pub fn build_line_indices(
+        stops_data: &[StopMetadata],
+    ) -> (HashMap<String, Vec<String>>, HashMap<String, Vec<String>>) {
+        let mut stop_to_lines: HashMap<String, Vec<String>> = HashMap::new();
+        let mut line_to_stops: HashMap<String, Vec<String>> = HashMap::new();
+
+        for (id, _, _, _, line_refs) in stops_data {
+            let stop_id = Self::normalize_stop_id(id);
+            for line_ref in line_refs {
+                line_to_stops.entry(line_ref.clone()).or_default().push(stop_id.clone());
+            }
+            stop_to_lines.insert(stop_id, line_refs.clone());
+        }
+
+        (stop_to_lines, line_to_stops)
+    }
+
+    fn extract_stop_id(full_id: &str) -> Option<String> {
+        let stop_id = if full_id.contains("BP:") {
+            full_id
+                .split("BP:")
+                .nth(1)?
+                .split(':')
+                .next()?
+        } else if full_id.contains(':') {
+            let parts: Vec<&str> = full_id.split(':').collect();
+            if parts.len() >= 2 {
+                parts[parts.len() - 2]
+            } else {
+                full_id
+            }
+        } else {
+            full_id
+        };
+        Some(Self::normalize_stop_id(stop_id))
+    }
+
+    /// Canonical form for stop IDs so SIRI and GTFS-RT identifiers compare equal:
+    /// trimmed, BOM stripped, NFC-normalized and lowercased
+    pub fn normalize_stop_id(raw: &str) -> String {
+        let normalized: String = raw
+            .trim_matches(|c: char| c.is_ascii_whitespace() || c == '\u{FEFF}')
+            .nfc()
+            .collect::<String>()
+            .to_lowercase();
+
+        if normalized != raw {
+            log::debug!("Normalized stop ID {:?} -> {:?} (data quality issue upstream)", raw, normalized);
+        }
+
+        normalized
+    }
+
+    pub fn extract_line_id(line_ref: &str) -> Option<&str> {
+        line_ref.split(':').nth(2)
+    }
+
+    pub fn get_line_color(line_code: &str, network: &NetworkData) -> String {
+        network
+            .lines
+            .iter()
+            .find(|l| l.line_code.eq_ignore_ascii_case(line_code))
+            .map(|l| l.color.clone())
+            .unwrap_or_else(|| "808080".to_string())
+    }
+
+    /// Uppercase 6-digit form of a hex color, with or without a leading '#'.
+    /// 3-digit CSS shorthand is expanded ("ABC" -> "AABBCC"); None if not a hex color.
+    pub fn normalize_hex_color(hex_color: &str) -> Option<String> {
+        let digits = hex_color.trim().trim_start_matches('#');
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        match digits.len() {
+            3 => Some(digits.chars().flat_map(|c| [c, c]).collect::<String>().to_uppercase()),
+            6 => Some(digits.to_uppercase()),
+            _ => None,
+        }
+    }
+
+    /// RGB components of a hex color (see `normalize_hex_color`), grey if it isn't valid
+    pub fn parse_hex_color(hex_color: &str) -> (u8, u8, u8) {
+        let Some(hex_color) = Self::normalize_hex_color(hex_color) else {
+            return (128, 128, 128);
+        };
+        let r = u8::from_str_radix(&hex_color[0..2], 16).unwrap_or(128);
+        let g = u8::from_str_radix(&hex_color[2..4], 16).unwrap_or(128);
+        let b = u8::from_str_radix(&hex_color[4..6], 16).unwrap_or(128);
+        (r, g, b)
+    }
+
+    /// Relative luminance of an sRGB color, with the simplified 2.2 gamma linearisation
+    pub fn relative_luminance(r: u8, g: u8, b: u8) -> f32 {
+        let linear = |c: u8| (c as f32 / 255.0).powf(2.2);
+        0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+    }
+
+    /// WCAG 2.1 contrast ratio of two relative luminances, from 1:1 to 21:1
+    pub fn contrast_ratio(l1: f32, l2: f32) -> f32 {
+        (l1.max(l2) + 0.05) / (l1.min(l2) + 0.05)
+    }
+
+    /// Text color, "000000" or "FFFFFF", with the higher contrast on a line color
+    pub fn line_text_color(bg_hex: &str) -> &'static str {
+        let (r, g, b) = Self::parse_hex_color(bg_hex);
+        let background = Self::relative_luminance(r, g, b);
+        if Self::contrast_ratio(background, 0.0) > Self::contrast_ratio(background, 1.0) {
+            "000000"
+        } else {
+            "FFFFFF"
+        }
+    }
+
+    pub fn get_line_color_rgb(line_code: &str, network: &NetworkData) -> (u8, u8, u8) {
+        let hex_color = Self::get_line_color(line_code, network);
+        Self::parse_hex_color(&hex_color)
+    }
+
+    pub fn get_stop_by_name<'a>(name: &str, network: &'a NetworkData) -> Option<&'a Stop> {
+        network.stops.iter().find(|s| s.stop_name.eq_ignore_ascii_case(name))
+    }
+
+    /// Stop name reduced to what platforms of one station share: lowercase, without
+    /// trailing "(...)" qualifiers or compass directions ("Grand Parc (terminus)" -> "grand parc")
+    fn stop_group_key(name: &str) -> String {
+        const DIRECTION_SUFFIXES: [&str; 4] = [" nord", " sud", " est", " ouest"];
+
+        let mut key = name.trim().to_lowercase();
+        loop {
+            let before = key.len();
+            if key.ends_with(')')
+                && let Some(open) = key.rfind(" (")
+            {
+                key.truncate(open);
+            }
+            for suffix in DIRECTION_SUFFIXES {
+                if let Some(stripped) = key.strip_suffix(suffix) {
+                    key = stripped.trim_end().to_string();
+                }
+            }
+            if key.len() == before || key.is_empty() {
+                break;
+            }
+        }
+        key
+    }
+
+    /// Stops grouped by station, i.e. by name up to platform qualifiers (see `stop_group_key`)
+    pub fn group_stops_by_name(network: &NetworkData) -> HashMap<String, Vec<&Stop>> {
+        let mut groups: HashMap<String, Vec<&Stop>> = HashMap::new();
+        for stop in &network.stops {
+            groups.entry(Self::stop_group_key(&stop.stop_name)).or_default().push(stop);
+        }
+        groups
+    }
+
+    /// All platforms of the station called `name`, None unless there are several
+    pub fn find_stop_group<'a>(name: &str, network: &'a NetworkData) -> Option<Vec<&'a Stop>> {
+        let key = Self::stop_group_key(name);
+        let group: Vec<&Stop> = network.stops.iter()
+            .filter(|s| Self::stop_group_key(&s.stop_name) == key)
+            .collect();
+        (group.len() > 1).then_some(group)
+    }
+
+    /// Stops ranked by Jaro-Winkler similarity to `query`, best first.
+    /// Used as a typo-tolerant fallback when substring search finds nothing.
+    pub fn find_stops_fuzzy<'a>(query: &str, network: &'a NetworkData, limit: usize) -> Vec<&'a Stop> {
+        Self::find_stops_fuzzy_with_threshold(query, network, limit, Self::FUZZY_STOP_THRESHOLD)
+    }
+
+    pub fn find_stops_fuzzy_with_threshold<'a>(
+        query: &str,
+        network: &'a NetworkData,
+        limit: usize,
+        threshold: f64,
+    ) -> Vec<&'a Stop> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        // Score against the full name and each word, so "quinconce" matches "Quinconces - Fleuve"
+        let mut scored: Vec<(&Stop, f64)> = network
+            .stops
+            .iter()
+            .map(|stop| {
+                let name = stop.stop_name.to_lowercase();
+                let best_word = name
+                    .split(|c: char| !c.is_alphanumeric())
+                    .filter(|w| !w.is_empty())
+                    .map(|w| strsim::jaro_winkler(&query, w))
+                    .fold(0.0, f64::max);
+                (stop, strsim::jaro_winkler(&query, &name).max(best_word))
+            })
+            .filter(|(_, score)| *score >= threshold)
+            .collect();
+
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        scored.into_iter().take(limit).map(|(stop, _)| stop).collect()
+    }
+
+    /// Stops within `radius_m` metres of a coordinate, nearest first, with their distance
+    pub fn nearest_stops(lat: f64, lon: f64, radius_m: f64, network: &NetworkData) -> Vec<(&Stop, f64)> {
+        // Slightly larger than the radius: the box uses 111.32 km per degree, the haversine a smaller Earth
+        let (min_lat, max_lat, min_lon, max_lon) = Self::bounding_box(lat, lon, radius_m / 1000.0 * 1.01);
+        let mut nearby: Vec<(&Stop, f64)> = Self::get_stops_in_bbox(min_lat, max_lat, min_lon, max_lon, network)
+            .into_iter()
+            .map(|stop| (stop, Self::haversine_m(lat, lon, stop.latitude, stop.longitude)))
+            .filter(|(_, distance)| *distance <= radius_m)
+            .collect();
+
+        nearby.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        nearby
+    }
+
+    /// (min_lat, max_lat, min_lon, max_lon) of the square extending `radius_km` around a coordinate
+    pub fn bounding_box(lat: f64, lon: f64, radius_km: f64) -> (f64, f64, f64, f64) {
+        const KM_PER_DEGREE: f64 = 111.32;
+
+        let d_lat = radius_km / KM_PER_DEGREE;
+        let d_lon = radius_km / (KM_PER_DEGREE * lat.to_radians().cos().max(0.01));
+        (lat - d_lat, lat + d_lat, lon - d_lon, lon + d_lon)
+    }
+
+    /// Stops whose coordinates fall inside the box, bounds included
+    pub fn get_stops_in_bbox(min_lat: f64, max_lat: f64, min_lon: f64, max_lon: f64, network: &NetworkData) -> Vec<&Stop> {
+        network.stop_grid
+            .query_bbox(min_lat, max_lat, min_lon, max_lon)
+            .into_iter()
+            .filter_map(|idx| network.stops.get(idx))
+            .collect()
+    }
+
+    /// Great-circle distance in metres between two WGS84 coordinates
+    fn haversine_m(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+        const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+        let d_lat = (lat2 - lat1).to_radians();
+        let d_lon = (lon2 - lon1).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2)
+            + lat1.to_radians().cos() * lat2.to_radians().cos() * (d_lon / 2.0).sin().powi(2);
+
+        2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+    }
+
+    /// Seconds needed to walk between two (latitude, longitude) points in a straight line
+    /// at `speed_kmh`, rounded up
+    pub fn estimate_walk_time(from: (f64, f64), to: (f64, f64), speed_kmh: f32) -> u32 {
+        let speed_mps = speed_kmh.max(0.1) as f64 * 1000.0 / 3600.0;
+        let secs = Self::haversine_m(from.0, from.1, to.0, to.1) / speed_mps;
+        // Round to the millisecond first so float noise doesn't add a whole second
+        ((secs * 1000.0).round() / 1000.0).ceil() as u32
+    }
+
+    /// Lines shared by two stops and those served by only one of them, with the distance between them
+    pub fn compare_stops(stop_a: &Stop, stop_b: &Stop, network: &NetworkData) -> StopComparison {
+        let lines_a: HashSet<&String> = stop_a.lines.iter().collect();
+        let lines_b: HashSet<&String> = stop_b.lines.iter().collect();
+        // Ordered by line code like the line list, shorter codes first
+        let sorted = |lines: Vec<&&String>| {
+            let mut lines: Vec<String> = lines.into_iter().map(|l| l.to_string()).collect();
+            lines.sort_by_cached_key(|line_ref| {
+                let code = network.lines.iter()
+                    .find(|l| &l.line_ref == line_ref)
+                    .map_or(line_ref.as_str(), |l| l.line_code.as_str());
+                (code.len(), code.to_string())
+            });
+            lines
+        };
+
+        StopComparison {
+            common_lines: sorted(lines_a.intersection(&lines_b).collect()),
+            only_a: sorted(lines_a.difference(&lines_b).collect()),
+            only_b: sorted(lines_b.difference(&lines_a).collect()),
+            distance_m: Self::haversine_m(stop_a.latitude, stop_a.longitude, stop_b.latitude, stop_b.longitude),
+        }
+    }
+
+    /// Lines whose code or name matches `query` as a case-insensitive regex (e.g. `^L[0-9]+`),
+    /// or contains it when it isn't a valid regex. Sorted by code, shorter codes first.
+    pub fn search_lines<'a>(query: &str, network: &'a NetworkData) -> Vec<&'a Line> {
+        let mut lines: Vec<&Line> = match RegexBuilder::new(query).case_insensitive(true).build() {
+            Ok(pattern) => network.lines.iter()
+                .filter(|l| pattern.is_match(&l.line_code) || pattern.is_match(&l.line_name))
+                .collect(),
+            Err(_) => {
+                let query = query.to_lowercase();
+                network.lines.iter()
+                    .filter(|l| l.line_code.to_lowercase().contains(&query) || l.line_name.to_lowercase().contains(&query))
+                    .collect()
+            }
+        };
+
+        lines.sort_by(|a, b| (a.line_code.len(), &a.line_code).cmp(&(b.line_code.len(), &b.line_code)));
+        lines
+    }
+
+    pub fn get_line_by_name<'a>(name: &str, network: &'a NetworkData) -> Option<&'a Line> {
+        network.lines.iter().find(|l| l.line_name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn get_line_by_route_id<'a>(route_id: &str, network: &'a NetworkData) -> Option<&'a Line> {
+        network
+            .lines
+            .iter()
+            .find(|l| Self::extract_line_id(&l.line_ref) == Some(route_id))
+    }
+
+    /// Stop with this (normalized) ID, looked up in the network's stop index
+    pub fn get_stop_by_id<'a>(stop_id: &str, network: &'a NetworkData) -> Option<&'a Stop> {
+        network.stop_index.get(stop_id).and_then(|&idx| network.stops.get(idx))
+    }
+
+    pub fn get_stops_for_line<'a>(line_ref: &str, network: &'a NetworkData) -> Vec<&'a Stop> {
+        network
+            .line_to_stops
+            .get(line_ref)
+            .into_iter()
+            .flatten()
+            .filter_map(|stop_id| Self::get_stop_by_id(stop_id, network))
+            .collect()
+    }
+
+    /// Stops served by both lines, in line A's outbound stop order when the GTFS sequence is known
+    pub fn find_transfer_stops<'a>(line_a: &str, line_b: &str, network: &'a NetworkData) -> Vec<&'a Stop> {
+        let line_b_stops: HashSet<&str> = network
+            .line_to_stops
+            .get(line_b)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+
+        let mut transfers: Vec<&Stop> = Self::get_stops_for_line(line_a, network)
+            .into_iter()
+            .filter(|s| line_b_stops.contains(s.stop_id.as_str()))
+            .collect();
+
+        let sequence = Self::get_stop_sequence(line_a, 0, &network.gtfs);
+        let position: HashMap<&str, usize> = sequence
+            .iter()
+            .enumerate()
+            .map(|(i, stop_id)| (stop_id.as_str(), i))
+            .collect();
+        transfers.sort_by(|a, b| {
+            let pos_a = position.get(a.stop_id.as_str()).copied().unwrap_or(usize::MAX);
+            let pos_b = position.get(b.stop_id.as_str()).copied().unwrap_or(usize::MAX);
+            pos_a.cmp(&pos_b).then_with(|| a.stop_name.cmp(&b.stop_name))
+        });
+        transfers
+    }
+
+    pub fn get_next_vehicles_for_stop<'a>(
+        stop_id: &str,
+        network: &'a NetworkData,
+    ) -> Vec<&'a RealTimeInfo> {
+        Self::get_stop_by_id(stop_id, network)
+            .map(|stop| {
+                let mut vehicles: Vec<&RealTimeInfo> = stop.real_time.iter().collect();
+                vehicles.sort_by_key(|rt| rt.timestamp.unwrap_or(i64::MAX));
+                vehicles
+            })
+            .unwrap_or_default()
+    }
+
+    /// Arrivals expected between `from_ts` and `to_ts` (Unix seconds, both included).
+    /// Arrivals without a predicted time are left out.
+    pub fn filter_real_time_by_time_window(arrivals: &[RealTimeInfo], from_ts: i64, to_ts: i64) -> Vec<&RealTimeInfo> {
+        arrivals
+            .iter()
+            .filter(|rt| rt.timestamp.is_some_and(|ts| (from_ts..=to_ts).contains(&ts)))
+            .collect()
+    }
+
+    /// (stop_id, predicted arrival, delay in seconds) of every stop in a trip's GTFS-RT
+    /// trip update, in stop sequence order. The departure stands in for a missing arrival.
+    /// Empty if the trip has no trip update.
+    pub fn get_trip_stop_sequence_realtime(trip_id: &str, cache: &CachedNetworkData) -> Vec<(String, Option<i64>, Option<i32>)> {
+        let Some(trip_update) = cache.trip_updates
+            .iter()
+            .find(|tu| tu.trip.trip_id.as_deref() == Some(trip_id))
+        else {
+            return Vec::new();
+        };
+
+        let mut updates: Vec<&gtfs_rt::trip_update::StopTimeUpdate> = trip_update.stop_time_update
+            .iter()
+            .filter(|stu| stu.stop_id.is_some())
+            .collect();
+        updates.sort_by_key(|stu| stu.stop_sequence.unwrap_or(u32::MAX));
+
+        updates
+            .into_iter()
+            .map(|stu| {
+                let arrival_time = stu.arrival.as_ref().and_then(|a| a.time)
+                    .or_else(|| stu.departure.as_ref().and_then(|d| d.time));
+                let delay = stu.arrival.as_ref().and_then(|a| a.delay)
+                    .or_else(|| stu.departure.as_ref().and_then(|d| d.delay));
+                (Self::normalize_stop_id(stu.stop_id.as_deref().unwrap_or_default()), arrival_time, delay)
+            })
+            .collect()
+    }
+
+    /// Build the full list of stops of a trip from its GTFS-RT trip update.
+    /// The current stop is the one reported by the vehicle's GPS position,
+    /// or the first stop not yet reached when no position is available.
+    pub fn get_vehicle_journey(
+        trip_id: &str,
+        cache: &CachedNetworkData,
+        network: &NetworkData,
+    ) -> Vec<JourneyStop> {
+        let now = Self::get_current_timestamp();
+        let gps_stop = cache.real_time
+            .iter()
+            .find(|rt| rt.trip_id == trip_id)
+            .and_then(|rt| rt.stop_id.clone());
+
+        let mut journey: Vec<JourneyStop> = Self::get_trip_stop_sequence_realtime(trip_id, cache)
+            .into_iter()
+            .map(|(raw_id, arrival_time, delay)| {
+                let stop = network.stops.iter().find(|s| {
+                    s.stop_id == raw_id || Some(&s.stop_id) == Self::extract_stop_id(&raw_id).as_ref()
                 });
 
-                // Sort by timestamp
-                stop_rt.sort_by_key(|rt| rt.timestamp.unwrap_or(i64::MAX));
+                JourneyStop {
+                    stop_id: stop.map(|s| s.stop_id.clone()).unwrap_or_else(|| raw_id.clone()),
+                    stop_name: stop.map(|s| s.stop_name.clone()).unwrap_or(raw_id),
+                    arrival_time,
+                    delay,
+                    status: StopVisitStatus::Upcoming,
+                    is_terminus: false,
+                }
+            })
+            .collect();
+
+        let current_idx = gps_stop
+            .and_then(|sid| journey.iter().position(|js| js.stop_id == sid))
+            .or_else(|| journey.iter().position(|js| js.arrival_time.map(|t| t >= now).unwrap_or(true)));
+
+        for (idx, js) in journey.iter_mut().enumerate() {
+            js.status = match current_idx {
+                Some(current) if idx < current => StopVisitStatus::Departed,
+                Some(current) if idx == current => StopVisitStatus::Current,
+                Some(_) => StopVisitStatus::Upcoming,
+                None => StopVisitStatus::Departed,
+            };
+        }
+
+        if let Some(last) = journey.last_mut() {
+            last.is_terminus = true;
+        }
+
+        journey
+    }
+
+    pub fn format_timestamp(timestamp: i64) -> String {
+        match Utc.timestamp_opt(timestamp, 0).single() {
+            Some(dt) => {
+                let paris_time = dt.with_timezone(&Paris);
+                paris_time.format("%H:%M:%S").to_string()
+            }
+            None => "??:??:??".to_string(),
+        }
+    }
+
+    /// Arrival time relative to `now`: "Now" within 30 s, "in N min" / "N min ago" (rounded
+    /// to the nearest minute), or "at HH:MM" (Paris time) when more than an hour away
+    pub fn format_relative_time(timestamp: i64, now: i64) -> String {
+        let diff = timestamp - now;
+        if diff.abs() < 30 {
+            return "Now".to_string();
+        }
+        if diff.abs() > 3600 {
+            return format!("at {}", &Self::format_timestamp(timestamp)[..5]);
+        }
+
+        let minutes = (diff.abs() + 30) / 60;
+        if diff > 0 {
+            format!("in {} min", minutes)
+        } else {
+            format!("{} min ago", minutes)
+        }
+    }
+
+    pub fn format_timestamp_full(timestamp: i64) -> String {
+        match Utc.timestamp_opt(timestamp, 0).single() {
+            Some(dt) => {
+                let paris_time = dt.with_timezone(&Paris);
+                paris_time.format("%Y-%m-%d %H:%M:%S").to_string()
+            }
+            None => format!("Invalid timestamp: {}", timestamp),
+        }
+    }
+
+    /// Alert validity like "Until 15 Jun 18:00" or "25 May 09:00 – 25 May 22:00" (Paris time);
+    /// periods that have already started only show their end, and no end means "Ongoing"
+    pub fn format_alert_period(start: Option<i64>, end: Option<i64>) -> String {
+        let format = |timestamp: i64| match Utc.timestamp_opt(timestamp, 0).single() {
+            Some(dt) => dt.with_timezone(&Paris).format("%d %b %H:%M").to_string(),
+            None => "??".to_string(),
+        };
+
+        match (start, end) {
+            (_, None) => "Ongoing".to_string(),
+            (Some(start), Some(end)) if start > Self::get_current_timestamp() => {
+                format!("{} – {}", format(start), format(end))
+            }
+            (_, Some(end)) => format!("Until {}", format(end)),
+        }
+    }
+
+    pub fn get_current_timestamp() -> i64 {
+        Utc::now().timestamp()
+    }
+
+    // ========================================================================
+    // Network Analysis
+    // ========================================================================
+
+    /// Simplified betweenness centrality of every stop, normalized to [0, 1].
+    ///
+    /// Up to 1000 stop pairs are sampled with a fixed-seed generator (so scores
+    /// stay stable across refreshes); each intermediate stop on the shortest
+    /// path between a pair earns one point.
+    pub fn compute_transfer_hub_scores(graph: &TransitGraph) -> HashMap<String, f64> {
+        const MAX_SAMPLED_PAIRS: usize = 1000;
+
+        let stop_count = graph.stop_count;
+        let mut counts = vec![0u32; stop_count];
+
+        if stop_count >= 3 {
+            let total_pairs = stop_count * (stop_count - 1) / 2;
+            let mut seed: u64 = 0x9E37_79B9_7F4A_7C15;
+            let mut next_index = || {
+                // xorshift64
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                (seed % stop_count as u64) as usize
+            };
+
+            for _ in 0..MAX_SAMPLED_PAIRS.min(total_pairs) {
+                let from = next_index();
+                let to = next_index();
+                if from == to {
+                    continue;
+                }
+
+                if let Some(path) = Self::shortest_path(graph, from, to) {
+                    for &node in &path[1..path.len() - 1] {
+                        if node < stop_count {
+                            counts[node] += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        let max_count = counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+
+        graph.nodes[..stop_count]
+            .iter()
+            .zip(counts)
+            .map(|(stop_id, count)| (stop_id.clone(), count as f64 / max_count))
+            .collect()
+    }
+
+    /// Directed stop graph of the GTFS stop sequences: an edge links consecutive stops of a
+    /// route direction and weighs the median headway in seconds (see `compute_headway_seconds`)
+    /// on `date` of the most frequent line between them, `UNKNOWN_HEADWAY_SECS` without timetable.
+    pub fn compute_network_graph(cache: &GTFSCache, date: NaiveDate) -> StableGraph<String, u32> {
+        const UNKNOWN_HEADWAY_SECS: u32 = 3600;
+
+        let mut graph = StableGraph::new();
+        let mut nodes: HashMap<&str, NodeIndex> = HashMap::new();
+        let mut edges: HashMap<(NodeIndex, NodeIndex), u32> = HashMap::new();
+
+        for (route_id, by_direction) in &cache.stop_sequences {
+            for (direction_id, sequence) in by_direction {
+                for pair in sequence.windows(2) {
+                    let headway = Self::compute_headway_seconds(&pair[0], route_id, *direction_id, date, cache)
+                        .unwrap_or(UNKNOWN_HEADWAY_SECS);
+                    let [from, to] = [&pair[0], &pair[1]].map(|stop_id| {
+                        *nodes.entry(stop_id.as_str()).or_insert_with(|| graph.add_node(stop_id.clone()))
+                    });
+                    edges.entry((from, to))
+                        .and_modify(|weight| *weight = (*weight).min(headway))
+                        .or_insert(headway);
+                }
+            }
+        }
+
+        for ((from, to), headway) in edges {
+            graph.add_edge(from, to, headway);
+        }
+        graph
+    }
+
+    /// Longest of the shortest paths between two stops of the network graph, counted in
+    /// stops travelled (not time). Pairs with no path between them are ignored; None without edges.
+    pub fn graph_diameter(graph: &StableGraph<String, u32>) -> Option<u32> {
+        let mut diameter = None;
+        let mut depth = vec![u32::MAX; graph.node_bound()];
+        let mut queue = VecDeque::new();
+
+        for start in graph.node_indices() {
+            depth.fill(u32::MAX);
+            depth[start.index()] = 0;
+            queue.push_back(start);
+
+            while let Some(node) = queue.pop_front() {
+                let next_depth = depth[node.index()] + 1;
+                for next in graph.neighbors(node) {
+                    if depth[next.index()] == u32::MAX {
+                        depth[next.index()] = next_depth;
+                        diameter = diameter.max(Some(next_depth));
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        diameter
+    }
+
+    /// Dijkstra shortest path between two graph nodes (inclusive of both ends)
+    fn shortest_path(graph: &TransitGraph, from: usize, to: usize) -> Option<Vec<usize>> {
+        let mut dist = vec![u32::MAX; graph.nodes.len()];
+        let mut prev: Vec<Option<usize>> = vec![None; graph.nodes.len()];
+        let mut heap = BinaryHeap::new();
+
+        dist[from] = 0;
+        heap.push(Reverse((0u32, from)));
+
+        while let Some(Reverse((cost, node))) = heap.pop() {
+            if node == to {
+                break;
+            }
+            if cost > dist[node] {
+                continue;
+            }
+            for &(next, weight) in &graph.adjacency[node] {
+                let next_cost = cost.saturating_add(weight);
+                if next_cost < dist[next] {
+                    dist[next] = next_cost;
+                    prev[next] = Some(node);
+                    heap.push(Reverse((next_cost, next)));
+                }
+            }
+        }
+
+        if dist[to] == u32::MAX {
+            return None;
+        }
+
+        let mut path = vec![to];
+        let mut current = to;
+        while let Some(p) = prev[current] {
+            path.push(p);
+            current = p;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    // ========================================================================
+    // Reliability
+    // ========================================================================
+
+    /// Fraction of a route's logged arrivals within `ON_TIME_TOLERANCE_SECS` of the schedule.
+    /// None if the log can't be read or has no arrival for the route.
+    pub fn compute_on_time_rate(route_id: &str, log_path: &Path) -> Option<f32> {
+        let records = Self::read_log_records::<LoggedArrival>(log_path).ok()?;
+        Self::on_time_rate(route_id, &records)
+    }
+
+    /// `compute_on_time_rate` of every route in an arrival log (see `ArrivalLogger`),
+    /// counting only arrivals logged at or after `since` when given.
+    /// Lines that aren't valid log records are skipped.
+    pub fn compute_on_time_rates(log_path: &Path, since: Option<i64>) -> Result<HashMap<String, f32>> {
+        let records: Vec<LoggedArrival> = Self::read_log_records::<LoggedArrival>(log_path)?
+            .into_iter()
+            .filter(|record| since.is_none_or(|since| record.ts >= since))
+            .collect();
+        let routes: HashSet<&str> = records.iter().map(|record| record.route.as_str()).collect();
+
+        Ok(routes
+            .into_iter()
+            .filter_map(|route_id| Some((route_id.to_string(), Self::on_time_rate(route_id, &records)?)))
+            .collect())
+    }
 
-                // OPTIONAL: Limit to next N arrivals to avoid overwhelming UI
-                const MAX_ARRIVALS_PER_STOP: usize = 10;
-                if stop_rt.len() > MAX_ARRIVALS_PER_STOP {
-                    stop_rt.truncate(MAX_ARRIVALS_PER_STOP);
-                }
+    /// On-time fraction of a route's arrivals among `records`, None without any
+    fn on_time_rate(route_id: &str, records: &[LoggedArrival]) -> Option<f32> {
+        let delays: Vec<i32> = records.iter().filter(|r| r.route == route_id).map(|r| r.delay).collect();
+        if delays.is_empty() {
+            return None;
+        }
+        let on_time = delays.iter().filter(|delay| delay.abs() <= Self::ON_TIME_TOLERANCE_SECS).count();
+        Some(on_time as f32 / delays.len() as f32)
+    }
 
-                let stop_alerts: Vec<AlertInfo> = alerts
-                    .iter()
-                    .filter(|alert| alert.stop_ids.contains(&id))
-                    .cloned()
-                    .collect();
+    /// Records of the arrival log of one kind, skipping the lines of other kinds and
+    /// anything that isn't valid JSON
+    fn read_log_records<T: serde::de::DeserializeOwned>(log_path: &Path) -> Result<Vec<T>> {
+        let contents = fs::read_to_string(log_path)
+            .map_err(|e| NVTError::file(format!("Failed to read arrival log {:?}", log_path)).with_source(e))?;
+        Ok(contents.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+    }
 
-                Stop {
-                    stop_id: id,
-                    stop_name: name,
-                    latitude: lat,
-                    longitude: lon,
-                    lines: line_refs,
-                    alerts: stop_alerts,
-                    real_time: stop_rt,
-                }
-            })
+    /// Mean absolute ETA error of a route in seconds, from the arrival log.
+    /// None if the log can't be read or has no ETA record for the route.
+    pub fn compute_eta_mae(route_id: &str, log_path: &Path) -> Option<f32> {
+        let records = Self::read_log_records::<LoggedEtaError>(log_path).ok()?;
+        Self::eta_mae(route_id, &records)
+    }
+
+    /// `compute_eta_mae` of every route in an arrival log,
+    /// counting only records logged at or after `since` when given
+    pub fn compute_eta_maes(log_path: &Path, since: Option<i64>) -> Result<HashMap<String, f32>> {
+        let records: Vec<LoggedEtaError> = Self::read_log_records::<LoggedEtaError>(log_path)?
+            .into_iter()
+            .filter(|record| since.is_none_or(|since| record.ts >= since))
             .collect();
+        let routes: HashSet<&str> = records.iter().map(|record| record.route.as_str()).collect();
 
-        let lines: Vec<Line> = lines_data
+        Ok(routes
             .into_iter()
-            .map(|(ref_, name, code, destinations)| {
-                let line_id = Self::extract_line_id(&ref_).unwrap_or("");
-                let color = line_color_map
-                    .get(line_id)
-                    .cloned()
-                    .unwrap_or_else(|| "808080".to_string());
+            .filter_map(|route_id| Some((route_id.to_string(), Self::eta_mae(route_id, &records)?)))
+            .collect())
+    }
 
-                let line_alerts: Vec<AlertInfo> = alerts
-                    .iter()
-                    .filter(|alert| {
-                        alert.route_ids.contains(&code) ||
-                            alert.route_ids.contains(&line_id.to_string())
-                    })
-                    .cloned()
-                    .collect();
+    /// Mean absolute ETA error of a route's records among `records`, None without any
+    fn eta_mae(route_id: &str, records: &[LoggedEtaError]) -> Option<f32> {
+        let errors: Vec<i64> = records.iter().filter(|r| r.route == route_id).map(|r| r.eta_error.abs()).collect();
+        if errors.is_empty() {
+            return None;
+        }
+        Some(errors.iter().sum::<i64>() as f32 / errors.len() as f32)
+    }
 
-                let mut line_rt: Vec<RealTimeInfo> = real_time
-                    .iter()
-                    .filter(|rt| {
-                        rt.route_id
-                            .as_ref()
-                            .map(|route| route == line_id)
-                            .unwrap_or(false)
-                    })
-                    .filter(|rt| {
-                        if let Some(ts) = rt.timestamp {
-                            ts >= cutoff_time
-                        } else {
-                            true
-                        }
-                    })
-                    .cloned()
-                    .collect();
+    /// Historical load indicator of a route at a stop during one hour of the day (Paris time),
+    /// from the arrival log, see `compute_load_indicators`. None without logged arrivals.
+    pub fn get_occupancy_trend(route_id: &str, stop_id: &str, hour_of_day: u8, log_path: &Path) -> Option<f32> {
+        Self::compute_load_indicators(log_path, stop_id).ok()?.remove(&(route_id.to_string(), hour_of_day))
+    }
 
-                line_rt.sort_by_key(|rt| rt.timestamp.unwrap_or(i64::MAX));
+    /// Load indicator per (route_id, hour of day) at a stop, using the mean absolute delay of
+    /// the logged arrivals scheduled in that hour as a proxy for crowding: 0.0 when vehicles
+    /// run on time, 1.0 when they are `FULL_LOAD_DELAY_SECS` or more off schedule on average.
+    /// Hours are Paris time, all logged days together.
+    pub fn compute_load_indicators(log_path: &Path, stop_id: &str) -> Result<HashMap<(String, u8), f32>> {
+        let contents = fs::read_to_string(log_path)
+            .map_err(|e| NVTError::file(format!("Failed to read arrival log {:?}", log_path)).with_source(e))?;
+
+        // (route_id, hour) -> (sum of absolute delays, arrivals)
+        let mut delays: HashMap<(String, u8), (i64, u32)> = HashMap::new();
+        for record in contents.lines().filter_map(|line| serde_json::from_str::<LoggedArrival>(line).ok()) {
+            if record.stop != stop_id || record.sched == 0 {
+                continue;
+            }
+            let (sum, count) = delays.entry((record.route, Self::local_hour(record.sched))).or_default();
+            *sum += record.delay.unsigned_abs() as i64;
+            *count += 1;
+        }
 
-                Line {
-                    line_ref: ref_,
-                    line_name: name,
-                    line_code: code,
-                    destinations,
-                    alerts: line_alerts,
-                    real_time: line_rt,
-                    color,
-                }
+        Ok(delays
+            .into_iter()
+            .map(|(key, (sum, count))| {
+                let mean = sum as f32 / count as f32;
+                (key, (mean / Self::FULL_LOAD_DELAY_SECS as f32).min(1.0))
             })
+            .collect())
+    }
+
+    /// Hour of the day of a Unix timestamp in Paris time
+    pub fn local_hour(timestamp: i64) -> u8 {
+        Utc.timestamp_opt(timestamp, 0)
+            .single()
+            .map_or(0, |dt| dt.with_timezone(&Paris).hour() as u8)
+    }
+
+    // ========================================================================
+    // Journey Planning
+    // ========================================================================
+
+    /// Earliest-arrival itinerary from one stop to another, leaving at `at_time`.
+    /// None if `to_stop_id` isn't reachable within `JOURNEY_HORIZON_SECS`.
+    pub fn plan_journey(
+        from_stop_id: &str,
+        to_stop_id: &str,
+        at_time: i64,
+        walking_speed_kmh: f32,
+        network: &NetworkData,
+        cache: &GTFSCache,
+    ) -> Option<Journey> {
+        let from = Self::normalize_stop_id(from_stop_id);
+        let to = Self::normalize_stop_id(to_stop_id);
+        if from == to {
+            return Some(Journey::default());
+        }
+
+        let search = Self::earliest_arrivals(&from, Some(&to), at_time, Self::JOURNEY_HORIZON_SECS, walking_speed_kmh, network, cache);
+        search.arrivals.get(to.as_str())?;
+
+        let mut hops = Vec::new();
+        let mut current = to.as_str();
+        while let Some(hop) = search.previous.get(current) {
+            hops.push((current, *hop));
+            current = hop.from();
+        }
+        hops.reverse();
+
+        // Merge consecutive rides on the same route direction into one leg
+        let mut legs: Vec<Leg> = Vec::new();
+        let mut riding: Option<(&str, u32)> = None;
+        for (stop, hop) in hops {
+            let rides_before = legs.iter().filter(|leg| leg.mode == LegMode::Ride).count() as u32;
+            let JourneyHop::Ride { from, route_id, direction_id, depart } = hop else {
+                let from = hop.from();
+                let depart = search.arrivals.get(from).copied().unwrap_or(at_time);
+                legs.push(Leg {
+                    mode: LegMode::Walk,
+                    board_stop_id: from.to_string(),
+                    alight_stop_id: stop.to_string(),
+                    route_id: String::new(),
+                    depart_time: depart,
+                    arrive_time: search.arrivals.get(stop).copied().unwrap_or(depart),
+                    transfers: rides_before.saturating_sub(1),
+                });
+                riding = None;
+                continue;
+            };
+            let arrive = search.arrivals.get(stop).copied().unwrap_or(depart);
+
+            match legs.last_mut() {
+                Some(leg) if riding == Some((route_id, direction_id)) => {
+                    leg.alight_stop_id = stop.to_string();
+                    leg.arrive_time = arrive;
+                }
+                _ => legs.push(Leg {
+                    mode: LegMode::Ride,
+                    board_stop_id: from.to_string(),
+                    alight_stop_id: stop.to_string(),
+                    route_id: route_id.to_string(),
+                    depart_time: depart,
+                    arrive_time: arrive,
+                    transfers: rides_before,
+                }),
+            }
+            riding = Some((route_id, direction_id));
+        }
+
+        Some(Journey { legs })
+    }
+
+    /// Stops reachable from `from_stop_id` within `max_minutes` of `at_time`,
+    /// with their minimum travel time in minutes (rounded up), nearest first.
+    /// The starting stop itself is included at 0 minutes.
+    pub fn reachable_stops(
+        from_stop_id: &str,
+        max_minutes: u32,
+        at_time: i64,
+        walking_speed_kmh: f32,
+        network: &NetworkData,
+        cache: &GTFSCache,
+    ) -> Vec<(String, u32)> {
+        let from = Self::normalize_stop_id(from_stop_id);
+        let search = Self::earliest_arrivals(&from, None, at_time, max_minutes as i64 * 60, walking_speed_kmh, network, cache);
+
+        let mut reachable: Vec<(String, u32)> = search
+            .arrivals
+            .into_iter()
+            .map(|(stop_id, arrival)| (stop_id.to_string(), ((arrival - at_time).max(0) as u32).div_ceil(60)))
+            .filter(|(_, minutes)| *minutes <= max_minutes)
+            .collect();
+        reachable.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        reachable
+    }
+
+    /// Label-setting Dijkstra over stops shared by the journey planner and the isochrone.
+    ///
+    /// Riding from a stop to the next one of a route's GTFS stop sequence costs
+    /// the wait for the next departure (real-time when known, static timetable
+    /// otherwise) plus the ride. Changing vehicle needs `MIN_TRANSFER_SECS`, and
+    /// stops up to `MAX_WALK_TRANSFER_M` apart can be walked between. Stops
+    /// arriving later than `max_duration_secs` after `at_time` are left out; the
+    /// search stops early once `to` is settled.
+    fn earliest_arrivals<'a>(
+        from: &'a str,
+        to: Option<&str>,
+        at_time: i64,
+        max_duration_secs: i64,
+        walking_speed_kmh: f32,
+        network: &'a NetworkData,
+        cache: &'a GTFSCache,
+    ) -> JourneySearch<'a> {
+        // stop_id -> (route_id, direction_id, next stop_id on that route direction)
+        let mut next_stops: HashMap<&str, Vec<(&str, u32, &str)>> = HashMap::new();
+        for (route_id, by_direction) in &cache.stop_sequences {
+            for (direction_id, sequence) in by_direction {
+                for pair in sequence.windows(2) {
+                    next_stops
+                        .entry(pair[0].as_str())
+                        .or_default()
+                        .push((route_id.as_str(), *direction_id, pair[1].as_str()));
+                }
+            }
+        }
+
+        let mut timetable = JourneyTimetable::new(at_time, network, cache);
+        let latest_arrival = at_time + max_duration_secs;
+        let mut search = JourneySearch::default();
+        let mut heap = BinaryHeap::new();
+
+        search.arrivals.insert(from, at_time);
+        heap.push(Reverse((at_time, from)));
+
+        while let Some(Reverse((time, stop))) = heap.pop() {
+            if Some(stop) == to {
+                break;
+            }
+            if search.arrivals.get(stop).is_some_and(|&arrival| time > arrival) {
+                continue;
+            }
+
+            let arrived_by = search.previous.get(stop).copied();
+            let mut candidates: Vec<(&str, i64, JourneyHop, Option<String>)> = Vec::new();
+
+            for &(route_id, direction_id, next_stop) in next_stops.get(stop).into_iter().flatten() {
+                // Stay on the vehicle when continuing on the same route direction
+                let on_board = match arrived_by {
+                    Some(JourneyHop::Ride { route_id: r, direction_id: d, .. }) if r == route_id && d == direction_id => {
+                        search.trips.get(stop).map(|trip| (time, trip.clone()))
+                    }
+                    _ => None,
+                };
+                let boarding = on_board.or_else(|| {
+                    let ready = match arrived_by {
+                        Some(JourneyHop::Ride { .. }) => time + Self::MIN_TRANSFER_SECS,
+                        _ => time,
+                    };
+                    timetable.next_departure(stop, route_id, direction_id, ready)
+                });
+                let Some((depart, trip_id)) = boarding else {
+                    continue;
+                };
+                // The boarded trip's departure from the next stop stands in for its arrival there
+                let Some(arrive) = timetable.trip_time(next_stop, route_id, direction_id, &trip_id, depart) else {
+                    continue;
+                };
+                candidates.push((next_stop, arrive, JourneyHop::Ride { from: stop, route_id, direction_id, depart }, Some(trip_id)));
+            }
+
+            // Only one walk in a row, so transfers stay between nearby stops
+            if !matches!(arrived_by, Some(JourneyHop::Walk { .. }))
+                && let Some(origin) = Self::get_stop_by_id(stop, network)
+            {
+                let (min_lat, max_lat, min_lon, max_lon) =
+                    Self::bounding_box(origin.latitude, origin.longitude, Self::MAX_WALK_TRANSFER_M / 1000.0);
+                for other in Self::get_stops_in_bbox(min_lat, max_lat, min_lon, max_lon, network)
+                    .into_iter()
+                    .filter(|s| s.stop_id != stop)
+                {
+                    let distance = Self::haversine_m(origin.latitude, origin.longitude, other.latitude, other.longitude);
+                    if distance <= Self::MAX_WALK_TRANSFER_M {
+                        let walk = Self::estimate_walk_time(
+                            (origin.latitude, origin.longitude),
+                            (other.latitude, other.longitude),
+                            walking_speed_kmh,
+                        );
+                        let arrive = time + walk as i64;
+                        candidates.push((other.stop_id.as_str(), arrive, JourneyHop::Walk { from: stop }, None));
+                    }
+                }
+            }
+
+            for (next_stop, arrive, hop, trip_id) in candidates {
+                if arrive <= latest_arrival && search.arrivals.get(next_stop).is_none_or(|&current| arrive < current) {
+                    search.arrivals.insert(next_stop, arrive);
+                    search.previous.insert(next_stop, hop);
+                    match trip_id {
+                        Some(trip_id) => search.trips.insert(next_stop, trip_id),
+                        None => search.trips.remove(next_stop),
+                    };
+                    heap.push(Reverse((arrive, next_stop)));
+                }
+            }
+        }
+
+        search
+    }
+
+    // ========================================================================
+    // Data Export
+    // ========================================================================
+
+    /// Export all stops with line/alert/arrival counts to an Apache Parquet file
+    pub fn export_stops_parquet(network: &NetworkData, path: &Path) -> Result<()> {
+        let schema = "
+            message stops {
+                REQUIRED BYTE_ARRAY stop_id (UTF8);
+                REQUIRED BYTE_ARRAY stop_name (UTF8);
+                REQUIRED DOUBLE latitude;
+                REQUIRED DOUBLE longitude;
+                REQUIRED INT32 line_count;
+                REQUIRED INT32 alert_count;
+                REQUIRED INT32 arrival_count;
+            }
+        ";
+
+        let stops = &network.stops;
+        let columns = vec![
+            ParquetColumn::Text(stops.iter().map(|s| Some(s.stop_id.clone())).collect()),
+            ParquetColumn::Text(stops.iter().map(|s| Some(s.stop_name.clone())).collect()),
+            ParquetColumn::Double(stops.iter().map(|s| s.latitude).collect()),
+            ParquetColumn::Double(stops.iter().map(|s| s.longitude).collect()),
+            ParquetColumn::Int32(stops.iter().map(|s| Some(s.lines.len() as i32)).collect()),
+            ParquetColumn::Int32(stops.iter().map(|s| Some(s.alerts.len() as i32)).collect()),
+            ParquetColumn::Int32(stops.iter().map(|s| Some(s.real_time.len() as i32)).collect()),
+        ];
+
+        Self::write_parquet_file(path, schema, columns, "stops")
+    }
+
+    /// Export every upcoming arrival of every stop to an Apache Parquet file
+    pub fn export_arrivals_parquet(network: &NetworkData, path: &Path) -> Result<()> {
+        let schema = "
+            message arrivals {
+                REQUIRED BYTE_ARRAY stop_id (UTF8);
+                REQUIRED BYTE_ARRAY trip_id (UTF8);
+                OPTIONAL BYTE_ARRAY route_id (UTF8);
+                REQUIRED BYTE_ARRAY vehicle_id (UTF8);
+                OPTIONAL INT64 scheduled_timestamp;
+                OPTIONAL INT32 delay_seconds;
+                REQUIRED BOOLEAN is_realtime;
+                OPTIONAL INT32 occupancy;
+            }
+        ";
+
+        let arrivals: Vec<(&Stop, &RealTimeInfo)> = network.stops
+            .iter()
+            .flat_map(|stop| stop.real_time.iter().map(move |rt| (stop, rt)))
             .collect();
 
-        NetworkData { stops, lines }
+        let columns = vec![
+            ParquetColumn::Text(arrivals.iter().map(|(s, _)| Some(s.stop_id.clone())).collect()),
+            ParquetColumn::Text(arrivals.iter().map(|(_, rt)| Some(rt.trip_id.clone())).collect()),
+            ParquetColumn::Text(arrivals.iter().map(|(_, rt)| rt.route_id.clone()).collect()),
+            ParquetColumn::Text(arrivals.iter().map(|(_, rt)| Some(rt.vehicle_id.clone())).collect()),
+            ParquetColumn::Int64(arrivals.iter().map(|(_, rt)| rt.timestamp).collect()),
+            ParquetColumn::Int32(arrivals.iter().map(|(_, rt)| rt.delay).collect()),
+            ParquetColumn::Bool(arrivals.iter().map(|(_, rt)| !Self::is_scheduled(rt)).collect()),
+            ParquetColumn::Int32(arrivals.iter().map(|(_, rt)| rt.occupancy.map(|o| o as i32)).collect()),
+        ];
+
+        Self::write_parquet_file(path, schema, columns, "arrivals")
     }
 
-    fn extract_stop_id(full_id: &str) -> Option<String> {
-        if full_id.contains("BP:") {
-            full_id
-                .split("BP:")
-                .nth(1)?
-                .split(':')
-                .next()
-                .map(String::from)
-        } else if full_id.contains(':') {
-            let parts: Vec<&str> = full_id.split(':').collect();
-            if parts.len() >= 2 {
-                Some(parts[parts.len() - 2].to_string())
-            } else {
-                Some(full_id.to_string())
+    /// Write the upcoming arrivals of the given stops as CSV, one row per `real_time` entry
+    pub fn export_arrivals_csv(stops: &[&Stop], network: &NetworkData, writer: impl Write) -> Result<()> {
+        let mut csv_writer = csv::Writer::from_writer(writer);
+        csv_writer.write_record([
+            "stop_id", "stop_name", "line_code", "destination", "arrival_time_iso8601", "delay_seconds", "source",
+        ])?;
+
+        for stop in stops {
+            for rt in &stop.real_time {
+                let line_code = rt.route_id.as_deref()
+                    .and_then(|route_id| Self::get_line_by_route_id(route_id, network))
+                    .map(|l| l.line_code.clone())
+                    .unwrap_or_default();
+                let arrival = rt.timestamp
+                    .and_then(|ts| DateTime::from_timestamp(ts, 0))
+                    .map(|dt| dt.with_timezone(&Paris).to_rfc3339())
+                    .unwrap_or_default();
+                let source = match rt.vehicle_id.as_str() {
+                    "static" => "static",
+                    "scheduled" | "fallback_trip_update" => "scheduled",
+                    _ => "realtime",
+                };
+
+                csv_writer.write_record([
+                    stop.stop_id.as_str(),
+                    stop.stop_name.as_str(),
+                    line_code.as_str(),
+                    rt.destination.as_deref().unwrap_or_default(),
+                    arrival.as_str(),
+                    &rt.delay.map(|d| d.to_string()).unwrap_or_default(),
+                    source,
+                ])?;
             }
-        } else {
-            Some(full_id.to_string())
         }
+
+        csv_writer.flush()?;
+        Ok(())
     }
 
-    pub fn extract_line_id(line_ref: &str) -> Option<&str> {
-        line_ref.split(':').nth(2)
+    /// All stops as a GeoJSON FeatureCollection of points, for QGIS, Leaflet and the like
+    pub fn export_stops_geojson(network: &NetworkData) -> String {
+        let features: Vec<serde_json::Value> = network.stops
+            .iter()
+            .map(|stop| serde_json::json!({
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [stop.longitude, stop.latitude] },
+                "properties": {
+                    "stop_id": stop.stop_id,
+                    "stop_name": stop.stop_name,
+                    "lines": stop.lines,
+                    "alert_count": stop.alerts.len(),
+                },
+            }))
+            .collect();
+
+        Self::geojson_collection(features)
     }
 
-    pub fn get_line_color(line_code: &str, network: &NetworkData) -> String {
-        network
-            .lines
+    /// GPS-tracked vehicle positions as a GeoJSON FeatureCollection of points
+    pub fn export_vehicles_geojson(network: &NetworkData) -> String {
+        let mut seen: HashSet<&str> = HashSet::new();
+        let features: Vec<serde_json::Value> = network.lines
             .iter()
-            .find(|l| l.line_code.eq_ignore_ascii_case(line_code))
-            .map(|l| l.color.clone())
-            .unwrap_or_else(|| "808080".to_string())
+            .flat_map(|line| &line.real_time)
+            .filter(|rt| !Self::is_scheduled(rt))
+            .filter(|rt| rt.latitude != 0.0 || rt.longitude != 0.0)
+            .filter(|rt| seen.insert(rt.vehicle_id.as_str()))
+            .map(|rt| serde_json::json!({
+                "type": "Feature",
+                "geometry": { "type": "Point", "coordinates": [rt.longitude, rt.latitude] },
+                "properties": {
+                    "vehicle_id": rt.vehicle_id,
+                    "route_id": rt.route_id,
+                    "timestamp": rt.timestamp,
+                    "delay": rt.delay,
+                },
+            }))
+            .collect();
+
+        Self::geojson_collection(features)
     }
 
-    pub fn parse_hex_color(hex_color: &str) -> (u8, u8, u8) {
-        if hex_color.len() != 6 {
-            return (128, 128, 128);
+    fn geojson_collection(features: Vec<serde_json::Value>) -> String {
+        serde_json::json!({ "type": "FeatureCollection", "features": features }).to_string()
+    }
+
+    /// Write a single row group Parquet file; columns must follow the schema order
+    fn write_parquet_file(
+        path: &Path,
+        schema: &str,
+        columns: Vec<ParquetColumn>,
+        dataset: &str,
+    ) -> Result<()> {
+        let schema = Arc::new(parse_message_type(schema)
+            .map_err(|e| NVTError::parse("Invalid Parquet schema").with_source(e))?);
+
+        let metadata = vec![
+            KeyValue::new("dataset".to_string(), dataset.to_string()),
+            KeyValue::new("source".to_string(), "TBM Open Data (SIRI-Lite, GTFS-RT)".to_string()),
+            KeyValue::new("generator".to_string(), format!("NVT {}", env!("CARGO_PKG_VERSION"))),
+            KeyValue::new("exported_at".to_string(), Utc::now().to_rfc3339()),
+        ];
+        let props = Arc::new(WriterProperties::builder()
+            .set_key_value_metadata(Some(metadata))
+            .build());
+
+        let file = fs::File::create(path)
+            .map_err(|e| NVTError::file(format!("Failed to create {:?}", path)).with_source(e))?;
+
+        let write_err = |e: parquet::errors::ParquetError| {
+            NVTError::file(format!("Failed to write Parquet file {:?}", path)).with_source(e)
+        };
+
+        let mut writer = SerializedFileWriter::new(file, schema, props).map_err(write_err)?;
+        let mut row_group = writer.next_row_group().map_err(write_err)?;
+
+        for column in columns {
+            let mut col_writer = row_group.next_column()
+                .map_err(write_err)?
+                .ok_or_else(|| NVTError::parse("More columns than schema fields"))?;
+
+            match column {
+                ParquetColumn::Text(values) => {
+                    let levels: Vec<i16> = values.iter().map(|v| v.is_some() as i16).collect();
+                    let present: Vec<ByteArray> = values.iter()
+                        .flatten()
+                        .map(|v| ByteArray::from(v.as_str()))
+                        .collect();
+                    col_writer.typed::<ByteArrayType>()
+                        .write_batch(&present, Some(&levels), None)
+                        .map_err(write_err)?;
+                }
+                ParquetColumn::Double(values) => {
+                    col_writer.typed::<DoubleType>()
+                        .write_batch(&values, None, None)
+                        .map_err(write_err)?;
+                }
+                ParquetColumn::Int32(values) => {
+                    let levels: Vec<i16> = values.iter().map(|v| v.is_some() as i16).collect();
+                    let present: Vec<i32> = values.into_iter().flatten().collect();
+                    col_writer.typed::<Int32Type>()
+                        .write_batch(&present, Some(&levels), None)
+                        .map_err(write_err)?;
+                }
+                ParquetColumn::Int64(values) => {
+                    let levels: Vec<i16> = values.iter().map(|v| v.is_some() as i16).collect();
+                    let present: Vec<i64> = values.into_iter().flatten().collect();
+                    col_writer.typed::<Int64Type>()
+                        .write_batch(&present, Some(&levels), None)
+                        .map_err(write_err)?;
+                }
+                ParquetColumn::Bool(values) => {
+                    col_writer.typed::<BoolType>()
+                        .write_batch(&values, None, None)
+                        .map_err(write_err)?;
+                }
+            }
+
+            col_writer.close().map_err(write_err)?;
         }
-        let r = u8::from_str_radix(&hex_color[0..2], 16).unwrap_or(128);
-        let g = u8::from_str_radix(&hex_color[2..4], 16).unwrap_or(128);
-        let b = u8::from_str_radix(&hex_color[4..6], 16).unwrap_or(128);
-        (r, g, b)
+
+        row_group.close().map_err(write_err)?;
+        writer.close().map_err(write_err)?;
+
+        Ok(())
     }
 
-    pub fn get_line_color_rgb(line_code: &str, network: &NetworkData) -> (u8, u8, u8) {
-        let hex_color = Self::get_line_color(line_code, network);
-        Self::parse_hex_color(&hex_color)
+    /// Count lines, vehicles, alerts and delays in one pass over the lines, plus the stops' alerts
+    pub fn network_summary(network: &NetworkData) -> NetworkSummary {
+        let mut summary = NetworkSummary {
+            total_stops: network.stops.len(),
+            total_lines: network.lines.len(),
+            ..NetworkSummary::default()
+        };
+
+        let mut vehicles: HashSet<&str> = HashSet::new();
+        let mut alerts: HashSet<&str> = HashSet::new();
+        let (mut delay_sum, mut delay_count) = (0i64, 0usize);
+
+        for line in &network.lines {
+            if Self::is_tram_line(line) {
+                summary.tram_lines += 1;
+            } else {
+                summary.bus_lines += 1;
+            }
+
+            let mut delayed = false;
+            for rt in &line.real_time {
+                if !Self::is_scheduled(rt) {
+                    vehicles.insert(&rt.vehicle_id);
+                }
+                if let Some(delay) = rt.delay {
+                    delay_sum += delay as i64;
+                    delay_count += 1;
+                }
+                delayed |= rt.delay.unwrap_or(0).abs() > 60;
+            }
+            if delayed {
+                summary.lines_with_delays += 1;
+            }
+
+            alerts.extend(line.alerts.iter().map(|a| a.id.as_str()));
+        }
+        alerts.extend(network.stops.iter().flat_map(|s| s.alerts.iter().map(|a| a.id.as_str())));
+
+        summary.active_vehicles = vehicles.len();
+        summary.active_alerts = alerts.len();
+        summary.stops_without_rt = Self::stops_without_real_time(network).len();
+        summary.service_reliability_index = Self::service_reliability_index(network);
+        summary.graph_diameter_stops = *network.graph_diameter.get_or_init(|| match &network.network_graph {
+            Some(graph) => Self::graph_diameter(graph),
+            None => Self::graph_diameter(&Self::compute_network_graph(&network.gtfs, Self::service_date(network.service_date))),
+        });
+        summary.accessible_stop_count = Self::stops_accessible(network).len();
+        summary.group_stop_count = Self::group_stops_by_name(network)
+            .values()
+            .filter(|stops| stops.len() > 1)
+            .count();
+        summary.most_delayed_lines = Self::get_most_delayed_lines(network, 5);
+        summary.top_hubs = Self::detect_high_frequency_stops(network, 0)
+            .into_iter()
+            .take(5)
+            .map(|(stop, per_hour)| (stop.stop_id.clone(), per_hour))
+            .collect();
+        if delay_count > 0 {
+            summary.avg_delay_seconds = delay_sum as f32 / delay_count as f32;
+        }
+        summary
     }
 
-    pub fn get_stop_by_name<'a>(name: &str, network: &'a NetworkData) -> Option<&'a Stop> {
-        network.stops.iter().find(|s| s.stop_name.eq_ignore_ascii_case(name))
+    /// Service Reliability Index: percentage of the trips reporting a delay that are on time
+    /// (within `ON_TIME_TOLERANCE_SECS`) or early by less than two minutes. 0 without delay data.
+    /// Entries without a trip_id can't be told apart and are skipped.
+    pub fn service_reliability_index(network: &NetworkData) -> f32 {
+        const EARLY_TOLERANCE_SECS: i32 = 120;
+
+        let mut seen: HashSet<&str> = HashSet::new();
+        let (mut reliable, mut total) = (0usize, 0usize);
+        for rt in network.lines.iter().flat_map(|line| &line.real_time) {
+            let Some(delay) = rt.delay else { continue };
+            if rt.trip_id == Self::UNKNOWN_TRIP_ID || !seen.insert(rt.trip_id.as_str()) {
+                continue;
+            }
+
+            total += 1;
+            if (-EARLY_TOLERANCE_SECS + 1..=Self::ON_TIME_TOLERANCE_SECS).contains(&delay) {
+                reliable += 1;
+            }
+        }
+
+        if total == 0 {
+            return 0.0;
+        }
+        100.0 * reliable as f32 / total as f32
     }
 
-    pub fn get_line_by_name<'a>(name: &str, network: &'a NetworkData) -> Option<&'a Line> {
-        network.lines.iter().find(|l| l.line_name.eq_ignore_ascii_case(name))
+    /// Live delay of every trip of every route, as route_id -> delays. A trip appears at each
+    /// upcoming stop; its delay is taken from the latest of those arrivals, the one that
+    /// accounts for all the delay built up along the way. Entries without a trip_id are skipped.
+    fn trip_delays_by_route(network: &NetworkData) -> HashMap<&str, Vec<i32>> {
+        // (route_id, trip_id) -> (arrival time, delay)
+        let mut latest: HashMap<(&str, &str), (Option<i64>, i32)> = HashMap::new();
+        for rt in network.stops.iter().flat_map(|stop| &stop.real_time) {
+            let (Some(route_id), Some(delay)) = (rt.route_id.as_deref(), rt.delay) else { continue };
+            if rt.trip_id == Self::UNKNOWN_TRIP_ID {
+                continue;
+            }
+            latest
+                .entry((route_id, rt.trip_id.as_str()))
+                .and_modify(|entry| if rt.timestamp >= entry.0 { *entry = (rt.timestamp, delay) })
+                .or_insert((rt.timestamp, delay));
+        }
+
+        let mut by_route: HashMap<&str, Vec<i32>> = HashMap::new();
+        for ((route_id, _), (_, delay)) in latest {
+            by_route.entry(route_id).or_default().push(delay);
+        }
+        by_route
     }
 
-    pub fn get_line_by_route_id<'a>(route_id: &str, network: &'a NetworkData) -> Option<&'a Line> {
-        network
-            .lines
+    /// Delays of the trips of a route currently reporting one, each trip counted once
+    pub fn compute_delay_statistics(route_id: &str, network: &NetworkData) -> DelayStats {
+        let mut delays = Self::trip_delays_by_route(network).remove(route_id).unwrap_or_default();
+        if delays.is_empty() {
+            return DelayStats::default();
+        }
+        delays.sort_unstable();
+
+        // Nearest-rank percentile
+        let percentile = |p: f32| delays[((p * delays.len() as f32).ceil() as usize).clamp(1, delays.len()) - 1] as f32;
+        DelayStats {
+            mean_delay_s: delays.iter().map(|&d| d as f32).sum::<f32>() / delays.len() as f32,
+            median_delay_s: percentile(0.5),
+            p90_delay_s: percentile(0.9),
+            delayed_count: delays.iter().filter(|&&d| d > 60).count(),
+            early_count: delays.iter().filter(|&&d| d < -60).count(),
+            on_time_count: delays.iter().filter(|&&d| (-60..=60).contains(&d)).count(),
+        }
+    }
+
+    /// The `top_n` lines with the highest mean live delay, as (line_code, mean delay in seconds)
+    pub fn get_most_delayed_lines(network: &NetworkData, top_n: usize) -> Vec<(String, f32)> {
+        let delays = Self::trip_delays_by_route(network);
+        let mut lines: Vec<(String, f32)> = network.lines
             .iter()
-            .find(|l| Self::extract_line_id(&l.line_ref) == Some(route_id))
+            .filter_map(|line| {
+                let route_id = Self::extract_line_id(&line.line_ref).unwrap_or(&line.line_ref);
+                let trips = delays.get(route_id).filter(|trips| !trips.is_empty())?;
+                let mean = trips.iter().map(|&d| d as f32).sum::<f32>() / trips.len() as f32;
+                Some((line.line_code.clone(), mean))
+            })
+            .collect();
+
+        lines.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        lines.truncate(top_n);
+        lines
     }
 
-    pub fn get_stops_for_line<'a>(line_ref: &str, network: &'a NetworkData) -> Vec<&'a Stop> {
-        network
-            .stops
+    /// Stops with no vehicle position or trip update; arrivals filled in from the
+    /// static timetable don't count as real-time data
+    pub fn stops_without_real_time(network: &NetworkData) -> Vec<&Stop> {
+        network.stops
             .iter()
-            .filter(|s| s.lines.iter().any(|l| l == line_ref))
+            .filter(|stop| stop.real_time.iter().all(|rt| rt.vehicle_id == "static"))
             .collect()
     }
 
-    pub fn get_next_vehicles_for_stop<'a>(
-        stop_id: &str,
-        network: &'a NetworkData,
-    ) -> Vec<&'a RealTimeInfo> {
-        network
-            .stops
+    /// Stops with more than `threshold_per_hour` vehicles per hour across all their lines,
+    /// busiest first. The rate is extrapolated from the spacing of the arrivals currently
+    /// known at the stop, over at least `HUB_MIN_WINDOW_SECS`.
+    pub fn detect_high_frequency_stops(network: &NetworkData, threshold_per_hour: u32) -> Vec<(&Stop, u32)> {
+        let mut hubs: Vec<(&Stop, u32)> = network.stops
             .iter()
-            .find(|s| s.stop_id == stop_id)
-            .map(|stop| {
-                let mut vehicles: Vec<&RealTimeInfo> = stop.real_time.iter().collect();
-                vehicles.sort_by_key(|rt| rt.timestamp.unwrap_or(i64::MAX));
-                vehicles
+            .filter_map(|stop| {
+                let timestamps = stop.real_time.iter().filter_map(|rt| rt.timestamp);
+                let (first, last) = timestamps.clone().fold(None, |range, ts| match range {
+                    None => Some((ts, ts)),
+                    Some((first, last)) => Some((ts.min(first), ts.max(last))),
+                })?;
+
+                let window = (last - first).max(Self::HUB_MIN_WINDOW_SECS);
+                let intervals = timestamps.count() as i64 - 1;
+                let per_hour = ((intervals * 3600) as f64 / window as f64).round() as u32;
+                (per_hour > threshold_per_hour).then_some((stop, per_hour))
             })
-            .unwrap_or_default()
+            .collect();
+
+        hubs.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.stop_name.cmp(&b.0.stop_name)));
+        hubs
     }
 
-    pub fn format_timestamp(timestamp: i64) -> String {
-        match Utc.timestamp_opt(timestamp, 0).single() {
-            Some(dt) => {
-                let paris_time = dt.with_timezone(&Paris);
-                paris_time.format("%H:%M:%S").to_string()
+    /// Routes with less than half of their scheduled vehicles in the vehicle positions feed,
+    /// which hints at a disruption even before an alert is published.
+    ///
+    /// Expected vehicles are the trips that left the first stop of each direction within one
+    /// trip duration (first departure at the first stop to the next one at the last stop) before
+    /// now. Only routes whose timetable covers more than `DISRUPTION_MIN_CONFIDENCE` of their stops
+    /// and expect at least `DISRUPTION_MIN_EXPECTED` vehicles are reported. Without any vehicle
+    /// position at all the feed itself is down, so nothing is reported.
+    pub fn detect_service_disruption(cache: &CachedNetworkData) -> Vec<DisruptionReport> {
+        if cache.real_time.is_empty() {
+            return Vec::new();
+        }
+
+        let date = Self::service_date(cache.service_date);
+        let Some(midnight) = date.and_hms_opt(0, 0, 0).and_then(|dt| Paris.from_local_datetime(&dt).earliest()) else {
+            return Vec::new();
+        };
+        let now_secs = (Utc::now().timestamp() - midnight.timestamp()).max(0) as u32;
+
+        let mut observed: HashMap<&str, HashSet<&str>> = HashMap::new();
+        for rt in cache.real_time.iter().filter(|rt| !rt.cancelled) {
+            if let Some(route_id) = rt.route_id.as_deref() {
+                observed.entry(route_id).or_default().insert(rt.trip_id.as_str());
             }
-            None => "??:??:??".to_string(),
         }
-    }
 
-    pub fn format_timestamp_full(timestamp: i64) -> String {
-        match Utc.timestamp_opt(timestamp, 0).single() {
-            Some(dt) => {
-                let paris_time = dt.with_timezone(&Paris);
-                paris_time.format("%Y-%m-%d %H:%M:%S").to_string()
+        let gtfs = &cache.gtfs;
+        let mut reports = Vec::new();
+        for (route_id, by_direction) in &gtfs.stop_sequences {
+            let mut expected_vehicles = 0;
+            let (mut scheduled_stops, mut total_stops) = (0, 0);
+
+            for (&direction_id, sequence) in by_direction {
+                let (Some(first), Some(last)) = (sequence.first(), sequence.last()) else {
+                    continue;
+                };
+                let departures = |stop_id: &str| -> Vec<u32> {
+                    let mut times: Vec<u32> = Self::get_static_schedule(stop_id, date, gtfs)
+                        .into_iter()
+                        .filter(|(rid, dir, _)| rid == route_id && *dir == direction_id)
+                        .map(|(_, _, secs)| secs)
+                        .collect();
+                    times.sort_unstable();
+                    times
+                };
+                let first_times = departures(first);
+                let last_times = departures(last);
+
+                let trip_secs = first_times.first()
+                    .and_then(|&start| last_times.iter().find(|&&end| end > start).map(|&end| end - start))
+                    .unwrap_or(3600)
+                    .clamp(300, 3 * 3600);
+                expected_vehicles += first_times.iter()
+                    .filter(|&&secs| secs <= now_secs && secs + trip_secs > now_secs)
+                    .count();
+
+                total_stops += sequence.len();
+                scheduled_stops += sequence.iter()
+                    .filter(|stop_id| gtfs.scheduled_departures.get(stop_id.as_str()).is_some_and(|deps| {
+                        deps.iter().any(|(rid, dir, _, _, _)| rid == route_id && *dir == direction_id)
+                    }))
+                    .count();
+            }
+
+            let observed_vehicles = observed.get(route_id.as_str()).map_or(0, HashSet::len);
+            let confidence = if total_stops == 0 { 0.0 } else { scheduled_stops as f32 / total_stops as f32 };
+            if expected_vehicles >= Self::DISRUPTION_MIN_EXPECTED
+                && (observed_vehicles as f32) < expected_vehicles as f32 * 0.5
+                && confidence > Self::DISRUPTION_MIN_CONFIDENCE
+            {
+                reports.push(DisruptionReport {
+                    route_id: route_id.clone(),
+                    expected_vehicles,
+                    observed_vehicles,
+                    confidence,
+                });
             }
-            None => format!("Invalid timestamp: {}", timestamp),
         }
+
+        reports.sort_by(|a, b| a.route_id.cmp(&b.route_id));
+        reports
     }
 
-    pub fn get_current_timestamp() -> i64 {
-        Utc::now().timestamp()
+    /// A disruption report as a warning alert on its route, for clients of the alert list
+    pub fn disruption_alert(report: &DisruptionReport, cache: &GTFSCache) -> AlertInfo {
+        let line = cache.route_short_names.get(&report.route_id).unwrap_or(&report.route_id);
+        AlertInfo {
+            id: format!("disruption:{}", report.route_id),
+            text: format!("Possible disruption on line {}", line),
+            description: format!(
+                "Only {} of the {} scheduled vehicles are running. Detected from real-time data, not an official alert.",
+                report.observed_vehicles, report.expected_vehicles
+            ),
+            url: None,
+            route_ids: vec![report.route_id.clone()],
+            stop_ids: Vec::new(),
+            active_period_start: None,
+            active_period_end: None,
+            severity: 3,
+        }
+    }
+
+    /// Stops with step-free boarding according to GTFS wheelchair_boarding
+    pub fn stops_accessible(network: &NetworkData) -> Vec<&Stop> {
+        network.stops
+            .iter()
+            .filter(|stop| stop.wheelchair_accessible)
+            .collect()
     }
 
     pub fn get_cache_stats(cache: &CachedNetworkData) -> String {
@@ -1118,21 +5808,58 @@ impl NVTModels {
 
         let static_age = now.saturating_sub(cache.last_static_update);
         let dynamic_age = now.saturating_sub(cache.last_dynamic_update);
+        let network = cache.to_network_data();
+        let hubs: Vec<String> = Self::detect_high_frequency_stops(&network, 0)
+            .into_iter()
+            .take(5)
+            .map(|(stop, per_hour)| format!("{} ({}/h)", stop.stop_name, per_hour))
+            .collect();
+        let disruptions: String = Self::detect_service_disruption(cache)
+            .iter()
+            .map(|report| format!(
+                "🚨 Possible disruption on line {}: {} of {} scheduled vehicles running\n",
+                cache.gtfs.route_short_names.get(&report.route_id).unwrap_or(&report.route_id),
+                report.observed_vehicles,
+                report.expected_vehicles,
+            ))
+            .collect();
 
         format!(
-            "📊 Cache Statistics:\n\
+            "{}📊 Cache Statistics:\n\
+             🎯 Service Reliability: {:.1}%\n\
+             🚏 Busiest stops: {}\n\
              • Stops: {} | Lines: {} | Colors: {}\n\
              • Vehicles tracked: {} | Alerts (Active or Future): {}\n\
+             • Stops without real-time data: {} | Cancelled trips: {}\n\
+             • Alerts: {} raw received | {} deduplicated\n\
+             • SIRI-Lite revalidation: {} not modified | {} downloaded | {} static refreshes skipped\n\
              • Static data age: {}s | Dynamic data age: {}s\n\
-             • Last update: {}",
+             • Last update: {}\n\
+             • Config: {}",
+            disruptions,
+            Self::service_reliability_index(&network),
+            if hubs.is_empty() { "none".to_string() } else { hubs.join(" | ") },
             cache.stops_metadata.len(),
             cache.lines_metadata.len(),
             cache.line_colors.len(),
             cache.real_time.len(),
             cache.alerts.len(),
+            Self::stops_without_real_time(&network).len(),
+            Self::get_cancelled_trips(cache).len(),
+            Self::raw_alerts_received(),
+            cache.alerts.len(),
+            cache.etag_hit_count,
+            cache.etag_miss_count,
+            cache.static_refresh_skipped_count,
             static_age,
             dynamic_age,
-            Self::format_timestamp_full(cache.last_dynamic_update as i64)
+            Self::format_timestamp_full(cache.last_dynamic_update as i64),
+            cache.config.source.as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| "built-in defaults".to_string())
         )
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,163 @@
+// MQTT publishing (--mqtt) of the arrivals at pinned stops, for home automation
+// tools such as Node-RED or Home Assistant. Messages are sent with QoS 0: a missed
+// update is simply replaced by the next refresh.
+
+use crate::nvt_models::{NVTError, RealTimeInfo, Result};
+use rumqttc::{AsyncClient, MqttOptions, QoS};
+use std::time::Duration;
+
+#[derive(Debug)]
+pub struct NVTMqttPublisher {
+    client: AsyncClient,
+    topic_prefix: String,
+    /// Stops whose arrivals are published after each refresh
+    stop_ids: Vec<String>,
+}
+
+impl NVTMqttPublisher {
+    pub const DEFAULT_TOPIC_PREFIX: &'static str = "tbm_nvt";
+    const DEFAULT_PORT: u16 = 1883;
+    /// Publishes queued while the broker is unreachable before new ones are dropped
+    const QUEUE_CAPACITY: usize = 64;
+    const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+    /// Connect to `broker` (`host`, `host:port` or `mqtt://host:port`) in the background.
+    /// Must be called from within the tokio runtime, which drives the connection.
+    pub fn new(broker: &str, topic_prefix: &str) -> Result<Self> {
+        let address = broker.strip_prefix("mqtt://").unwrap_or(broker).trim_end_matches('/');
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port.parse::<u16>()
+                    .map_err(|e| NVTError::parse(format!("Invalid MQTT broker port in {:?}", broker)).with_source(e))?;
+                (host, port)
+            }
+            None => (address, Self::DEFAULT_PORT),
+        };
+        if host.is_empty() {
+            return Err(NVTError::parse(format!("Invalid MQTT broker address {:?}", broker)));
+        }
+
+        let mut options = MqttOptions::new(format!("tbm_nvt-{}", std::process::id()), host, port);
+        options.set_keep_alive(Duration::from_secs(30));
+        let (client, mut event_loop) = AsyncClient::new(options, Self::QUEUE_CAPACITY);
+
+        let broker = broker.to_string();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = event_loop.poll().await {
+                    log::warn!("MQTT connection to {} failed: {}", broker, e);
+                    tokio::time::sleep(Self::RECONNECT_DELAY).await;
+                }
+            }
+        });
+
+        Ok(NVTMqttPublisher {
+            client,
+            topic_prefix: topic_prefix.trim_end_matches('/').to_string(),
+            stop_ids: Vec::new(),
+        })
+    }
+
+    /// Publish the arrivals of these stops after each refresh
+    pub fn with_stops(mut self, stop_ids: Vec<String>) -> Self {
+        self.stop_ids = stop_ids;
+        self
+    }
+
+    pub fn stop_ids(&self) -> &[String] {
+        &self.stop_ids
+    }
+
+    /// Queue the arrivals at a stop as a JSON array on `{topic_prefix}/arrivals/{stop_id}`
+    pub fn publish_arrivals(&self, stop_id: &str, arrivals: &[&RealTimeInfo]) -> Result<()> {
+        let payload = serde_json::to_vec(arrivals)
+            .map_err(|e| NVTError::parse("Failed to serialize arrivals").with_source(e))?;
+        let topic = format!("{}/arrivals/{}", self.topic_prefix, stop_id);
+
+        self.client
+            .try_publish(topic, QoS::AtMostOnce, false, payload)
+            .map_err(|e| NVTError::network("MQTT publish failed").with_source(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost::bytes::BytesMut;
+    use rumqttc::mqttbytes::v4::{self, ConnAck, ConnectReturnCode, Packet, Publish};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Next MQTT packet from a client connection
+    async fn next_packet(stream: &mut TcpStream, buffer: &mut BytesMut) -> Packet {
+        loop {
+            if let Ok(packet) = v4::read(buffer, 64 * 1024) {
+                return packet;
+            }
+            assert!(stream.read_buf(buffer).await.unwrap() > 0, "client disconnected");
+        }
+    }
+
+    /// One-connection broker on a free local port: accepts the client and returns the
+    /// first message it publishes
+    async fn mock_broker() -> (String, tokio::task::JoinHandle<Publish>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let broker = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = BytesMut::new();
+            assert!(matches!(next_packet(&mut stream, &mut buffer).await, Packet::Connect(_)));
+
+            let mut connack = BytesMut::new();
+            ConnAck::new(ConnectReturnCode::Success, false).write(&mut connack).unwrap();
+            stream.write_all(&connack).await.unwrap();
+
+            loop {
+                if let Packet::Publish(publish) = next_packet(&mut stream, &mut buffer).await {
+                    return publish;
+                }
+            }
+        });
+        (address, broker)
+    }
+
+    fn arrival() -> RealTimeInfo {
+        RealTimeInfo {
+            vehicle_id: "tram-2201".to_string(),
+            trip_id: "C-0800".to_string(),
+            route_id: Some("C".to_string()),
+            direction_id: Some(0),
+            destination: Some("Gare Saint-Jean".to_string()),
+            latitude: 44.84,
+            longitude: -0.57,
+            stop_id: Some("quinc".to_string()),
+            timestamp: Some(1_900_000_000),
+            delay: Some(60),
+            occupancy: None,
+            cancelled: false,
+            speed_kmh: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn arrivals_are_published_as_json_under_the_topic_prefix() {
+        let (address, broker) = mock_broker().await;
+        let publisher = NVTMqttPublisher::new(&format!("mqtt://{}", address), "home/tbm/").unwrap();
+
+        let arrival = arrival();
+        publisher.publish_arrivals("quinc", &[&arrival]).unwrap();
+
+        let publish = tokio::time::timeout(Duration::from_secs(5), broker).await.expect("message published").unwrap();
+        assert_eq!(publish.topic, "home/tbm/arrivals/quinc");
+        assert_eq!(publish.qos, QoS::AtMostOnce);
+        let payload: serde_json::Value = serde_json::from_slice(&publish.payload).unwrap();
+        assert_eq!(payload[0]["vehicle_id"], "tram-2201");
+        assert_eq!(payload[0]["delay"], 60);
+    }
+
+    #[test]
+    fn invalid_broker_addresses_are_rejected() {
+        assert!(NVTMqttPublisher::new("mqtt://broker.local:99999", "tbm_nvt").is_err());
+        assert!(NVTMqttPublisher::new(":1883", "tbm_nvt").is_err());
+    }
+}
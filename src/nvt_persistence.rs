@@ -0,0 +1,183 @@
+// SQLite persistence for SIRI-derived network metadata (stops and lines)
+// GTFS static data keeps its own JSON cache; this database lets restarts skip
+// the stops/lines discovery requests while the data is still fresh.
+
+use crate::nvt_models::{CachedNetworkData, LineMetadata, NVTError, Result, StopMetadata};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::fs;
+use std::path::PathBuf;
+
+pub struct NVTPersistence;
+
+impl NVTPersistence {
+    const SCHEMA: &'static str = "
+        CREATE TABLE IF NOT EXISTS stops_meta (
+            position INTEGER PRIMARY KEY,
+            stop_id TEXT NOT NULL,
+            stop_name TEXT NOT NULL,
+            latitude REAL NOT NULL,
+            longitude REAL NOT NULL,
+            line_refs TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS lines_meta (
+            position INTEGER PRIMARY KEY,
+            line_ref TEXT NOT NULL,
+            line_name TEXT NOT NULL,
+            line_code TEXT NOT NULL,
+            destinations TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS cache_meta (
+            key TEXT PRIMARY KEY,
+            value INTEGER NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS etags (
+            endpoint TEXT PRIMARY KEY,
+            etag TEXT NOT NULL
+        );
+    ";
+
+    pub fn db_path() -> PathBuf {
+        let mut path = dirs::cache_dir().unwrap_or_else(|| PathBuf::from("."));
+        path.push("tbm_nvt");
+        fs::create_dir_all(&path).ok();
+        path.push("nvt_cache.sqlite");
+        path
+    }
+
+    /// Open (or create) the cache database and make sure the schema exists
+    pub fn open() -> Result<Connection> {
+        let path = Self::db_path();
+        let conn = Connection::open(&path)
+            .map_err(|e| NVTError::file(format!("Failed to open cache database {:?}", path)).with_source(e))?;
+
+        conn.execute_batch(Self::SCHEMA)
+            .map_err(|e| NVTError::file("Failed to create cache database schema").with_source(e))?;
+
+        Ok(conn)
+    }
+
+    /// Replace the stored stops/lines metadata, and the ETags they were served with,
+    /// with the cache's current contents
+    pub fn save_cache(conn: &Connection, cache: &CachedNetworkData) -> Result<()> {
+        let tx = conn.unchecked_transaction().map_err(Self::db_error)?;
+
+        tx.execute("DELETE FROM stops_meta", []).map_err(Self::db_error)?;
+        tx.execute("DELETE FROM lines_meta", []).map_err(Self::db_error)?;
+        tx.execute("DELETE FROM etags", []).map_err(Self::db_error)?;
+
+        {
+            let mut insert_stop = tx.prepare(
+                "INSERT INTO stops_meta (position, stop_id, stop_name, latitude, longitude, line_refs)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)"
+            ).map_err(Self::db_error)?;
+
+            for (position, (stop_id, stop_name, latitude, longitude, line_refs)) in cache.stops_metadata.iter().enumerate() {
+                let line_refs = serde_json::to_string(line_refs)
+                    .map_err(|e| NVTError::parse("Failed to serialize stop lines").with_source(e))?;
+                insert_stop.execute(params![position as i64, stop_id, stop_name, latitude, longitude, line_refs])
+                    .map_err(Self::db_error)?;
+            }
+
+            let mut insert_line = tx.prepare(
+                "INSERT INTO lines_meta (position, line_ref, line_name, line_code, destinations)
+                 VALUES (?1, ?2, ?3, ?4, ?5)"
+            ).map_err(Self::db_error)?;
+
+            for (position, (line_ref, line_name, line_code, destinations)) in cache.lines_metadata.iter().enumerate() {
+                let destinations = serde_json::to_string(destinations)
+                    .map_err(|e| NVTError::parse("Failed to serialize line destinations").with_source(e))?;
+                insert_line.execute(params![position as i64, line_ref, line_name, line_code, destinations])
+                    .map_err(Self::db_error)?;
+            }
+
+            let mut set_meta = tx.prepare(
+                "INSERT OR REPLACE INTO cache_meta (key, value) VALUES (?1, ?2)"
+            ).map_err(Self::db_error)?;
+
+            set_meta.execute(params!["last_static_update", cache.last_static_update as i64]).map_err(Self::db_error)?;
+            set_meta.execute(params!["stop_count", cache.stops_metadata.len() as i64]).map_err(Self::db_error)?;
+            set_meta.execute(params!["line_count", cache.lines_metadata.len() as i64]).map_err(Self::db_error)?;
+
+            let mut insert_etag = tx.prepare(
+                "INSERT INTO etags (endpoint, etag) VALUES (?1, ?2)"
+            ).map_err(Self::db_error)?;
+
+            for (endpoint, etag) in [("stops", &cache.stops_etag), ("lines", &cache.lines_etag)] {
+                if let Some(etag) = etag {
+                    insert_etag.execute(params![endpoint, etag]).map_err(Self::db_error)?;
+                }
+            }
+        }
+
+        tx.commit().map_err(Self::db_error)
+    }
+
+    /// Load stored stops/lines metadata and their ETags, or None if the database holds no
+    /// usable data. Only the static fields are filled; dynamic data must be fetched separately.
+    pub fn load_cache(conn: &Connection) -> Result<Option<CachedNetworkData>> {
+        let last_static_update: Option<i64> = conn
+            .query_row("SELECT value FROM cache_meta WHERE key = 'last_static_update'", [], |row| row.get(0))
+            .optional()
+            .map_err(Self::db_error)?;
+
+        let Some(last_static_update) = last_static_update else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT stop_id, stop_name, latitude, longitude, line_refs FROM stops_meta ORDER BY position"
+        ).map_err(Self::db_error)?;
+        let stops: Vec<StopMetadata> = stmt
+            .query_map([], |row| {
+                let line_refs: String = row.get(4)?;
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    serde_json::from_str(&line_refs).unwrap_or_default(),
+                ))
+            })
+            .map_err(Self::db_error)?
+            .collect::<std::result::Result<_, _>>()
+            .map_err(Self::db_error)?;
+
+        let mut stmt = conn.prepare(
+            "SELECT line_ref, line_name, line_code, destinations FROM lines_meta ORDER BY position"
+        ).map_err(Self::db_error)?;
+        let lines: Vec<LineMetadata> = stmt
+            .query_map([], |row| {
+                let destinations: String = row.get(3)?;
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    serde_json::from_str(&destinations).unwrap_or_default(),
+                ))
+            })
+            .map_err(Self::db_error)?
+            .collect::<std::result::Result<_, _>>()
+            .map_err(Self::db_error)?;
+
+        if stops.is_empty() || lines.is_empty() {
+            return Ok(None);
+        }
+
+        let mut stmt = conn.prepare("SELECT etag FROM etags WHERE endpoint = ?1").map_err(Self::db_error)?;
+        let mut etag = |endpoint: &str| -> Result<Option<String>> {
+            stmt.query_row([endpoint], |row| row.get(0)).optional().map_err(Self::db_error)
+        };
+
+        let mut cache = CachedNetworkData::new();
+        cache.stops_etag = etag("stops")?;
+        cache.lines_etag = etag("lines")?;
+        cache.stops_metadata = stops;
+        cache.lines_metadata = lines;
+        cache.last_static_update = last_static_update.max(0) as u64;
+        Ok(Some(cache))
+    }
+
+    fn db_error(e: rusqlite::Error) -> NVTError {
+        NVTError::file("Cache database error").with_source(e)
+    }
+}
@@ -0,0 +1,515 @@
+// REST API server mode (--serve) exposing live TBM data as JSON
+// The cache is refreshed in the background; handlers only read the latest snapshot.
+
+use crate::nvt_config::NVTConfig;
+use crate::nvt_models::{CachedNetworkData, NVTError, NVTModels, NetworkData, Result, FETCH_DURATION, status};
+use axum::extract::ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{FromRef, Path, State};
+use axum::http::{header, Method, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use lazy_static::lazy_static;
+use prometheus::{Encoder, IntGauge, TextEncoder};
+use serde_json::json;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, RwLock};
+use tower_http::cors::{Any, CorsLayer};
+
+lazy_static! {
+    static ref VEHICLES_TOTAL: IntGauge = prometheus::register_int_gauge!(
+        "nvt_vehicles_total", "Vehicles in the latest real-time snapshot"
+    ).expect("Failed to register vehicles gauge");
+    static ref ALERTS_TOTAL: IntGauge = prometheus::register_int_gauge!(
+        "nvt_alerts_total", "Alerts in the latest snapshot"
+    ).expect("Failed to register alerts gauge");
+    static ref CACHE_STATIC_AGE: IntGauge = prometheus::register_int_gauge!(
+        "nvt_cache_static_age_seconds", "Age of the stops and lines data"
+    ).expect("Failed to register static age gauge");
+    static ref CACHE_DYNAMIC_AGE: IntGauge = prometheus::register_int_gauge!(
+        "nvt_cache_dynamic_age_seconds", "Age of the real-time data"
+    ).expect("Failed to register dynamic age gauge");
+}
+
+/// Latest cache and the network view built from it
+struct ServerData {
+    cache: CachedNetworkData,
+    network: NetworkData,
+}
+
+type SharedData = Arc<RwLock<ServerData>>;
+
+/// Router state: the data snapshot plus a channel announcing each refresh
+/// (carrying the new `last_dynamic_update`) to WebSocket subscribers
+#[derive(Clone)]
+struct ServerState {
+    data: SharedData,
+    updates: broadcast::Sender<u64>,
+}
+
+impl FromRef<ServerState> for SharedData {
+    fn from_ref(state: &ServerState) -> Self {
+        Arc::clone(&state.data)
+    }
+}
+
+pub struct NVTServer;
+
+impl NVTServer {
+    pub const DEFAULT_ADDR: &'static str = "127.0.0.1:8080";
+    /// WebSocket close code sent when the requested stop doesn't exist
+    const CLOSE_STOP_NOT_FOUND: u16 = 4004;
+
+    /// Load the network data, then serve it on `addr` until the process is stopped.
+    /// With a `metrics_port` different from `addr`'s, `/metrics` is served on that port instead.
+    pub async fn run_server(addr: SocketAddr, metrics_port: Option<u16>, config: NVTConfig) -> Result<()> {
+        let cache = NVTModels::initialize_cache(&config).await?;
+        let network = cache.to_network_data();
+        let state = ServerState {
+            data: Arc::new(RwLock::new(ServerData { cache, network })),
+            updates: broadcast::channel(16).0,
+        };
+
+        tokio::spawn(Self::refresh_loop(state.clone(), config));
+
+        let separate_metrics_port = metrics_port.filter(|port| *port != addr.port());
+        if let Some(port) = separate_metrics_port {
+            let metrics_addr = SocketAddr::new(addr.ip(), port);
+            let metrics_router = Router::new()
+                .route("/metrics", get(Self::get_metrics))
+                .with_state(state.clone());
+            let listener = Self::bind(metrics_addr).await?;
+            status!("📈 Serving metrics on http://{}/metrics", metrics_addr);
+            tokio::spawn(async move {
+                if let Err(e) = axum::serve(listener, metrics_router).await {
+                    eprintln!("⚠️  Metrics server error: {}", e);
+                }
+            });
+        }
+
+        let listener = Self::bind(addr).await?;
+        status!("🌐 Serving TBM data on http://{}", addr);
+        axum::serve(listener, Self::router(state, separate_metrics_port.is_none()))
+            .await
+            .map_err(|e| NVTError::network("Server error").with_source(e))
+    }
+
+    async fn bind(addr: SocketAddr) -> Result<tokio::net::TcpListener> {
+        tokio::net::TcpListener::bind(addr)
+            .await
+            .map_err(|e| NVTError::network(format!("Failed to bind {}", addr)).with_source(e))
+    }
+
+    fn router(state: ServerState, with_metrics: bool) -> Router {
+        let cors = CorsLayer::new()
+            .allow_origin(Any)
+            .allow_methods([Method::GET]);
+
+        let mut router = Router::new();
+        if with_metrics {
+            router = router.route("/metrics", get(Self::get_metrics));
+        }
+
+        router
+            .route("/stops", get(Self::get_stops))
+            .route("/stops/{id}/arrivals", get(Self::get_stop_arrivals))
+            .route("/lines", get(Self::get_lines))
+            .route("/lines/{code}/stops", get(Self::get_line_stops))
+            .route("/alerts", get(Self::get_alerts))
+            .route("/summary", get(Self::get_summary))
+            .route("/health", get(Self::get_health))
+            .route("/ws/arrivals/{stop_id}", get(Self::ws_arrivals))
+            .layer(cors)
+            .with_state(state)
+    }
+
+    /// Refresh a copy of the cache so readers aren't blocked during network requests
+    async fn refresh_loop(state: ServerState, config: NVTConfig) {
+        let mut interval = tokio::time::interval(Duration::from_secs(config.dynamic_max_age_secs));
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            let mut cache = state.data.read().await.cache.clone();
+            if let Err(e) = NVTModels::smart_refresh(&mut cache, &config).await {
+                eprintln!("⚠️  Refresh failed: {}", e);
+                continue;
+            }
+
+            let network = cache.to_network_data();
+            let refreshed_at = cache.last_dynamic_update;
+            *state.data.write().await = ServerData { cache, network };
+            // No receivers simply means no WebSocket client is connected
+            let _ = state.updates.send(refreshed_at);
+        }
+    }
+
+    fn not_found(message: String) -> Response {
+        (StatusCode::NOT_FOUND, Json(json!({ "error": message }))).into_response()
+    }
+
+    async fn get_stops(State(data): State<SharedData>) -> Response {
+        Json(&data.read().await.network.stops).into_response()
+    }
+
+    async fn get_stop_arrivals(State(data): State<SharedData>, Path(id): Path<String>) -> Response {
+        let data = data.read().await;
+        let stop_id = NVTModels::normalize_stop_id(&id);
+
+        if !data.network.stops.iter().any(|s| s.stop_id == stop_id) {
+            return Self::not_found(format!("Unknown stop: {}", id));
+        }
+        Json(NVTModels::get_next_vehicles_for_stop(&stop_id, &data.network)).into_response()
+    }
+
+    /// Arrivals at a stop serialized like `GET /stops/{id}/arrivals`, None for unknown stops
+    async fn arrivals_json(data: &SharedData, stop_id: &str) -> Option<String> {
+        let data = data.read().await;
+        if !data.network.stops.iter().any(|s| s.stop_id == stop_id) {
+            return None;
+        }
+        serde_json::to_string(&NVTModels::get_next_vehicles_for_stop(stop_id, &data.network)).ok()
+    }
+
+    async fn ws_arrivals(
+        ws: WebSocketUpgrade,
+        State(state): State<ServerState>,
+        Path(stop_id): Path<String>,
+    ) -> Response {
+        ws.on_upgrade(move |socket| Self::stream_arrivals(socket, state, stop_id))
+    }
+
+    /// Send the stop's arrivals on connect and after every refresh, one JSON document per line
+    async fn stream_arrivals(mut socket: WebSocket, state: ServerState, stop_id: String) {
+        let stop_id = NVTModels::normalize_stop_id(&stop_id);
+        let mut updates = state.updates.subscribe();
+
+        let Some(arrivals) = Self::arrivals_json(&state.data, &stop_id).await else {
+            let _ = socket.send(Message::Text("{\"error\":\"stop_not_found\"}\n".into())).await;
+            let _ = socket.send(Message::Close(Some(CloseFrame {
+                code: Self::CLOSE_STOP_NOT_FOUND,
+                reason: "stop_not_found".into(),
+            }))).await;
+            return;
+        };
+        if socket.send(Message::Text(format!("{}\n", arrivals).into())).await.is_err() {
+            return;
+        }
+
+        loop {
+            tokio::select! {
+                update = updates.recv() => {
+                    if let Err(broadcast::error::RecvError::Closed) = update {
+                        break;
+                    }
+                    let Some(arrivals) = Self::arrivals_json(&state.data, &stop_id).await else {
+                        break;
+                    };
+                    if socket.send(Message::Text(format!("{}\n", arrivals).into())).await.is_err() {
+                        break;
+                    }
+                }
+                incoming = socket.recv() => {
+                    if matches!(incoming, None | Some(Err(_)) | Some(Ok(Message::Close(_)))) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn get_lines(State(data): State<SharedData>) -> Response {
+        Json(&data.read().await.network.lines).into_response()
+    }
+
+    async fn get_line_stops(State(data): State<SharedData>, Path(code): Path<String>) -> Response {
+        let data = data.read().await;
+        let Some(line) = data.network.lines.iter().find(|l| l.line_code.eq_ignore_ascii_case(&code)) else {
+            return Self::not_found(format!("Unknown line: {}", code));
+        };
+
+        Json(NVTModels::get_stops_for_line(&line.line_ref, &data.network)).into_response()
+    }
+
+    /// Published alerts, followed by a warning for each route detected as disrupted
+    async fn get_alerts(State(data): State<SharedData>) -> Response {
+        let data = data.read().await;
+        let mut alerts = data.cache.alerts.clone();
+        alerts.extend(
+            NVTModels::detect_service_disruption(&data.cache)
+                .iter()
+                .map(|report| NVTModels::disruption_alert(report, &data.cache.gtfs)),
+        );
+        Json(alerts).into_response()
+    }
+
+    async fn get_summary(State(data): State<SharedData>) -> Response {
+        Json(NVTModels::network_summary(&data.read().await.network)).into_response()
+    }
+
+    /// Prometheus text exposition of the snapshot gauges and fetch histograms
+    async fn get_metrics(State(data): State<SharedData>) -> Response {
+        {
+            let data = data.read().await;
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+
+            VEHICLES_TOTAL.set(data.cache.real_time.len() as i64);
+            ALERTS_TOTAL.set(data.cache.alerts.len() as i64);
+            CACHE_STATIC_AGE.set(now.saturating_sub(data.cache.last_static_update) as i64);
+            CACHE_DYNAMIC_AGE.set(now.saturating_sub(data.cache.last_dynamic_update) as i64);
+        }
+        lazy_static::initialize(&FETCH_DURATION);
+
+        let mut body = Vec::new();
+        let encoder = TextEncoder::new();
+        if let Err(e) = encoder.encode(&prometheus::gather(), &mut body) {
+            return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+
+        ([(header::CONTENT_TYPE, encoder.format_type().to_string())], body).into_response()
+    }
+
+    async fn get_health(State(data): State<SharedData>) -> Response {
+        let data = data.read().await;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        Json(json!({
+            "status": "ok",
+            "static_age_secs": now.saturating_sub(data.cache.last_static_update),
+            "dynamic_age_secs": now.saturating_sub(data.cache.last_dynamic_update),
+            "vehicles": data.cache.real_time.len(),
+        }))
+        .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nvt_models::RealTimeInfo;
+    use tokio_tungstenite::tungstenite;
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+    }
+
+    /// Cache as loaded at startup: two tram C stops and one vehicle heading to Quinconces
+    fn mock_cache() -> CachedNetworkData {
+        let line_ref = "TBM:Line:C:LOC".to_string();
+        let mut cache = CachedNetworkData::new();
+        cache.stops_metadata = vec![
+            ("quinc".to_string(), "Quinconces".to_string(), 44.8447, -0.5737, vec![line_ref.clone()]),
+            ("stjean".to_string(), "Gare Saint-Jean".to_string(), 44.8260, -0.5566, vec![line_ref.clone()]),
+        ];
+        cache.lines_metadata = vec![(line_ref, "Tram C".to_string(), "C".to_string(), Vec::new())];
+        (cache.stop_to_lines, cache.line_to_stops) = NVTModels::build_line_indices(&cache.stops_metadata);
+        cache.real_time = vec![RealTimeInfo {
+            vehicle_id: "tram-2201".to_string(),
+            trip_id: "C-0800".to_string(),
+            route_id: Some("C".to_string()),
+            direction_id: Some(0),
+            destination: Some("Quinconces".to_string()),
+            latitude: 44.84,
+            longitude: -0.57,
+            stop_id: Some("quinc".to_string()),
+            timestamp: Some(now() as i64),
+            delay: None,
+            occupancy: None,
+            cancelled: false,
+            speed_kmh: None,
+        }];
+        cache.last_static_update = now();
+        cache.last_dynamic_update = now();
+        cache
+    }
+
+    fn mock_state() -> ServerState {
+        let cache = mock_cache();
+        let network = cache.to_network_data();
+        ServerState {
+            data: Arc::new(RwLock::new(ServerData { cache, network })),
+            updates: broadcast::channel(16).0,
+        }
+    }
+
+    /// Serve the full router on a free local port, returning its address
+    async fn spawn_server(state: ServerState) -> SocketAddr {
+        let listener = NVTServer::bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, NVTServer::router(state, true)).await.ok() });
+        addr
+    }
+
+    #[tokio::test]
+    async fn get_stops_returns_non_empty_array() {
+        let addr = spawn_server(mock_state()).await;
+
+        let response = reqwest::get(format!("http://{}/stops", addr)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let stops: serde_json::Value = response.json().await.unwrap();
+        let stops = stops.as_array().expect("JSON array");
+        assert_eq!(stops.len(), 2);
+        assert_eq!(stops[0]["stop_name"], "Quinconces");
+    }
+
+    #[tokio::test]
+    async fn health_answers_within_100_ms() {
+        let addr = spawn_server(mock_state()).await;
+        // Built up front: only the request itself is timed
+        let client = reqwest::Client::new();
+
+        let response = tokio::time::timeout(
+            Duration::from_millis(100),
+            client.get(format!("http://{}/health", addr)).send(),
+        )
+        .await
+        .expect("health answered within 100 ms")
+        .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let health: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(health["status"], "ok");
+        assert_eq!(health["vehicles"], 1);
+    }
+
+    #[tokio::test]
+    async fn unknown_stop_and_line_are_404() {
+        let addr = spawn_server(mock_state()).await;
+
+        for path in ["/stops/nowhere/arrivals", "/lines/Z/stops"] {
+            let response = reqwest::get(format!("http://{}{}", addr, path)).await.unwrap();
+            assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND, "{}", path);
+            let body: serde_json::Value = response.json().await.unwrap();
+            assert!(body["error"].is_string());
+        }
+    }
+
+    #[tokio::test]
+    async fn stop_arrivals_accept_raw_stop_ids() {
+        let addr = spawn_server(mock_state()).await;
+
+        let response = reqwest::get(format!("http://{}/stops/QUINC/arrivals", addr)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let arrivals: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(arrivals.as_array().map(Vec::len), Some(1));
+    }
+
+    /// Next text frame from the WebSocket, failing the test after `within`
+    async fn next_text<S>(socket: &mut S, within: Duration) -> String
+    where
+        S: futures::Stream<Item = std::result::Result<tungstenite::Message, tungstenite::Error>> + Unpin,
+    {
+        use futures::StreamExt;
+
+        loop {
+            let message = tokio::time::timeout(within, socket.next())
+                .await
+                .expect("message received in time")
+                .expect("socket still open")
+                .expect("valid frame");
+            if let tungstenite::Message::Text(text) = message {
+                return text.to_string();
+            }
+        }
+    }
+
+    /// Checks the schema of a WebSocket arrivals document
+    fn assert_arrivals_document(text: &str) {
+        assert!(text.ends_with('\n'), "one JSON document per line");
+        let arrivals: serde_json::Value = serde_json::from_str(text).expect("valid JSON");
+        let arrivals = arrivals.as_array().expect("JSON array");
+        assert!(!arrivals.is_empty());
+        for arrival in arrivals {
+            assert!(arrival["vehicle_id"].is_string());
+            assert!(arrival["trip_id"].is_string());
+            assert!(arrival["timestamp"].is_i64());
+        }
+    }
+
+    #[tokio::test]
+    async fn websocket_streams_arrivals_after_refresh() {
+        let state = mock_state();
+        let addr = spawn_server(state.clone()).await;
+        let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/ws/arrivals/quinc", addr))
+            .await
+            .expect("WebSocket connected");
+
+        assert_arrivals_document(&next_text(&mut socket, Duration::from_millis(500)).await);
+
+        // Mocked refresh: a new snapshot is announced to subscribers
+        state.updates.send(now()).expect("client subscribed");
+        assert_arrivals_document(&next_text(&mut socket, Duration::from_millis(500)).await);
+    }
+
+    #[tokio::test]
+    async fn websocket_closes_with_4004_for_unknown_stop() {
+        use futures::StreamExt;
+
+        let addr = spawn_server(mock_state()).await;
+        let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{}/ws/arrivals/nowhere", addr))
+            .await
+            .expect("WebSocket connected");
+
+        let error: serde_json::Value =
+            serde_json::from_str(&next_text(&mut socket, Duration::from_millis(500)).await).unwrap();
+        assert_eq!(error["error"], "stop_not_found");
+
+        let close = tokio::time::timeout(Duration::from_millis(500), socket.next()).await.unwrap();
+        let Some(Ok(tungstenite::Message::Close(Some(frame)))) = close else {
+            panic!("expected a close frame, got {:?}", close);
+        };
+        assert_eq!(u16::from(frame.code), NVTServer::CLOSE_STOP_NOT_FOUND);
+    }
+
+    /// Value of the `name` sample in a Prometheus text exposition
+    fn metric_value(body: &str, name: &str) -> Option<f64> {
+        body.lines()
+            .filter(|line| !line.starts_with('#'))
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(' ')?.trim().parse().ok())
+    }
+
+    #[tokio::test]
+    async fn metrics_report_vehicles_total_after_refresh() {
+        let state = mock_state();
+        let addr = spawn_server(state.clone()).await;
+
+        // Mocked refresh cycle: the new snapshot holds two vehicles
+        {
+            let mut data = state.data.write().await;
+            let mut second = data.cache.real_time[0].clone();
+            second.vehicle_id = "tram-2202".to_string();
+            second.trip_id = "C-0810".to_string();
+            data.cache.real_time.push(second);
+            data.network = data.cache.to_network_data();
+        }
+
+        let response = reqwest::get(format!("http://{}/metrics", addr)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let content_type = response.headers()[reqwest::header::CONTENT_TYPE].to_str().unwrap().to_string();
+        assert!(content_type.starts_with("text/plain"), "{}", content_type);
+
+        let body = response.text().await.unwrap();
+        assert_eq!(metric_value(&body, "nvt_vehicles_total"), Some(2.0), "{}", body);
+        assert!(metric_value(&body, "nvt_alerts_total").is_some());
+        assert!(metric_value(&body, "nvt_cache_dynamic_age_seconds").is_some());
+    }
+
+    #[tokio::test]
+    async fn metrics_can_be_left_out_of_the_main_router() {
+        let listener = NVTServer::bind("127.0.0.1:0".parse().unwrap()).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let router = NVTServer::router(mock_state(), false);
+        tokio::spawn(async move { axum::serve(listener, router).await.ok() });
+
+        let response = reqwest::get(format!("http://{}/metrics", addr)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,206 @@
+// Full-screen terminal interface (--tui) built on ratatui + crossterm
+// Terminal setup/teardown, key handling and periodic refresh live here;
+// the widgets themselves are drawn by NVTViews::render_tui.
+
+use crate::nvt_config::NVTConfig;
+use crate::nvt_models::{CachedNetworkData, NVTModels, NetworkData};
+use crate::nvt_views::{NVTViews, TuiItem, TuiState};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::backend::CrosstermBackend;
+use ratatui::Terminal;
+use std::io::{self, Stdout};
+use std::time::{Duration, Instant};
+use tokio::runtime::Handle;
+use tui_input::backend::crossterm::EventHandler;
+use tui_input::Input;
+
+type TuiTerminal = Terminal<CrosstermBackend<Stdout>>;
+
+enum TuiAction {
+    None,
+    Refresh,
+    Quit,
+}
+
+/// Restores the terminal when dropped, including on early return or panic
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        NVTModels::set_quiet(false);
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, crossterm::cursor::Show);
+    }
+}
+
+pub struct NVTTui;
+
+impl NVTTui {
+    const TICK_MS: u64 = 250;
+
+    /// Run the TUI until the user quits
+    pub fn run(cache: &mut CachedNetworkData, config: &NVTConfig) -> io::Result<()> {
+        enable_raw_mode()?;
+        let guard = TerminalGuard;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        // Progress messages would be drawn over the interface
+        NVTModels::set_quiet(true);
+
+        let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+        let result = Self::event_loop(&mut terminal, cache, config);
+
+        drop(guard);
+        result
+    }
+
+    fn event_loop(terminal: &mut TuiTerminal, cache: &mut CachedNetworkData, config: &NVTConfig) -> io::Result<()> {
+        let refresh_interval = Duration::from_secs(config.dynamic_max_age_secs);
+        let mut state = TuiState::default();
+        state.list.select(Some(0));
+        let mut network = cache.to_network_data();
+        let mut last_refresh = Instant::now();
+        let mut items = Vec::new();
+        // (search, line, direction) the items were listed for; None after a data refresh
+        let mut items_for = None;
+
+        loop {
+            let selection = (state.search_query.clone(), state.line_filter.clone(), state.direction);
+            if items_for.as_ref() != Some(&selection) {
+                items = Self::items(&state, &network);
+                items_for = Some(selection);
+            }
+            Self::clamp_selection(&mut state, items.len());
+            state.next_refresh_secs = refresh_interval.saturating_sub(last_refresh.elapsed()).as_secs();
+
+            terminal.draw(|frame| NVTViews::render_tui(frame, &mut state, &items, cache, &network))?;
+
+            let mut refresh = last_refresh.elapsed() >= refresh_interval;
+            if event::poll(Duration::from_millis(Self::TICK_MS))?
+                && let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                match Self::handle_key(key, &mut state, &items) {
+                    TuiAction::Quit => return Ok(()),
+                    TuiAction::Refresh => refresh = true,
+                    TuiAction::None => {}
+                }
+            }
+
+            if refresh {
+                state.message = Some("Refreshing...".to_string());
+                terminal.draw(|frame| NVTViews::render_tui(frame, &mut state, &items, cache, &network))?;
+
+                state.message = Some(match Handle::current().block_on(NVTModels::smart_refresh(cache, config)) {
+                    Ok(_) => "✓ Data refreshed".to_string(),
+                    Err(e) => format!("⚠️ Refresh failed: {}", e),
+                });
+                network = cache.to_network_data();
+                last_refresh = Instant::now();
+                items_for = None;
+            }
+        }
+    }
+
+    fn handle_key(key: KeyEvent, state: &mut TuiState, items: &[TuiItem]) -> TuiAction {
+        if let Some(input) = state.search.as_mut() {
+            match key.code {
+                KeyCode::Esc => state.search = None,
+                KeyCode::Enter => {
+                    let query = input.value().trim().to_string();
+                    state.search_query = (!query.is_empty()).then_some(query);
+                    state.search = None;
+                    state.list.select(Some(0));
+                }
+                _ => {
+                    input.handle_event(&Event::Key(key));
+                }
+            }
+            return TuiAction::None;
+        }
+
+        match key.code {
+            KeyCode::Char('q') => return TuiAction::Quit,
+            KeyCode::Char('r') => return TuiAction::Refresh,
+            KeyCode::Char('/') => state.search = Some(Input::default()),
+            KeyCode::Char('d') if state.line_filter.is_some() => {
+                state.direction = 1 - state.direction;
+                state.list.select(Some(0));
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let next = state.list.selected().map_or(0, |i| i + 1);
+                state.list.select(Some(next.min(items.len().saturating_sub(1))));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let previous = state.list.selected().map_or(0, |i| i.saturating_sub(1));
+                state.list.select(Some(previous));
+            }
+            KeyCode::Enter => match state.list.selected().and_then(|i| items.get(i)) {
+                Some(TuiItem::Line(line_ref)) => {
+                    state.line_filter = Some(line_ref.clone());
+                    state.direction = 0;
+                    state.list.select(Some(0));
+                }
+                Some(TuiItem::Stop(stop_id)) => state.selected_stop = Some(stop_id.clone()),
+                None => {}
+            },
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('h') => {
+                if state.search_query.is_some() {
+                    state.search_query = None;
+                } else {
+                    state.line_filter = None;
+                }
+                state.list.select(Some(0));
+            }
+            _ => {}
+        }
+
+        TuiAction::None
+    }
+
+    /// Entries of the left pane: search results, the stops of the chosen line in the
+    /// chosen direction, or all lines
+    fn items(state: &TuiState, network: &NetworkData) -> Vec<TuiItem> {
+        if let Some(query) = &state.search_query {
+            let query_lower = query.to_lowercase();
+            let matches: Vec<TuiItem> = network.stops.iter()
+                .filter(|s| s.stop_name.to_lowercase().contains(&query_lower))
+                .map(|s| TuiItem::Stop(s.stop_id.clone()))
+                .collect();
+            if !matches.is_empty() {
+                return matches;
+            }
+            return NVTModels::find_stops_fuzzy(query, network, 20)
+                .into_iter()
+                .map(|s| TuiItem::Stop(s.stop_id.clone()))
+                .collect();
+        }
+
+        if let Some(line_ref) = &state.line_filter {
+            let sequence = NVTModels::get_stop_sequence(line_ref, state.direction, &network.gtfs);
+            let ordered: Vec<TuiItem> = sequence.iter()
+                .filter(|id| network.stops.iter().any(|s| &s.stop_id == *id))
+                .map(|id| TuiItem::Stop(id.clone()))
+                .collect();
+            if !ordered.is_empty() {
+                return ordered;
+            }
+
+            let mut stops = NVTModels::get_stops_for_line(line_ref, network);
+            stops.sort_by(|a, b| a.stop_name.cmp(&b.stop_name));
+            return stops.into_iter().map(|s| TuiItem::Stop(s.stop_id.clone())).collect();
+        }
+
+        network.lines.iter().map(|l| TuiItem::Line(l.line_ref.clone())).collect()
+    }
+
+    fn clamp_selection(state: &mut TuiState, len: usize) {
+        match state.list.selected() {
+            _ if len == 0 => state.list.select(None),
+            Some(i) if i >= len => state.list.select(Some(len - 1)),
+            None => state.list.select(Some(0)),
+            _ => {}
+        }
+    }
+}
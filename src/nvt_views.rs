@@ -1,11 +1,85 @@
 // Views for TBM Next Vehicle application
-use crate::nvt_models::{Line, Stop, RealTimeInfo, NetworkData, NVTModels};
+use crate::nvt_config::NVTConfig;
+use crate::nvt_favorites::Favorites;
+use crate::nvt_models::{Line, Stop, RealTimeInfo, NetworkData, NVTModels, Journey, JourneyStop, LegMode, NetworkSummary, DelayStats, LineFrequency, ServiceGap, StopComparison, StopVisitStatus, CachedNetworkData};
 use crate::nvt_controllers::NVTControllers;
+use crate::NearQuery;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line as TextLine, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Row, Table};
+use ratatui::Frame;
+use std::collections::HashMap;
 use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tui_input::Input;
+
+/// Whether output may contain ANSI colors (off with --no-color or NO_COLOR)
+static USE_COLOR: AtomicBool = AtomicBool::new(true);
+/// Whether arrival times are shown relative to now (use_relative_time in the config)
+static USE_RELATIVE_TIME: AtomicBool = AtomicBool::new(true);
+
+/// Arrival log (--log) the historical load of each vehicle is computed from
+static HISTORY_LOG: OnceLock<PathBuf> = OnceLock::new();
 
 pub struct NVTViews;
 
+/// Entry of the --tui navigation list
+#[derive(Debug, Clone, PartialEq)]
+pub enum TuiItem {
+    Line(String),
+    Stop(String),
+}
+
+/// State of the --tui interface, updated by NVTTui and drawn by NVTViews
+#[derive(Default)]
+pub struct TuiState {
+    pub list: ListState,
+    /// Line whose stops are listed in the left pane
+    pub line_filter: Option<String>,
+    /// GTFS direction_id of `line_filter` whose stops are listed, toggled with 'd'
+    pub direction: u32,
+    pub selected_stop: Option<String>,
+    /// Search overlay being typed
+    pub search: Option<Input>,
+    /// Submitted search; the left pane lists matching stops
+    pub search_query: Option<String>,
+    pub next_refresh_secs: u64,
+    pub message: Option<String>,
+}
+
 impl NVTViews {
+    /// Enable or disable ANSI colors in all views
+    pub fn set_color(enabled: bool) {
+        USE_COLOR.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn use_color() -> bool {
+        USE_COLOR.load(Ordering::Relaxed)
+    }
+
+    /// Show arrival times as "in 5 min" (default) or as a clock time
+    pub fn set_relative_time(enabled: bool) {
+        USE_RELATIVE_TIME.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Show the historical load of each vehicle from this arrival log
+    pub fn set_history_log(path: PathBuf) {
+        let _ = HISTORY_LOG.set(path);
+    }
+
+    /// Wrap text in an ANSI SGR sequence, or leave it unchanged when colors are disabled
+    fn styled(text: String, sgr: &str) -> String {
+        if Self::use_color() {
+            format!("\x1b[{}m{}\x1b[0m", sgr, text)
+        } else {
+            text
+        }
+    }
+
     /// Show main menu with better formatting
     pub fn show_menu() {
         println!("\n{}", "═".repeat(60));
@@ -18,16 +92,53 @@ impl NVTViews {
         println!("  4️⃣  Browse all stops");
         println!("  5️⃣  Browse all lines");
         println!("  6️⃣  Show cache statistics 📊");
+        println!("  7️⃣  Show stops for selected line 🗺️");
+        println!("  8️⃣  My favorites ⭐");
+        println!("  9️⃣  Trip details for a shown vehicle 🧭");
+        println!("  🔟 Line frequency table 🔁");
+        println!("  h  Next arrivals at the busiest stop 🚏");
+        println!("  c  Compare two stops ⚖️");
         println!("  0️⃣  Quit application");
         println!("\n{}", "─".repeat(60));
         print!("➜ Your choice: ");
         let _ = io::stdout().flush();
     }
 
+    /// Examples printed above the stop prompt (before raw mode, which needs \r\n line ends)
+    pub fn show_stop_prompt_hint() {
+        println!("\n📍 Enter stop name");
+        println!("   Examples: 'Quinconces', 'Victoire', 'Gare Saint-Jean'");
+        println!("   Or 'A→B' / 'A,B' for the stops where two lines meet");
+    }
+
+    /// Redraw the autocomplete stop prompt line, leaving the cursor after the typed text
+    pub fn render_autocomplete_prompt(input: &str) {
+        print!("\r➜ Stop: {}\x1b[K", input);
+        let _ = io::stdout().flush();
+    }
+
+    /// Draw the matches below the autocomplete stop prompt (raw mode, so lines end in \r\n),
+    /// highlighting the selected one, then move the cursor back up to the prompt line
+    pub fn render_autocomplete_list(matches: &[&str], selected_index: usize) {
+        let mut out = io::stdout();
+        let _ = write!(out, "\r\x1b[J");
+        for (idx, name) in matches.iter().enumerate() {
+            let _ = match idx == selected_index {
+                true if Self::use_color() => write!(out, "\r\n  ➤ \x1b[7m{}\x1b[0m", name),
+                true => write!(out, "\r\n  ➤ {}", name),
+                false => write!(out, "\r\n    {}", name),
+            };
+        }
+        if !matches.is_empty() {
+            let _ = write!(out, "\x1b[{}A", matches.len());
+        }
+        let _ = out.flush();
+    }
+
     /// Prompt for line input with examples
     pub fn prompt_line() -> String {
-        print!("\n🚌 Enter line name or code\n");
-        print!("   Examples: 'A', 'C', '1', '23', 'Tram A'\n");
+        println!("\n🚌 Enter line name or code");
+        println!("   Examples: 'A', 'C', '1', '23', 'Tram A'");
         print!("➜ Line: ");
         let _ = io::stdout().flush();
         let mut input = String::new();
@@ -37,8 +148,12 @@ impl NVTViews {
 
     /// Prompt for stop input with examples
     pub fn prompt_stop() -> String {
-        print!("\n📍 Enter stop name\n");
-        print!("   Examples: 'Quinconces', 'Victoire', 'Gare Saint-Jean'\n");
+        Self::show_stop_prompt_hint();
+        Self::read_stop_input()
+    }
+
+    /// Line-buffered stop prompt, once the examples are shown
+    pub fn read_stop_input() -> String {
         print!("➜ Stop: ");
         let _ = io::stdout().flush();
         let mut input = String::new();
@@ -46,18 +161,61 @@ impl NVTViews {
         input.trim().to_string()
     }
 
+    /// Startup overview of the loaded network
+    pub fn show_network_summary(summary: &NetworkSummary) {
+        println!("\n{}", "─".repeat(60));
+        println!("🗺️  {} stops ({} multi-platform stations) | {} lines ({} tram, {} bus)",
+                 summary.total_stops, summary.group_stop_count, summary.total_lines, summary.tram_lines, summary.bus_lines);
+        println!("🚍 {} vehicles tracked | ⚠️  {} alerts", summary.active_vehicles, summary.active_alerts);
+        println!("⏱️  {} line(s) with delays | average delay {}",
+                 summary.lines_with_delays,
+                 NVTControllers::format_delay(summary.avg_delay_seconds.round() as i32));
+        println!("📵 {} stop(s) without real-time data | ♿ {} accessible stop(s)",
+                 summary.stops_without_rt, summary.accessible_stop_count);
+        if let Some(diameter) = summary.graph_diameter_stops {
+            println!("🕸️  Network diameter: {} stops", diameter);
+        }
+        if !summary.most_delayed_lines.is_empty() {
+            let lines: Vec<String> = summary.most_delayed_lines.iter()
+                .map(|(code, delay)| format!("{} {}", code, NVTControllers::format_delay(delay.round() as i32)))
+                .collect();
+            println!("🐢 Most delayed: {}", lines.join(" | "));
+        }
+        println!("{}", "─".repeat(60));
+    }
+
     /// Show selected line with better formatting
     pub fn show_line_selected(line: &Line) {
         println!("\n{}", "─".repeat(60));
-        println!("✓ Line selected: {} - {}",
-                 Self::colorize_line(&line.line_code, &line.color),
-                 line.line_name
+        println!("✓ Line selected: {} - {}{}",
+                 Self::format_line_badge(&line.line_code, &line.color),
+                 line.line_name,
+                 if line.is_circular { " 🔄 Circular" } else { "" }
         );
 
-        if !line.destinations.is_empty() {
+        if line.is_circular {
+            if let Some(terminus) = &line.terminus_a {
+                println!("\n  🏁 Starts and ends at {}", terminus);
+            }
+        } else if let (Some(terminus_a), Some(terminus_b)) = (&line.terminus_a, &line.terminus_b) {
+            println!("\n  🏁 Terminals: {} ↔ {}", terminus_a, terminus_b);
+        }
+
+        if line.is_circular {
+            println!("\n  🔄 Circular route");
+            for (_, place_name) in &line.destinations {
+                println!("     • {}", place_name);
+            }
+        } else if !line.destinations.is_empty() {
             println!("\n  🎯 Destinations:");
             for (dir_ref, place_name) in &line.destinations {
-                let direction = if dir_ref == "0" { "→ Outbound" } else { "← Inbound" };
+                // Name the direction after the terminal it leaves from when known
+                let origin = if dir_ref == "0" { &line.terminus_a } else { &line.terminus_b };
+                let direction = match origin {
+                    Some(origin) => format!("{} From {}", if dir_ref == "0" { "→" } else { "←" }, origin),
+                    None if dir_ref == "0" => "→ Outbound".to_string(),
+                    None => "← Inbound".to_string(),
+                };
                 println!("     {} : {}", direction, place_name);
             }
         }
@@ -65,27 +223,61 @@ impl NVTViews {
         if !line.alerts.is_empty() {
             println!("\n  ⚠️  Alerts (Active or Future):");
             for alert in &line.alerts {
-                println!("     • {}", alert.text);
+                println!("     • [{}] {}", NVTModels::severity_label(alert.severity), alert.text);
+                println!("       🕒 {}", NVTModels::format_alert_period(alert.active_period_start, alert.active_period_end));
             }
         }
 
         println!("{}", "─".repeat(60));
     }
 
+    /// Stop name in the display language chosen with --lang, if translated
+    fn stop_display_name<'a>(stop: &'a Stop, network: &'a NetworkData) -> &'a str {
+        match &network.display_lang {
+            Some(lang) => NVTModels::get_stop_name(stop, lang, &network.gtfs),
+            None => &stop.stop_name,
+        }
+    }
+
     /// Show selected stop with comprehensive info
     pub fn show_stop_selected(stop: &Stop, network: &NetworkData) {
+        Self::print_stop_selected(stop, None, network);
+    }
+
+    /// Selected stop info with its lines as a LINE | FREQUENCY table, most frequent first,
+    /// see `NVTModels::list_lines_at_stop_with_frequency`
+    pub fn show_stop_detail(stop: &Stop, lines_with_freq: &[(String, Option<u32>)], network: &NetworkData) {
+        Self::print_stop_selected(stop, Some(lines_with_freq), network);
+    }
+
+    fn print_stop_selected(stop: &Stop, lines_with_freq: Option<&[(String, Option<u32>)]>, network: &NetworkData) {
         println!("\n{}", "─".repeat(60));
-        println!("✓ Stop selected: {}", stop.stop_name);
+        println!("✓ Stop selected: {}", Self::stop_display_name(stop, network));
         println!("  📌 Location: ({:.6}, {:.6})", stop.latitude, stop.longitude);
         println!("  🆔 Stop ID: {}", stop.stop_id);
+        if stop.hub_score > 0.0 {
+            println!("  🔀 Transfer hub score: {:.2}", stop.hub_score);
+        }
 
-        if !stop.lines.is_empty() {
+        if let Some(lines_with_freq) = lines_with_freq.filter(|lines| !lines.is_empty()) {
+            println!("\n  🚌 Lines serving this stop ({}):", lines_with_freq.len());
+            for (line_code, headway) in lines_with_freq {
+                let badge = Self::format_line_badge(line_code, &NVTModels::get_line_color(line_code, network));
+                // Badges carry ANSI codes, so pad by the visible code length
+                let padding = " ".repeat(8usize.saturating_sub(line_code.chars().count() + 2));
+                let frequency = match headway {
+                    Some(secs) => format!("Every {} min (peak)", (secs + 30) / 60),
+                    None => "Schedule only".to_string(),
+                };
+                println!("     {}{}  {}", badge, padding, frequency);
+            }
+        } else if !stop.lines.is_empty() {
             println!("\n  🚌 Lines serving this stop ({}):", stop.lines.len());
             let mut line_display = Vec::new();
             for line_ref in &stop.lines {
                 if let Some(line) = network.lines.iter().find(|l| &l.line_ref == line_ref) {
                     line_display.push(format!("{}",
-                                              Self::colorize_line(&line.line_code, &line.color)
+                                              Self::format_line_badge(&line.line_code, &line.color)
                     ));
                 }
             }
@@ -98,17 +290,21 @@ impl NVTViews {
         if !stop.alerts.is_empty() {
             println!("\n  ⚠️  Alerts: (Active or Future)");
             for alert in &stop.alerts {
-                println!("     • {}", alert.text);
+                println!("     • [{}] {}", NVTModels::severity_label(alert.severity), alert.text);
+                println!("       🕒 {}", NVTModels::format_alert_period(alert.active_period_start, alert.active_period_end));
             }
         }
 
         println!("{}", "─".repeat(60));
     }
 
-    /// Show stop choices when multiple matches
-    /// Show stop choices when multiple matches
-    pub fn show_stop_choices(stops: &[&Stop], network: &NetworkData) {
-        println!("\n📍 Multiple stops found. Please choose:");
+    /// Show stop choices when multiple matches; `is_station` when they are all platforms of one station
+    pub fn show_stop_choices(stops: &[&Stop], is_station: bool, network: &NetworkData) {
+        if is_station {
+            println!("\n🚉 {} has {} platforms. Please choose:", stops[0].stop_name, stops.len());
+        } else {
+            println!("\n📍 Multiple stops found. Please choose:");
+        }
         println!("{}", "─".repeat(60));
         for (i, stop) in stops.iter().enumerate() {
             println!("  {}. {} (ID: {})", i + 1, stop.stop_name, stop.stop_id);
@@ -120,7 +316,7 @@ impl NVTViews {
                     .filter_map(|line_ref| {
                         network.lines.iter()
                             .find(|l| &l.line_ref == line_ref)
-                            .map(|l| Self::colorize_line(&l.line_code, &l.color))
+                            .map(|l| Self::format_line_badge(&l.line_code, &l.color))
                     })
                     .take(10)
                     .collect();
@@ -136,15 +332,127 @@ impl NVTViews {
                 println!();
             }
         }
+        if is_station {
+            println!("\n  a. Show all platforms");
+        }
+        println!("{}", "─".repeat(60));
+    }
+    /// Show close stop name matches when the search found nothing
+    pub fn show_fuzzy_stop_suggestions(input: &str, stops: &[&Stop]) {
+        println!("\n✗ No stop named '{}'", input);
+        println!("💡 Did you mean one of these stops?");
+        println!("{}", "─".repeat(60));
+        for (i, stop) in stops.iter().enumerate() {
+            println!("  {}. {} (ID: {})", i + 1, stop.stop_name, stop.stop_id);
+        }
         println!("{}", "─".repeat(60));
     }
+
+    /// Stops shared by two lines, numbered for selection
+    pub fn show_transfer_stops(line_a: &Line, line_b: &Line, stops: &[&Stop]) {
+        println!("\n🔄 Transfers between {} and {}",
+                 Self::format_line_badge(&line_a.line_code, &line_a.color),
+                 Self::format_line_badge(&line_b.line_code, &line_b.color));
+        println!("{}", "─".repeat(60));
+
+        if stops.is_empty() {
+            println!("  ✗ These lines have no stop in common");
+            println!("{}", "─".repeat(60));
+            return;
+        }
+
+        for (i, stop) in stops.iter().enumerate() {
+            println!("  {}. {} (ID: {})", i + 1, stop.stop_name, stop.stop_id);
+        }
+        println!("{}", "─".repeat(60));
+    }
+
+    /// Show stops found around a coordinate, nearest first
+    pub fn show_nearest_stops(stops: &[(&Stop, f64)], near: &NearQuery, network: &NetworkData) {
+        println!("\n{}", "═".repeat(70));
+        println!("📍 STOPS WITHIN {:.0} m OF ({:.6}, {:.6})", near.radius_m, near.latitude, near.longitude);
+        println!("{}", "═".repeat(70));
+
+        if stops.is_empty() {
+            println!("\n✗ No stops found in this radius");
+            println!("💡 Try a larger radius, e.g. --near {},{},800", near.latitude, near.longitude);
+            return;
+        }
+
+        for (stop, distance) in stops {
+            let line_codes: Vec<String> = stop.lines.iter()
+                .filter_map(|line_ref| {
+                    network.lines.iter()
+                        .find(|l| &l.line_ref == line_ref)
+                        .map(|l| Self::format_line_badge(&l.line_code, &l.color))
+                })
+                .collect();
+
+            let accessible = if stop.wheelchair_accessible { " ♿" } else { "" };
+            println!("  {:>5.0} m  {}{} (ID: {})", distance, stop.stop_name, accessible, stop.stop_id);
+            if !line_codes.is_empty() {
+                println!("           🚌 {}", line_codes.join(" "));
+            }
+        }
+        println!("{}", "─".repeat(70));
+        println!("✓ {} stop(s) found", stops.len());
+    }
+
+    /// Schematic map of the stops around a point, drawn after the --near listing
+    pub fn show_stop_map_ascii(stops: &[&Stop], center_lat: f64, center_lon: f64, radius_km: f64, network: &NetworkData) {
+        println!("\n🗺️  MAP ({:.1} km around ★, north up)", radius_km);
+        println!("{}", Self::stop_map_ascii(stops, center_lat, center_lon, radius_km, network));
+        println!("   Letters: first character of a line serving the stop | · no line | + several stops");
+    }
+
+    /// Stops projected linearly onto a bordered 60×20 grid covering `radius_km` around the centre.
+    /// Stops outside the box are left out.
+    pub fn stop_map_ascii(stops: &[&Stop], center_lat: f64, center_lon: f64, radius_km: f64, network: &NetworkData) -> String {
+        const WIDTH: usize = 60;
+        const HEIGHT: usize = 20;
+
+        let (min_lat, max_lat, min_lon, max_lon) = NVTModels::bounding_box(center_lat, center_lon, radius_km);
+        let cell = |lat: f64, lon: f64| -> Option<(usize, usize)> {
+            if !(min_lat..=max_lat).contains(&lat) || !(min_lon..=max_lon).contains(&lon) {
+                return None;
+            }
+            let col = ((lon - min_lon) / (max_lon - min_lon) * (WIDTH - 1) as f64).round() as usize;
+            let row = ((max_lat - lat) / (max_lat - min_lat) * (HEIGHT - 1) as f64).round() as usize;
+            Some((row, col))
+        };
+
+        let mut grid = vec![vec![' '; WIDTH]; HEIGHT];
+        for stop in stops {
+            let Some((row, col)) = cell(stop.latitude, stop.longitude) else {
+                continue;
+            };
+            let mark = stop.lines.iter()
+                .find_map(|line_ref| network.lines.iter().find(|l| &l.line_ref == line_ref))
+                .and_then(|line| line.line_code.chars().next())
+                .unwrap_or('·');
+            grid[row][col] = if grid[row][col] == ' ' { mark } else { '+' };
+        }
+        if let Some((row, col)) = cell(center_lat, center_lon) {
+            grid[row][col] = '★';
+        }
+
+        let mut map = format!("┌{}┐\n", "─".repeat(WIDTH));
+        for row in grid {
+            map.push('│');
+            map.extend(row);
+            map.push_str("│\n");
+        }
+        map.push_str(&format!("└{}┘", "─".repeat(WIDTH)));
+        map
+    }
+
     /// Show line suggestions with better formatting
     pub fn show_line_suggestions(lines: &[&Line]) {
         println!("\n💡 Did you mean one of these lines?");
         println!("{}", "─".repeat(60));
         for line in lines {
             println!("  • {} {} - {}",
-                     Self::colorize_line(&line.line_code, &line.color),
+                     Self::format_line_badge(&line.line_code, &line.color),
                      line.line_name,
                      line.line_ref
             );
@@ -160,10 +468,10 @@ impl NVTViews {
         network: &NetworkData,
     ) {
         println!("\n{}", "═".repeat(70));
-        println!("🕐 NEXT VEHICLES AT: {}", stop.stop_name);
+        println!("🕐 NEXT VEHICLES AT: {}", Self::stop_display_name(stop, network));
         if let Some(line) = selected_line {
             println!("   Filtered by line: {} {}",
-                     Self::colorize_line(&line.line_code, &line.color),
+                     Self::format_line_badge(&line.line_code, &line.color),
                      line.line_name
             );
         }
@@ -171,11 +479,12 @@ impl NVTViews {
 
         if vehicles.is_empty() {
             Self::show_no_vehicles_message(stop, selected_line);
+            Self::show_cancelled_arrivals(stop, selected_line, network);
             return;
         }
 
         let now = chrono::Utc::now().timestamp();
-        let is_all_scheduled = vehicles.iter().all(|v| NVTControllers::is_scheduled(v));
+        let is_all_scheduled = vehicles.iter().all(|v| NVTModels::is_scheduled(v));
 
         if is_all_scheduled {
             println!("\n📅 Showing scheduled times (real-time tracking unavailable)");
@@ -185,9 +494,19 @@ impl NVTViews {
 
         println!("{}", "─".repeat(70));
 
+        // A missing or unreadable log only means no historical load is shown
+        let loads = HISTORY_LOG.get()
+            .and_then(|path| NVTModels::compute_load_indicators(path, &stop.stop_id).ok())
+            .unwrap_or_default();
+        let hour = NVTModels::local_hour(now);
+
         let max_display = 10;
         for (i, rt) in vehicles.iter().take(max_display).enumerate() {
-            Self::display_vehicle_info(i + 1, rt, network, now);
+            // Service gaps are per route direction, so only the first vehicle of each mentions them
+            let first_of_route = !vehicles[..i].iter()
+                .any(|v| v.route_id == rt.route_id && v.direction_id == rt.direction_id);
+            let load = rt.route_id.as_ref().and_then(|route_id| loads.get(&(route_id.clone(), hour)).copied());
+            Self::display_vehicle_info(i + 1, rt, network, now, first_of_route, load);
             if i < vehicles.len().min(max_display) - 1 {
                 println!("{}", "  ┄".repeat(35));
             }
@@ -197,24 +516,603 @@ impl NVTViews {
             println!("\n  ... and {} more upcoming vehicles", vehicles.len() - max_display);
         }
 
+        Self::show_cancelled_arrivals(stop, selected_line, network);
+
         // Show alerts if any
         if !stop.alerts.is_empty() {
             println!("\n{}", "═".repeat(70));
             println!("⚠️  ALERTS (ACTIVE OR FUTURE) FOR THIS STOP:");
             for alert in &stop.alerts {
-                println!("  • {}", alert.text);
+                println!("  • [{}] {}", NVTModels::severity_label(alert.severity), alert.text);
+                println!("    🕒 {}", NVTModels::format_alert_period(alert.active_period_start, alert.active_period_end));
             }
         }
 
         println!("{}", "═".repeat(70));
     }
 
+    /// Departures as a fixed-width LINE | DESTINATION | SCHEDULED | STATUS table, like a stop display
+    pub fn show_departure_board(stop: &Stop, vehicles: &[&RealTimeInfo], network: &NetworkData) {
+        println!();
+        for row in Self::format_departure_board(stop, vehicles, network) {
+            println!("{}", row);
+        }
+    }
+
+    /// Lines of the departure board printed by `show_departure_board`
+    pub fn format_departure_board(stop: &Stop, vehicles: &[&RealTimeInfo], network: &NetworkData) -> Vec<String> {
+        const WIDTHS: [usize; 4] = [8, 20, 8, 15];
+        let inner_width: usize = WIDTHS.iter().map(|w| w + 3).sum::<usize>() - 1;
+
+        let mut rows = vec![
+            format!("🚏 {}", Self::stop_display_name(stop, network)),
+            format!("┌{}┐", "─".repeat(inner_width)),
+            Self::board_row(["LINE", "DESTINATION", "SCHED", "STATUS"], &WIDTHS, None),
+            format!("├{}┤", "─".repeat(inner_width)),
+        ];
+
+        if vehicles.is_empty() {
+            rows.push(format!("│ {:<width$} │", "No departures", width = inner_width - 2));
+        }
+
+        for rt in vehicles.iter().take(10) {
+            let line = rt.route_id.as_deref()
+                .and_then(|route_id| NVTModels::get_line_by_route_id(route_id, network));
+            let code = line.map_or("?", |l| l.line_code.as_str());
+            let destination = rt.destination.as_deref()
+                .or_else(|| {
+                    let direction = rt.direction_id?.to_string();
+                    line?.destinations.iter().find(|(d, _)| *d == direction).map(|(_, place)| place.as_str())
+                })
+                .unwrap_or("");
+            let scheduled = rt.timestamp
+                .map(|ts| NVTModels::format_timestamp(ts - rt.delay.unwrap_or(0) as i64))
+                .unwrap_or_else(|| "--:--:--".to_string());
+
+            let (status, sgr) = match rt.delay {
+                _ if rt.cancelled => ("Cancelled".to_string(), Some("31")),
+                Some(delay) if delay >= 180 => (NVTControllers::format_delay(delay), Some("31")),
+                Some(delay) if delay > 60 => (NVTControllers::format_delay(delay), Some("33")),
+                Some(_) => ("On time".to_string(), Some("32")),
+                None => ("Scheduled".to_string(), None),
+            };
+
+            rows.push(Self::board_row([code, destination, &scheduled, &status], &WIDTHS, sgr));
+        }
+
+        rows.push(format!("└{}┘", "─".repeat(inner_width)));
+        if vehicles.len() > 10 {
+            rows.push(format!("  ... and {} more upcoming vehicles", vehicles.len() - 10));
+        }
+        rows
+    }
+
+    /// One board row; cells are truncated or padded to their width, then the
+    /// last cell is styled with `status_sgr` so escape codes don't shift the columns
+    fn board_row(cells: [&str; 4], widths: &[usize; 4], status_sgr: Option<&str>) -> String {
+        let fitted: Vec<String> = cells.iter().zip(widths)
+            .map(|(cell, &width)| {
+                let mut text: String = cell.chars().take(width).collect();
+                if cell.chars().count() > width {
+                    text.pop();
+                    text.push('…');
+                }
+                format!("{:<width$}", text, width = width)
+            })
+            .collect();
+
+        let status = match status_sgr {
+            Some(sgr) => Self::styled(fitted[3].clone(), sgr),
+            None => fitted[3].clone(),
+        };
+        format!("│ {} │ {} │ {} │ {} │", fitted[0], fitted[1], fitted[2], status)
+    }
+
+    /// Scheduled frequency of every line as a LINE | NAME | PEAK | OFF-PEAK | FIRST | LAST table
+    pub fn show_line_frequency_table(rows: &[(&Line, LineFrequency)], sorted_by_peak: bool) {
+        const WIDTHS: [usize; 6] = [6, 26, 12, 12, 7, 7];
+        let inner_width: usize = WIDTHS.iter().map(|w| w + 3).sum::<usize>() - 1;
+        let row = |cells: [&str; 6]| {
+            let fitted: Vec<String> = cells.iter().zip(WIDTHS)
+                .map(|(cell, width)| {
+                    let mut text: String = cell.chars().take(width).collect();
+                    if cell.chars().count() > width {
+                        text.pop();
+                        text.push('…');
+                    }
+                    format!("{:<width$}", text, width = width)
+                })
+                .collect();
+            format!("│ {} │", fitted.join(" │ "))
+        };
+        let headway = |secs: Option<u32>| secs.map_or("-".to_string(), |s| format!("~{} min", (s + 30) / 60));
+        let time_of_day = |secs: u32| format!("{:02}:{:02}", (secs / 3600) % 24, (secs % 3600) / 60);
+
+        println!("\n🔁 LINE FREQUENCY (peak 07:00-09:00, off-peak 10:00-16:00, at the first stop)");
+        println!("┌{}┐", "─".repeat(inner_width));
+        println!("{}", row(["LINE", "NAME", "PEAK", "OFF-PEAK", "FIRST", "LAST"]));
+        println!("├{}┤", "─".repeat(inner_width));
+
+        if rows.is_empty() {
+            println!("│ {:<width$} │", "No lines", width = inner_width - 2);
+        }
+
+        for (line, frequency) in rows {
+            let (first, last) = frequency.service_span
+                .map_or(("-".to_string(), "-".to_string()), |(first, last)| (time_of_day(first), time_of_day(last)));
+            println!("{}", row([
+                &line.line_code,
+                &line.line_name,
+                &headway(frequency.peak_headway_s),
+                &headway(frequency.off_peak_headway_s),
+                &first,
+                &last,
+            ]));
+        }
+
+        println!("└{}┘", "─".repeat(inner_width));
+        println!("Sorted by {}", if sorted_by_peak { "peak headway" } else { "line code" });
+    }
+
+    /// Ask whether to change the line frequency table's sort order
+    pub fn prompt_frequency_sort() -> String {
+        print!("\n➜ [s] toggle sort, Enter to go back: ");
+        let _ = io::stdout().flush();
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).expect("Failed to read input");
+        input.trim().to_lowercase()
+    }
+
+    /// List cancelled trips that were due at this stop, so they are not silently missing
+    fn show_cancelled_arrivals(stop: &Stop, selected_line: Option<&Line>, network: &NetworkData) {
+        let selected_route = selected_line.and_then(|l| NVTModels::extract_line_id(&l.line_ref));
+        let cancelled: Vec<&RealTimeInfo> = stop.cancelled_arrivals.iter()
+            .filter(|rt| selected_route.is_none() || rt.route_id.as_deref() == selected_route)
+            .collect();
+
+        if cancelled.is_empty() {
+            return;
+        }
+
+        println!("\n{}", "─".repeat(70));
+        println!("🚫 CANCELLED TRIPS AT THIS STOP:");
+        for rt in cancelled {
+            let line = rt.route_id.as_deref()
+                .and_then(|route_id| NVTModels::get_line_by_route_id(route_id, network))
+                .map(|l| format!("{} {}", Self::format_line_badge(&l.line_code, &l.color), l.line_name))
+                .unwrap_or_else(|| "Unknown line".to_string());
+            let time = rt.timestamp
+                .map(NVTModels::format_timestamp)
+                .unwrap_or_else(|| "--:--".to_string());
+            let destination = rt.destination.as_deref().unwrap_or("Unknown destination");
+
+            println!("  🚫 Cancelled  {} → {} (was due {})", line, destination, time);
+        }
+    }
+
+    fn vehicle_headway(rt: &RealTimeInfo, network: &NetworkData) -> Option<u32> {
+        NVTModels::compute_headway_seconds(
+            rt.stop_id.as_deref()?,
+            rt.route_id.as_deref()?,
+            rt.direction_id.unwrap_or(0),
+            NVTModels::service_date(network.service_date),
+            &network.gtfs,
+        )
+    }
+
+    /// Lines matching a --search-lines pattern as a CODE | NAME | DESTINATIONS table
+    pub fn show_line_search_results(pattern: &str, is_regex: bool, lines: &[&Line]) {
+        println!("\n{}", "═".repeat(70));
+        println!("🔎 LINES MATCHING '{}' ({})", pattern, lines.len());
+        if !is_regex {
+            println!("   Invalid regex, using text search");
+        }
+        println!("{}", "═".repeat(70));
+
+        if lines.is_empty() {
+            println!("\n✗ No matching lines");
+            return;
+        }
+
+        println!("  {:<8} {:<30} DESTINATIONS", "CODE", "NAME");
+        println!("{}", "─".repeat(70));
+        for line in lines {
+            // Badges are the code plus two columns, with or without colors
+            let padding = " ".repeat(8usize.saturating_sub(line.line_code.chars().count() + 2));
+            let destinations: Vec<&str> = line.destinations.iter().map(|(_, place)| place.as_str()).collect();
+            println!("  {}{} {:<30} {}{}",
+                     Self::format_line_badge(&line.line_code, &line.color),
+                     padding,
+                     line.line_name,
+                     if line.is_circular { "🔄 " } else { "" },
+                     destinations.join(if line.is_circular { " / " } else { " ↔ " }));
+        }
+        println!("{}", "═".repeat(70));
+    }
+
+    /// Integrity issues found by --validate
+    pub fn show_validation_report(violations: &[String]) {
+        println!("\n{}", "═".repeat(70));
+        println!("🔍 DATA INTEGRITY");
+        println!("{}", "═".repeat(70));
+
+        if violations.is_empty() {
+            println!("\n✓ No issues found");
+            return;
+        }
+
+        for violation in violations {
+            println!("  ⚠️  {}", violation);
+        }
+        println!("{}", "─".repeat(70));
+        println!("✗ {} issue(s) found", violations.len());
+    }
+
+    /// Live delay distribution of a line (--delay-stats)
+    pub fn show_delay_statistics(line: &Line, stats: &DelayStats) {
+        println!("\n{}", "═".repeat(60));
+        println!("⏱️  LIVE DELAYS: {} {}", Self::format_line_badge(&line.line_code, &line.color), line.line_name);
+        println!("{}", "═".repeat(60));
+
+        let total = stats.delayed_count + stats.early_count + stats.on_time_count;
+        if total == 0 {
+            println!("\n✗ No delay reported for this line right now");
+            return;
+        }
+
+        let format = |seconds: f32| NVTControllers::format_delay(seconds.round() as i32);
+        println!("  {:<16} {}", "Mean", format(stats.mean_delay_s));
+        println!("  {:<16} {}", "Median", format(stats.median_delay_s));
+        println!("  {:<16} {}", "90th percentile", format(stats.p90_delay_s));
+        println!("{}", "─".repeat(60));
+        for (label, count) in [
+            ("🔴 Late (>1 min)", stats.delayed_count),
+            ("🟢 On time", stats.on_time_count),
+            ("🔵 Early (>1 min)", stats.early_count),
+        ] {
+            println!("  {:<18} {:>4}  ({:.0}%)", label, count, count as f32 * 100.0 / total as f32);
+        }
+        println!("{}", "═".repeat(60));
+    }
+
+    /// Stops without real-time data (--coverage-report), under each line serving them
+    pub fn show_coverage_report(uncovered: usize, total: usize, by_line: &[(&Line, Vec<&Stop>, usize)]) {
+        println!("\n{}", "═".repeat(70));
+        println!("📵 STOPS WITHOUT REAL-TIME DATA: {} of {}", uncovered, total);
+        println!("{}", "═".repeat(70));
+
+        if by_line.is_empty() {
+            println!("\n✓ Every stop served by a line has real-time data");
+            return;
+        }
+
+        for (line, stops, line_total) in by_line {
+            let whole_line = if stops.len() == *line_total { "  ← whole line" } else { "" };
+            println!("\n{} {} — {}/{} stops{}",
+                     Self::format_line_badge(&line.line_code, &line.color),
+                     line.line_name,
+                     stops.len(),
+                     line_total,
+                     whole_line);
+            let mut names: Vec<&str> = stops.iter().map(|s| s.stop_name.as_str()).collect();
+            names.sort_unstable();
+            names.dedup();
+            println!("   {}", names.join(", "));
+        }
+        println!("\n{}", "═".repeat(70));
+    }
+
+    /// Unusually long scheduled waits along a line (--gaps), per stop and direction
+    pub fn show_service_gaps(line: &Line, gaps: &[(&Stop, String, Vec<ServiceGap>)], network: &NetworkData) {
+        println!("\n{}", "═".repeat(60));
+        println!("⚠️  SERVICE GAPS ON {} {}", Self::format_line_badge(&line.line_code, &line.color), line.line_name);
+        println!("   Waits over {}× the median headway", NVTModels::SERVICE_GAP_FACTOR);
+        println!("{}", "═".repeat(60));
+
+        if gaps.is_empty() {
+            println!("\n✓ No service gaps in the timetable");
+            return;
+        }
+
+        for (stop, destination, stop_gaps) in gaps {
+            println!("\n  📍 {} → {}", Self::stop_display_name(stop, network), destination);
+            for (starts_at, gap) in stop_gaps {
+                println!("     • {} → {} ({} min)",
+                         &NVTModels::format_timestamp(*starts_at)[..5],
+                         &NVTModels::format_timestamp(*starts_at + gap.as_secs() as i64)[..5],
+                         gap.as_secs() / 60);
+            }
+        }
+        println!("\n{}", "═".repeat(60));
+    }
+
+    /// Show scheduled headways of the lines serving a stop (--headway)
+    pub fn show_headways(stop: &Stop, headways: &[(&Line, String, Option<u32>)], network: &NetworkData) {
+        println!("\n{}", "═".repeat(60));
+        println!("🔁 SCHEDULED HEADWAY AT: {} (ID: {})", Self::stop_display_name(stop, network), stop.stop_id);
+        println!("   Median gap between departures, 07:00-21:00");
+        println!("{}", "═".repeat(60));
+
+        if headways.is_empty() {
+            println!("\n✗ No lines found for this stop");
+            return;
+        }
+
+        for (line, destination, headway) in headways {
+            let frequency = match headway {
+                Some(secs) => format!("every ~{} min", (secs + 30) / 60),
+                None => "no timetable data".to_string(),
+            };
+            println!("  {} → {}: {}", Self::format_line_badge(&line.line_code, &line.color), destination, frequency);
+        }
+        println!("{}", "─".repeat(60));
+    }
+
+    /// Show a planned itinerary leg by leg, or why none was found
+    pub fn show_journey(journey: Option<&Journey>, from: &Stop, to: &Stop, now: i64, network: &NetworkData) {
+        let stop_name = |stop_id: &str| {
+            network.stops.iter()
+                .find(|s| s.stop_id == stop_id)
+                .map(|s| Self::stop_display_name(s, network).to_string())
+                .unwrap_or_else(|| stop_id.to_string())
+        };
+
+        println!("\n{}", "═".repeat(60));
+        println!("🧭 JOURNEY: {} → {}", Self::stop_display_name(from, network), Self::stop_display_name(to, network));
+        println!("{}", "═".repeat(60));
+
+        let Some(journey) = journey else {
+            println!("\n✗ No journey found in the next {} hours", NVTModels::JOURNEY_HORIZON_SECS / 3600);
+            println!("💡 Timetable data may be missing for these stops; try again later or with --date");
+            return;
+        };
+
+        let mut ride_number = 0;
+        for leg in &journey.legs {
+            if leg.mode == LegMode::Walk {
+                let minutes = ((leg.arrive_time - leg.depart_time).max(0) as u32).div_ceil(60).max(1);
+                println!("\n  🚶 Walk {} min to {}", minutes, stop_name(&leg.alight_stop_id));
+                continue;
+            }
+
+            ride_number += 1;
+            let line = match NVTModels::get_line_by_route_id(&leg.route_id, network) {
+                Some(l) => format!("{} {}", Self::format_line_badge(&l.line_code, &l.color), l.line_name),
+                None => format!("Route {}", leg.route_id),
+            };
+            println!("\n  {}. {}", ride_number, line);
+            println!("     🚏 {}  {}", NVTModels::format_timestamp(leg.depart_time), stop_name(&leg.board_stop_id));
+            println!("     🏁 {}  {}", NVTModels::format_timestamp(leg.arrive_time), stop_name(&leg.alight_stop_id));
+        }
+
+        println!("{}", "─".repeat(60));
+        match journey.depart_time().zip(journey.arrive_time()) {
+            Some((departure, arrival)) => println!(
+                "⏱️  Leave {} · arrive {} · {} min · {} transfer(s)",
+                NVTModels::format_timestamp(departure),
+                NVTModels::format_timestamp(arrival),
+                (arrival - now).max(0) / 60,
+                journey.transfers()
+            ),
+            None => println!("🚶 These stops are within walking distance"),
+        }
+    }
+
+    /// Show the stops reachable within a travel time budget, nearest first
+    pub fn show_isochrone(from: &Stop, max_minutes: u32, reachable: &[(String, u32)], network: &NetworkData) {
+        println!("\n{}", "═".repeat(60));
+        println!("⏳ REACHABLE WITHIN {} MIN FROM: {}", max_minutes, Self::stop_display_name(from, network));
+        println!("{}", "═".repeat(60));
+
+        let stops: Vec<(&Stop, u32)> = reachable.iter()
+            .filter(|(stop_id, _)| stop_id != &from.stop_id)
+            .filter_map(|(stop_id, minutes)| {
+                NVTModels::get_stop_by_id(stop_id, network).map(|s| (s, *minutes))
+            })
+            .collect();
+
+        if stops.is_empty() {
+            println!("\n✗ No stop reachable in this time");
+            println!("💡 Try a larger budget, e.g. --minutes {}", max_minutes * 2);
+            return;
+        }
+
+        println!("  {:>4}  STOP", "MIN");
+        for (stop, minutes) in &stops {
+            let line_codes: Vec<String> = stop.lines.iter()
+                .filter_map(|line_ref| {
+                    network.lines.iter()
+                        .find(|l| &l.line_ref == line_ref)
+                        .map(|l| Self::format_line_badge(&l.line_code, &l.color))
+                })
+                .collect();
+            println!("  {:>4}  {} {}", minutes, Self::stop_display_name(stop, network), line_codes.join(" "));
+        }
+        println!("{}", "─".repeat(60));
+        println!("✓ {} stop(s) reachable", stops.len());
+    }
+
+    /// Lines two stops share and those only one of them serves
+    pub fn show_stop_comparison(comparison: &StopComparison, stop_a: &Stop, stop_b: &Stop, network: &NetworkData) {
+        let badges = |line_refs: &[String]| -> String {
+            if line_refs.is_empty() {
+                return "none".to_string();
+            }
+            line_refs.iter()
+                .map(|line_ref| match network.lines.iter().find(|l| &l.line_ref == line_ref) {
+                    Some(l) => Self::format_line_badge(&l.line_code, &l.color),
+                    None => line_ref.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        let name_a = Self::stop_display_name(stop_a, network);
+        let name_b = Self::stop_display_name(stop_b, network);
+        println!("\n{}", "═".repeat(60));
+        println!("⚖️  COMPARE: {} ↔ {}", name_a, name_b);
+        println!("{}", "═".repeat(60));
+        println!("  📏 {:.0} m apart", comparison.distance_m);
+        println!("  🔗 Both:        {}", badges(&comparison.common_lines));
+        println!("  🅰️  Only {}: {}", name_a, badges(&comparison.only_a));
+        println!("  🅱️  Only {}: {}", name_b, badges(&comparison.only_b));
+        println!("{}", "─".repeat(60));
+    }
+
+    /// List pinned stops with their next arrival and pinned lines with their tracked vehicles
+    pub fn show_favorites(favorites: &Favorites, network: &NetworkData) {
+        println!("\n{}", "═".repeat(60));
+        println!("⭐ MY FAVORITES");
+        println!("{}", "═".repeat(60));
+
+        let now = chrono::Utc::now().timestamp();
+        println!("\n📍 Stops");
+        if favorites.stops.is_empty() {
+            println!("  (none pinned yet)");
+        }
+        for (i, stop_id) in favorites.stops.iter().enumerate() {
+            let Some(stop) = NVTModels::get_stop_by_id(stop_id, network) else {
+                println!("  {}. {} (no longer in the network data)", i + 1, stop_id);
+                continue;
+            };
+
+            let next = NVTModels::get_next_vehicles_for_stop(stop_id, network).into_iter().next().map(|rt| {
+                let line_code = rt.route_id.as_deref()
+                    .and_then(|route_id| NVTModels::get_line_by_route_id(route_id, network))
+                    .map(|l| Self::format_line_badge(&l.line_code, &l.color))
+                    .unwrap_or_else(|| "?".to_string());
+                let minutes = rt.timestamp.map(|ts| (ts - now).max(0) / 60);
+                match minutes {
+                    Some(minutes) => format!("{} in {} min", line_code, minutes),
+                    None => line_code,
+                }
+            });
+            println!("  {}. {} → {}", i + 1, Self::stop_display_name(stop, network),
+                     next.unwrap_or_else(|| "no upcoming arrival".to_string()));
+        }
+
+        println!("\n🚌 Lines");
+        if favorites.lines.is_empty() {
+            println!("  (none pinned yet)");
+        }
+        for line_ref in &favorites.lines {
+            match network.lines.iter().find(|l| &l.line_ref == line_ref) {
+                Some(line) => println!("  • {} {} → {} vehicle(s) tracked",
+                                       Self::format_line_badge(&line.line_code, &line.color),
+                                       line.line_name,
+                                       line.real_time.len()),
+                None => println!("  • {} (no longer in the network data)", line_ref),
+            }
+        }
+        println!("{}", "─".repeat(60));
+    }
+
+    /// Ask what to do on the favorites screen; returns the trimmed, lowercased answer
+    pub fn prompt_favorites_action() -> String {
+        println!("  NUMBER  select that stop");
+        println!("  s       pin/unpin the selected stop");
+        println!("  l       pin/unpin the selected line");
+        print!("➜ Choice (Enter to go back): ");
+        let _ = io::stdout().flush();
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).expect("Failed to read input");
+        input.trim().to_lowercase()
+    }
+
+    /// Per-line on-time rates from the arrival log, most reliable first
+    pub fn show_reliability_report(rates: &HashMap<String, f32>) {
+        const BAR_WIDTH: usize = 30;
+
+        println!("\n{}", "═".repeat(60));
+        println!("📈 ON-TIME RATE BY LINE (within ±{} s of schedule)", NVTModels::ON_TIME_TOLERANCE_SECS);
+        println!("{}", "═".repeat(60));
+
+        if rates.is_empty() {
+            println!("\n✗ No logged arrivals in this period");
+            println!("💡 Record some with --log PATH while watching arrivals");
+            return;
+        }
+
+        let mut sorted: Vec<(&String, &f32)> = rates.iter().collect();
+        sorted.sort_by(|a, b| b.1.total_cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+        for (route_id, rate) in sorted {
+            let filled = (rate * BAR_WIDTH as f32).round() as usize;
+            println!("  {:>6}  {}{} {:>5.1}%",
+                     route_id,
+                     "█".repeat(filled),
+                     "░".repeat(BAR_WIDTH - filled),
+                     rate * 100.0);
+        }
+        println!("{}", "─".repeat(60));
+    }
+
+    /// Historical load indicator of a line at a stop for each hour with logged arrivals
+    pub fn show_occupancy_trend(stop: &Stop, line: &Line, loads: &[(u8, f32)], network: &NetworkData) {
+        println!("\n{}", "═".repeat(60));
+        println!("📊 HISTORICAL LOAD: {} at {}",
+                 Self::format_line_badge(&line.line_code, &line.color),
+                 Self::stop_display_name(stop, network));
+        println!("   Mean delay of logged arrivals, full at {} min or more",
+                 NVTModels::FULL_LOAD_DELAY_SECS / 60);
+        println!("{}", "═".repeat(60));
+
+        if loads.is_empty() {
+            println!("\n✗ No logged arrivals of this line at this stop");
+            println!("💡 Record some with --log PATH while watching arrivals");
+            return;
+        }
+
+        for (hour, load) in loads {
+            println!("  {:02}:00  {}", hour, Self::format_load_bar(*load));
+        }
+        println!("{}", "─".repeat(60));
+    }
+
+    /// A 10-cell bar for a 0.0–1.0 load indicator: green, yellow above 0.4, red above 0.7
+    fn format_load_bar(load: f32) -> String {
+        const BAR_WIDTH: usize = 10;
+        let filled = (load.clamp(0.0, 1.0) * BAR_WIDTH as f32).round() as usize;
+        let sgr = match load {
+            l if l > 0.7 => "31",
+            l if l > 0.4 => "33",
+            _ => "32",
+        };
+        format!("{}{} {:>3.0}%",
+                Self::styled("█".repeat(filled), sgr),
+                "░".repeat(BAR_WIDTH - filled),
+                load * 100.0)
+    }
+
+    /// Per-line mean absolute ETA error from the arrival log, most accurate first
+    pub fn show_eta_accuracy_report(maes: &HashMap<String, f32>) {
+        println!("\n{}", "═".repeat(60));
+        println!("🎯 ETA ACCURACY BY LINE (mean absolute error)");
+        println!("{}", "═".repeat(60));
+
+        if maes.is_empty() {
+            println!("\n✗ No ETA accuracy records in this period");
+            println!("💡 They are logged with --log PATH while data refreshes");
+            return;
+        }
+
+        let mut sorted: Vec<(&String, &f32)> = maes.iter().collect();
+        sorted.sort_by(|a, b| a.1.total_cmp(b.1).then_with(|| a.0.cmp(b.0)));
+
+        for (route_id, mae) in sorted {
+            println!("  {:>6}  {:>7.0} s  ({:.1} min)", route_id, mae, mae / 60.0);
+        }
+        println!("{}", "─".repeat(60));
+    }
+
     /// Display individual vehicle information
     fn display_vehicle_info(
         index: usize,
         rt: &RealTimeInfo,
         network: &NetworkData,
         now: i64,
+        show_service_gap: bool,
+        historical_load: Option<f32>,
     ) {
         // Find the line for this vehicle
         let line = rt.route_id.as_ref().and_then(|route_id| {
@@ -225,7 +1123,7 @@ impl NVTViews {
 
         println!("\n  {}. {}", index, if let Some(l) = line {
             format!("{} {}",
-                    Self::colorize_line(&l.line_code, &l.color),
+                    Self::format_line_badge(&l.line_code, &l.color),
                     l.line_name
             )
         } else {
@@ -244,27 +1142,54 @@ impl NVTViews {
 
         // Show timing information
         if let Some(ts) = rt.timestamp {
-            let time_str = NVTModels::format_timestamp(ts);
-            let minutes = NVTControllers::minutes_until_arrival(ts, now);
-
-            print!("     ⏰ ");
-            if minutes < 0 {
-                println!("Time: {} (⚫ departed)", time_str);
-            } else if minutes == 0 {
-                println!("Time: {} (🔴 ARRIVING NOW!)", time_str);
-            } else if minutes <= 2 {
-                println!("Time: {} (🔴 {} min - approaching)", time_str, minutes);
-            } else if minutes <= 5 {
-                println!("Time: {} (🟡 {} min)", time_str, minutes);
-            } else if minutes <= 15 {
-                println!("Time: {} (🟢 {} min)", time_str, minutes);
+            let marker = match NVTControllers::minutes_until_arrival(ts, now) {
+                ..0 => "⚫ ",
+                0..=2 => "🔴 ",
+                3..=5 => "🟡 ",
+                6..=15 => "🟢 ",
+                _ => "",
+            };
+            let time_str = if USE_RELATIVE_TIME.load(Ordering::Relaxed) {
+                NVTModels::format_relative_time(ts, now)
             } else {
-                println!("Time: {} ({} min)", time_str, minutes);
-            }
+                NVTModels::format_timestamp(ts)
+            };
+            println!("     ⏰ Time: {}{}", marker, time_str);
         } else {
             println!("     ⏰ Time: Not available");
         }
 
+        if let Some(occupancy) = rt.occupancy {
+            println!("     👥 Occupancy: {}", NVTModels::occupancy_label(occupancy));
+        }
+        if let Some(load) = historical_load {
+            println!("     📊 Historical Load (this hour): {}", Self::format_load_bar(load));
+        }
+
+        // Late in the day, warn before the last vehicles leave
+        if let (Some(stop_id), Some(route_id)) = (rt.stop_id.as_deref(), rt.route_id.as_deref())
+            && (NVTModels::remaining_departures_today(stop_id, route_id, now, &network.gtfs) < 3
+                || NVTModels::is_last_service_soon(stop_id, route_id, 30, &network.gtfs))
+            && let Some(last) = NVTModels::get_last_service(stop_id, route_id, &network.gtfs)
+        {
+            println!("     ⏰ Last service today: {}", &NVTModels::format_timestamp(last)[..5]);
+        }
+
+        // Without a real-time delay, scheduled frequency is the most useful hint
+        if rt.delay.is_none() && let Some(headway) = Self::vehicle_headway(rt, network) {
+            println!("     🔁 Every ~{} min", (headway + 30) / 60);
+        }
+
+        if show_service_gap
+            && let (Some(stop_id), Some(route_id)) = (rt.stop_id.as_deref(), rt.route_id.as_deref())
+            && let Some((starts_at, gap)) = NVTModels::find_service_gaps(
+                stop_id, route_id, rt.direction_id.unwrap_or(0), now, &network.gtfs,
+            ).first()
+        {
+            println!("     ⚠️  Next service gap: {} min at {}",
+                     gap.as_secs() / 60, &NVTModels::format_timestamp(*starts_at)[..5]);
+        }
+
         // Show delay if available
         if let Some(delay) = rt.delay {
             let delay_str = NVTControllers::format_delay(delay);
@@ -281,7 +1206,9 @@ impl NVTViews {
         }
 
         // Show data source
-        if NVTControllers::is_scheduled(rt) {
+        if rt.vehicle_id == "static" {
+            println!("     📊 Source: Static timetable (no real-time data)");
+        } else if NVTModels::is_scheduled(rt) {
             println!("     📊 Source: Scheduled timetable");
         } else {
             println!("     📊 Source: Real-time GPS tracking");
@@ -291,6 +1218,71 @@ impl NVTViews {
             if rt.latitude != 0.0 && rt.longitude != 0.0 {
                 println!("     📍 Position: ({:.4}, {:.4})", rt.latitude, rt.longitude);
             }
+            if let Some(speed) = rt.speed_kmh {
+                println!("     🚀 {:.0} km/h", speed);
+            }
+        }
+    }
+
+    /// Show every stop of a vehicle's trip: departed stops dimmed, current stop highlighted
+    pub fn show_trip_details(journey: &[JourneyStop]) {
+        println!("\n{}", "═".repeat(70));
+        println!("🧭 TRIP DETAILS ({} stops)", journey.len());
+        println!("{}", "═".repeat(70));
+
+        for stop in journey {
+            let time_str = stop.arrival_time
+                .map(NVTModels::format_timestamp)
+                .unwrap_or_else(|| "--:--:--".to_string());
+            let delay_str = stop.delay
+                .filter(|d| d.abs() > 30)
+                .map(|d| format!(" ({})", NVTControllers::format_delay(d)))
+                .unwrap_or_default();
+            let marker = if stop.is_terminus { "◾" } else { "•" };
+
+            match stop.status {
+                StopVisitStatus::Departed => {
+                    println!("{}", Self::styled(
+                        format!("      {} {}  {}{} (departed)", marker, time_str, stop.stop_name, delay_str),
+                        "90"));
+                }
+                StopVisitStatus::Current => {
+                    println!("  ➜ {}", Self::styled(
+                        format!(" {} {}  {}{} ", marker, time_str, stop.stop_name, delay_str),
+                        "1;7"));
+                }
+                StopVisitStatus::Upcoming => {
+                    println!("{}", Self::styled(
+                        format!("    {} {}  {}{}", marker, time_str, stop.stop_name, delay_str),
+                        "97"));
+                }
+            }
+        }
+
+        println!("{}", "═".repeat(70));
+    }
+
+    /// Stops of a trip as a table of arrival times and delays (--trip)
+    pub fn show_trip_table(trip_id: &str, journey: &[JourneyStop]) {
+        println!("\n🧭 Trip {} ({} stops)", trip_id, journey.len());
+        println!("{:>4}  {:<30} {:<10} DELAY", "#", "STOP", "ARRIVAL");
+        println!("{}", "─".repeat(60));
+
+        for (i, stop) in journey.iter().enumerate() {
+            let time_str = stop.arrival_time
+                .map(NVTModels::format_timestamp)
+                .unwrap_or_else(|| "--:--:--".to_string());
+            let delay_str = stop.delay
+                .map(NVTControllers::format_delay)
+                .unwrap_or_else(|| "-".to_string());
+            let name: String = stop.stop_name.chars().take(30).collect();
+            let row = format!("{:>4}  {:<30} {:<10} {}", i + 1, name, time_str, delay_str);
+
+            match stop.status {
+                StopVisitStatus::Departed => println!("{}", Self::styled(row, "90")),
+                StopVisitStatus::Current => println!("{}", Self::styled(row, "1;7")),
+                StopVisitStatus::Upcoming => println!("{}", row),
+            }
         }
     }
 
@@ -337,8 +1329,8 @@ impl NVTViews {
             println!("{}", "─".repeat(70));
 
             for (idx, stop) in stops[start..end].iter().enumerate() {
-                println!("\n  {}. {} (ID: {})",
-                         start + idx + 1, stop.stop_name, stop.stop_id);
+                println!("\n  {}. {} {} (ID: {})",
+                         start + idx + 1, stop.stop_type.icon(), stop.stop_name, stop.stop_id);
                 println!("     📌 Location: ({:.6}, {:.6})",
                          stop.latitude, stop.longitude);
 
@@ -347,7 +1339,7 @@ impl NVTViews {
                         .filter_map(|line_ref| {
                             network.lines.iter()
                                 .find(|l| &l.line_ref == line_ref)
-                                .map(|l| Self::colorize_line(&l.line_code, &l.color))
+                                .map(|l| Self::format_line_badge(&l.line_code, &l.color))
                         })
                         .take(15)
                         .collect();
@@ -382,9 +1374,12 @@ impl NVTViews {
         // Group lines by type (Tram, Bus, etc.)
         let mut trams: Vec<&Line> = Vec::new();
         let mut buses: Vec<&Line> = Vec::new();
+        let mut night: Vec<&Line> = Vec::new();
 
         for line in lines {
-            if line.line_code.len() == 1 && line.line_code.chars().all(|c| c.is_alphabetic()) {
+            if line.is_night_service {
+                night.push(line);
+            } else if NVTModels::is_tram_line(line) {
                 trams.push(line);
             } else {
                 buses.push(line);
@@ -392,7 +1387,7 @@ impl NVTViews {
         }
 
         if !trams.is_empty() {
-            println!("\n🚊 TRAM/BRT LINES ({}):", trams.len());
+            println!("\n🚊 TRAM LINES ({}):", trams.len());
             println!("{}", "─".repeat(70));
             for line in trams {
                 Self::display_line_info(line);
@@ -410,18 +1405,29 @@ impl NVTViews {
             }
         }
 
+        if !night.is_empty() {
+            println!("\n🌙 NIGHT LINES ({}):", night.len());
+            println!("{}", "─".repeat(70));
+            for line in night {
+                Self::display_line_info(line);
+            }
+        }
+
         println!("\n{}", "═".repeat(70));
     }
 
     /// Display individual line information
     fn display_line_info(line: &Line) {
         println!("\n  {} {} - {}",
-                 Self::colorize_line(&line.line_code, &line.color),
+                 Self::format_line_badge(&line.line_code, &line.color),
                  line.line_name,
                  line.line_ref
         );
 
-        if !line.destinations.is_empty() {
+        if line.is_circular {
+            let places: Vec<&str> = line.destinations.iter().map(|(_, place)| place.as_str()).collect();
+            println!("    🔄 Circular route {}", places.join(" / "));
+        } else if !line.destinations.is_empty() {
             for (dir_ref, place_name) in &line.destinations {
                 let arrow = if dir_ref == "0" { "  →" } else { "  ←" };
                 println!("    {} {}", arrow, place_name);
@@ -470,6 +1476,108 @@ impl NVTViews {
         println!("{}", "─".repeat(60));
     }
 
+    pub fn line_required() {
+        println!("\n{}", "─".repeat(60));
+        println!("ℹ️  No line currently selected");
+        println!("   Use option 1 to select a line first");
+        println!("{}", "─".repeat(60));
+    }
+
+    /// Show the stops of a line in visit order for one direction
+    pub fn show_line_stop_sequence(line: &Line, direction_id: u32, stop_ids: &[String], network: &NetworkData) {
+        let destination = line.destinations.iter()
+            .find(|(dir_ref, _)| dir_ref == &direction_id.to_string())
+            .map(|(_, place)| place.as_str())
+            .unwrap_or("Unknown destination");
+
+        println!("\n{}", "═".repeat(60));
+        println!("🗺️  {} {} → {}",
+                 Self::format_line_badge(&line.line_code, &line.color),
+                 line.line_name,
+                 destination
+        );
+        println!("   Direction {} • {} stops", direction_id, stop_ids.len());
+        if let Some(shape) = NVTModels::get_line_shape(&line.line_ref, &network.gtfs) {
+            println!("   🛤️  Route path: {} points, ~{:.1} km", shape.len(), NVTModels::shape_length_m(shape) / 1000.0);
+        }
+        println!("{}", "═".repeat(60));
+
+        for (idx, stop_id) in stop_ids.iter().enumerate() {
+            let name = network.stops.iter()
+                .find(|s| &s.stop_id == stop_id)
+                .map(|s| Self::stop_display_name(s, network).to_string())
+                .or_else(|| {
+                    network.gtfs.stops.iter()
+                        .find(|(id, _, _, _)| &NVTModels::normalize_stop_id(id) == stop_id)
+                        .map(|(_, name, _, _)| name.clone())
+                })
+                .unwrap_or_else(|| stop_id.clone());
+
+            let marker = if idx == 0 || idx == stop_ids.len() - 1 { "◉" } else { "│" };
+            println!("  {} {:>2}. {} (ID: {})", marker, idx + 1, name, stop_id);
+        }
+        println!("{}", "─".repeat(60));
+    }
+
+    /// Arrivals as a JSON array for `--format json`
+    pub fn format_arrivals_json(vehicles: &[RealTimeInfo], network: &NetworkData) -> String {
+        serde_json::to_string_pretty(&Self::arrivals_json(vehicles, network)).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// One `--watch --format json` update on a single line: `{"updated_at": ..., "arrivals": [...]}`
+    pub fn format_watch_json(vehicles: &[RealTimeInfo], network: &NetworkData) -> String {
+        let updated_at = chrono::Utc::now().with_timezone(&chrono_tz::Europe::Paris).to_rfc3339();
+        serde_json::json!({
+            "updated_at": updated_at,
+            "arrivals": Self::arrivals_json(vehicles, network),
+        })
+        .to_string()
+    }
+
+    fn arrivals_json(vehicles: &[RealTimeInfo], network: &NetworkData) -> Vec<serde_json::Value> {
+        vehicles
+            .iter()
+            .map(|rt| {
+                let line_code = rt.route_id.as_deref()
+                    .and_then(|route_id| NVTModels::get_line_by_route_id(route_id, network))
+                    .map(|l| l.line_code.clone());
+                let arrival = rt.timestamp
+                    .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+                    .map(|dt| dt.with_timezone(&chrono_tz::Europe::Paris).to_rfc3339());
+                let source = if rt.vehicle_id == "static" {
+                    "static"
+                } else if NVTModels::is_scheduled(rt) {
+                    "scheduled"
+                } else {
+                    "realtime"
+                };
+
+                serde_json::json!({
+                    "line_code": line_code,
+                    "destination": rt.destination,
+                    "arrival_iso8601": arrival,
+                    "delay_seconds": rt.delay,
+                    "occupancy": rt.occupancy.and_then(NVTModels::occupancy_status_name),
+                    "source": source,
+                })
+            })
+            .collect()
+    }
+
+    /// Error object written to stderr in `--format json` mode
+    pub fn json_error(message: &str) {
+        eprintln!("{}", serde_json::json!({ "error": message }));
+    }
+
+    pub fn no_arrivals_shown() {
+        println!("\n{}", "─".repeat(60));
+        println!("✗ No vehicles shown yet");
+        println!("\n💡 Please display arrivals first:");
+        println!("  • Use option 2 to select a stop");
+        println!("  • Then option 3 to show next vehicles");
+        println!("{}", "─".repeat(60));
+    }
+
     pub fn no_stop_selected() {
         println!("\n{}", "─".repeat(60));
         println!("✗ No stop selected");
@@ -503,6 +1611,11 @@ impl NVTViews {
         println!("❌ NETWORK ERROR");
         println!("{}", "═".repeat(60));
         println!("\n{}", error);
+        if error.contains(NVTModels::INVALID_API_KEY_MSG) {
+            println!("\n💡 Check your API key in {}", NVTConfig::config_path().display());
+            println!("\n{}", "═".repeat(60));
+            return;
+        }
         println!("\n💡 Troubleshooting:");
         println!("  • Check your internet connection");
         println!("  • The TBM API might be temporarily unavailable");
@@ -511,6 +1624,16 @@ impl NVTViews {
         println!("\n{}", "═".repeat(60));
     }
 
+    /// Export success message listing the written files
+    pub fn show_export_complete(paths: &[PathBuf]) {
+        println!("\n{}", "─".repeat(60));
+        println!("✓ Export complete");
+        for path in paths {
+            println!("  📄 {}", path.display());
+        }
+        println!("{}", "─".repeat(60));
+    }
+
     /// Loading indicator
     pub fn show_loading(message: &str) {
         print!("\r🔄 {}...", message);
@@ -535,15 +1658,26 @@ impl NVTViews {
         println!();
     }
 
+    /// Line code on its line color, or `[CODE]` when colors are disabled
+    pub fn format_line_badge(code: &str, hex_color: &str) -> String {
+        if Self::use_color() {
+            Self::colorize_line(code, hex_color)
+        } else {
+            Self::colorize_line_plain(code)
+        }
+    }
+
+    /// Line badge without ANSI escape codes
+    pub fn colorize_line_plain(code: &str) -> String {
+        format!("[{}]", code)
+    }
+
     /// Colorize line code with ANSI colors (improved contrast)
     fn colorize_line(code: &str, hex_color: &str) -> String {
         let (r, g, b) = NVTModels::parse_hex_color(hex_color);
 
-        // Calculate relative luminance for contrast
-        let luminance = (0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32) / 255.0;
-
-        // Use white text on dark backgrounds, black on light backgrounds
-        let text_color = if luminance > 0.5 { "30" } else { "97" };
+        // Black or white text, whichever contrasts more with the line color (WCAG 2.1)
+        let text_color = if NVTModels::line_text_color(hex_color) == "000000" { "30" } else { "97" };
 
         // Format with background color and contrasting text
         format!(
@@ -553,7 +1687,14 @@ impl NVTViews {
     }
 
     /// Display a progress bar for long operations
+    /// Redraw a progress bar in place; with `total` 0 (unknown) only the count is shown
     pub fn show_progress(current: usize, total: usize, label: &str) {
+        if total == 0 {
+            print!("\r{}: {} KB", label, current / 1024);
+            io::stdout().flush().unwrap();
+            return;
+        }
+
         let percentage = (current as f32 / total as f32 * 100.0) as usize;
         let bar_length = 40;
         let filled = (bar_length * current) / total;
@@ -566,4 +1707,195 @@ impl NVTViews {
             println!();
         }
     }
-}
\ No newline at end of file
+
+    // ========================================================================
+    // Terminal UI (--tui)
+    // ========================================================================
+
+    /// Draw the full TUI: navigation list, arrivals table, status bar and search overlay
+    pub fn render_tui(
+        frame: &mut Frame,
+        state: &mut TuiState,
+        items: &[TuiItem],
+        cache: &CachedNetworkData,
+        network: &NetworkData,
+    ) {
+        let [main, status] = Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.size());
+        let [left, right] = Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)]).areas(main);
+
+        Self::render_tui_list(frame, left, state, items, network);
+        Self::render_tui_arrivals(frame, right, state, network);
+        Self::render_tui_status(frame, status, state, cache);
+
+        if let Some(input) = &state.search {
+            Self::render_tui_search(frame, main, input);
+        }
+    }
+
+    fn tui_line_style(line: &Line) -> Style {
+        let (r, g, b) = NVTModels::parse_hex_color(&line.color);
+        Style::default().fg(Color::Rgb(r, g, b)).add_modifier(Modifier::BOLD)
+    }
+
+    fn render_tui_list(frame: &mut Frame, area: Rect, state: &mut TuiState, items: &[TuiItem], network: &NetworkData) {
+        let title = match (&state.search_query, &state.line_filter) {
+            (Some(query), _) => format!(" Stops matching \"{}\" ", query),
+            (None, Some(line_ref)) => network.lines.iter()
+                .find(|l| &l.line_ref == line_ref)
+                .map(|l| {
+                    // Direction 0 runs from terminus_a to terminus_b
+                    let towards = if state.direction == 0 { &l.terminus_b } else { &l.terminus_a };
+                    match towards {
+                        Some(terminus) => format!(" Stops of {} {} → {} ", l.line_code, l.line_name, terminus),
+                        None => format!(" Stops of {} {} ", l.line_code, l.line_name),
+                    }
+                })
+                .unwrap_or_else(|| " Stops ".to_string()),
+            (None, None) => " Lines ".to_string(),
+        };
+
+        let list_items: Vec<ListItem> = items
+            .iter()
+            .map(|item| match item {
+                TuiItem::Line(line_ref) => match network.lines.iter().find(|l| &l.line_ref == line_ref) {
+                    Some(line) => ListItem::new(TextLine::from(vec![
+                        Span::styled(format!("{:<5}", line.line_code), Self::tui_line_style(line)),
+                        Span::raw(line.line_name.clone()),
+                    ])),
+                    None => ListItem::new(line_ref.clone()),
+                },
+                TuiItem::Stop(stop_id) => {
+                    let name = network.stops.iter()
+                        .find(|s| &s.stop_id == stop_id)
+                        .map(|s| Self::stop_display_name(s, network).to_string())
+                        .unwrap_or_else(|| stop_id.clone());
+                    let marker = if state.selected_stop.as_ref() == Some(stop_id) { "● " } else { "  " };
+                    ListItem::new(format!("{}{}", marker, name))
+                }
+            })
+            .collect();
+
+        let list = List::new(list_items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+
+        frame.render_stateful_widget(list, area, &mut state.list);
+    }
+
+    fn render_tui_arrivals(frame: &mut Frame, area: Rect, state: &TuiState, network: &NetworkData) {
+        let Some(stop) = state.selected_stop.as_ref()
+            .and_then(|id| NVTModels::get_stop_by_id(id, network)) else {
+            let hint = Paragraph::new("Select a stop with Enter to see its next arrivals")
+                .block(Block::default().borders(Borders::ALL).title(" Arrivals "));
+            frame.render_widget(hint, area);
+            return;
+        };
+
+        let line_id = state.line_filter.as_deref().and_then(NVTModels::extract_line_id);
+        let now = chrono::Utc::now().timestamp();
+
+        let rows: Vec<Row> = NVTModels::get_next_vehicles_for_stop(&stop.stop_id, network)
+            .into_iter()
+            .filter(|rt| line_id.is_none() || rt.route_id.as_deref() == line_id)
+            .map(|rt| {
+                let line = rt.route_id.as_deref()
+                    .and_then(|route_id| NVTModels::get_line_by_route_id(route_id, network));
+                let code = match line {
+                    Some(line) => Span::styled(line.line_code.clone(), Self::tui_line_style(line)),
+                    None => Span::raw("?"),
+                };
+                let (time, minutes) = match rt.timestamp {
+                    Some(ts) => (
+                        NVTModels::format_timestamp(ts),
+                        format!("{} min", NVTControllers::minutes_until_arrival(ts, now).max(0)),
+                    ),
+                    None => ("--:--".to_string(), String::new()),
+                };
+                let delay = rt.delay.map(NVTControllers::format_delay).unwrap_or_default();
+                let source = if rt.vehicle_id == "static" {
+                    "Timetable"
+                } else if NVTModels::is_scheduled(rt) {
+                    "Scheduled"
+                } else {
+                    "GPS"
+                };
+
+                Row::new(vec![
+                    TextLine::from(code),
+                    TextLine::from(rt.destination.clone().unwrap_or_default()),
+                    TextLine::from(time),
+                    TextLine::from(minutes),
+                    TextLine::from(delay),
+                    TextLine::from(source),
+                ])
+            })
+            .collect();
+
+        let widths = [
+            Constraint::Length(6),
+            Constraint::Min(16),
+            Constraint::Length(6),
+            Constraint::Length(8),
+            Constraint::Length(14),
+            Constraint::Length(10),
+        ];
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(vec!["Line", "Destination", "Time", "In", "Delay", "Source"])
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Arrivals at {} ", Self::stop_display_name(stop, network))),
+            );
+
+        frame.render_widget(table, area);
+    }
+
+    fn render_tui_status(frame: &mut Frame, area: Rect, state: &TuiState, cache: &CachedNetworkData) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let mut text = format!(
+            " Static data: {}s old | Real-time: {}s old | Refresh in {}s | j/k move  Enter select  d direction  Esc back  / search  r refresh  q quit",
+            now.saturating_sub(cache.last_static_update),
+            now.saturating_sub(cache.last_dynamic_update),
+            state.next_refresh_secs,
+        );
+        if let Some(message) = &state.message {
+            text = format!(" {} |{}", message, text);
+        }
+
+        let status = Paragraph::new(text).style(Style::default().bg(Color::DarkGray).fg(Color::White));
+        frame.render_widget(status, area);
+    }
+
+    fn render_tui_search(frame: &mut Frame, area: Rect, input: &Input) {
+        let width = area.width.saturating_sub(4).min(60);
+        let popup = Rect {
+            x: area.x + (area.width.saturating_sub(width)) / 2,
+            y: area.y + area.height / 3,
+            width,
+            height: 3,
+        };
+
+        let scroll = input.visual_scroll(width.saturating_sub(2) as usize);
+        let field = Paragraph::new(input.value())
+            .scroll((0, scroll as u16))
+            .block(Block::default().borders(Borders::ALL).title(" Search stops (Enter to apply, Esc to cancel) "));
+
+        frame.render_widget(Clear, popup);
+        frame.render_widget(field, popup);
+        frame.set_cursor(
+            popup.x + 1 + (input.visual_cursor().saturating_sub(scroll)) as u16,
+            popup.y + 1,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests;
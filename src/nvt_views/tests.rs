@@ -0,0 +1,270 @@
+use super::*;
+use crate::nvt_models::{LineMetadata, StopMetadata};
+use chrono::TimeZone;
+use std::sync::Mutex;
+
+// ----------------------------------------------------------------------------
+// Fixtures
+// ----------------------------------------------------------------------------
+
+/// Serializes the tests that depend on the process-wide color setting
+static COLOR_SETTING: Mutex<()> = Mutex::new(());
+
+/// Run `f` with ANSI colors enabled or disabled
+fn with_color<T>(enabled: bool, f: impl FnOnce() -> T) -> T {
+    let _guard = COLOR_SETTING.lock().unwrap_or_else(|e| e.into_inner());
+    let previous = NVTViews::use_color();
+    NVTViews::set_color(enabled);
+    let result = f();
+    NVTViews::set_color(previous);
+    result
+}
+
+/// Quinconces, served by trams B and C
+fn quinconces_network() -> NetworkData {
+    let (b, c) = ("TBM:Line:B:LOC".to_string(), "TBM:Line:C:LOC".to_string());
+    let stops: Vec<StopMetadata> = vec![("quinc".to_string(), "Quinconces".to_string(), 44.8447, -0.5737, vec![b.clone(), c.clone()])];
+    let destinations = |outbound: &str, inbound: &str| vec![("0".to_string(), outbound.to_string()), ("1".to_string(), inbound.to_string())];
+    let lines: Vec<LineMetadata> = vec![
+        (b, "Tram B".to_string(), "B".to_string(), destinations("Pessac Centre", "Berges de la Garonne")),
+        (c, "Tram C".to_string(), "C".to_string(), destinations("Villenave Pyrénées", "Parc des Expositions")),
+    ];
+    let (stop_to_lines, line_to_stops) = NVTModels::build_line_indices(&stops);
+    NVTModels::build_network_data(
+        stops,
+        lines,
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        HashMap::new(),
+        HashMap::new(),
+        &stop_to_lines,
+        &line_to_stops,
+        &NVTConfig::default(),
+    )
+}
+
+/// Unix time of 08:15 Paris time, far enough ahead that nothing is filtered out
+fn quarter_past_eight() -> i64 {
+    chrono_tz::Europe::Paris.with_ymd_and_hms(2030, 3, 12, 8, 15, 0).unwrap().timestamp()
+}
+
+/// Predicted arrival of `route_id` in `direction_id` at `timestamp`, `delay` seconds late
+fn arrival(route_id: &str, direction_id: u32, timestamp: i64, delay: Option<i32>) -> RealTimeInfo {
+    RealTimeInfo {
+        vehicle_id: "scheduled".to_string(),
+        trip_id: format!("{}-{}", route_id, timestamp),
+        route_id: Some(route_id.to_string()),
+        direction_id: Some(direction_id),
+        destination: None,
+        latitude: 44.8447,
+        longitude: -0.5737,
+        stop_id: Some("quinc".to_string()),
+        timestamp: Some(timestamp),
+        delay,
+        occupancy: None,
+        cancelled: false,
+        speed_kmh: None,
+    }
+}
+
+/// Text of `s` without ANSI escape sequences
+fn strip_ansi(s: &str) -> String {
+    let mut plain = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|c| *c == 'm');
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+// ----------------------------------------------------------------------------
+// Colors
+// ----------------------------------------------------------------------------
+
+#[test]
+fn no_color_badge_has_no_escape_codes() {
+    let badge = with_color(false, || NVTViews::format_line_badge("A", "E2001A"));
+    assert_eq!(badge, "[A]");
+    assert!(!badge.contains('\x1b'));
+}
+
+#[test]
+fn color_badge_uses_24_bit_background() {
+    let badge = with_color(true, || NVTViews::format_line_badge("A", "E2001A"));
+    assert!(badge.starts_with("\x1b[48;2;226;0;26m"), "{:?}", badge);
+    assert!(badge.contains(" A "));
+    assert!(badge.ends_with("\x1b[0m"));
+}
+
+#[test]
+fn no_color_styled_text_is_unchanged() {
+    let text = with_color(false, || NVTViews::styled("On time".to_string(), "32"));
+    assert_eq!(text, "On time");
+
+    let text = with_color(true, || NVTViews::styled("On time".to_string(), "32"));
+    assert_eq!(text, "\x1b[32mOn time\x1b[0m");
+}
+
+// ----------------------------------------------------------------------------
+// Departure board
+// ----------------------------------------------------------------------------
+
+fn board(vehicles: &[RealTimeInfo], color: bool) -> Vec<String> {
+    let network = quinconces_network();
+    let stop = network.stops[0].clone();
+    let vehicles: Vec<&RealTimeInfo> = vehicles.iter().collect();
+    with_color(color, || NVTViews::format_departure_board(&stop, &vehicles, &network))
+}
+
+#[test]
+fn departure_board_column_widths() {
+    let at = quarter_past_eight();
+    let rows = board(&[arrival("C", 0, at, Some(0)), arrival("B", 1, at + 300, Some(240))], false);
+
+    assert_eq!(rows, vec![
+        "🚏 Quinconces",
+        "┌──────────────────────────────────────────────────────────────┐",
+        "│ LINE     │ DESTINATION          │ SCHED    │ STATUS          │",
+        "├──────────────────────────────────────────────────────────────┤",
+        "│ C        │ Villenave Pyrénées   │ 08:15:00 │ On time         │",
+        "│ B        │ Berges de la Garonne │ 08:16:00 │ +4 min          │",
+        "└──────────────────────────────────────────────────────────────┘",
+    ]);
+    // Column separators line up on every row: 8, 20, 8 and 15 characters plus padding
+    for row in &rows[1..] {
+        assert_eq!(row.chars().count(), 64, "{}", row);
+    }
+    for row in [&rows[2], &rows[4], &rows[5]] {
+        let separators: Vec<usize> = row.chars().enumerate().filter(|(_, c)| *c == '│').map(|(i, _)| i).collect();
+        assert_eq!(separators, vec![0, 11, 34, 45, 63], "{}", row);
+    }
+}
+
+#[test]
+fn departure_board_truncates_long_destinations() {
+    let mut long = arrival("B", 0, quarter_past_eight(), None);
+    long.destination = Some("Pessac Centre via Gare de Pessac Alouette".to_string());
+    let rows = board(&[long], false);
+
+    assert_eq!(rows[4], "│ B        │ Pessac Centre via G… │ 08:15:00 │ Scheduled       │");
+}
+
+#[test]
+fn departure_board_status_colors_keep_columns() {
+    let at = quarter_past_eight();
+    let mut cancelled = arrival("C", 0, at + 900, None);
+    cancelled.cancelled = true;
+    let vehicles = [
+        arrival("C", 0, at, Some(20)),
+        arrival("C", 0, at + 300, Some(120)),
+        arrival("B", 0, at + 600, Some(180)),
+        cancelled,
+    ];
+    let rows = board(&vehicles, true);
+
+    assert!(rows[4].contains("\x1b[32mOn time"), "{:?}", rows[4]);
+    assert!(rows[5].contains("\x1b[33m+2 min"), "{:?}", rows[5]);
+    assert!(rows[6].contains("\x1b[31m+3 min"), "{:?}", rows[6]);
+    assert!(rows[7].contains("\x1b[31mCancelled"), "{:?}", rows[7]);
+    for row in &rows[4..8] {
+        assert_eq!(strip_ansi(row).chars().count(), 64, "{:?}", row);
+    }
+}
+
+#[test]
+fn departure_board_without_departures() {
+    let rows = board(&[], false);
+    assert_eq!(rows[4], format!("│ {:<60} │", "No departures"));
+    assert_eq!(rows.len(), 6);
+}
+
+#[test]
+fn no_color_departure_board_has_no_escape_codes() {
+    let at = quarter_past_eight();
+    let rows = board(&[arrival("C", 0, at, Some(0)), arrival("B", 1, at + 300, Some(240))], false);
+    assert!(rows.iter().all(|row| !row.contains('\x1b')));
+}
+
+// ----------------------------------------------------------------------------
+// ASCII stop map
+// ----------------------------------------------------------------------------
+
+const MAP_CENTER: (f64, f64) = (44.84, -0.58);
+
+/// Stops at (lat, lon) offsets from MAP_CENTER, the ones with `true` served by tram A
+fn map_network(offsets: &[(&str, f64, f64, bool)]) -> NetworkData {
+    let a = "TBM:Line:A:LOC".to_string();
+    let stops: Vec<StopMetadata> = offsets
+        .iter()
+        .map(|&(id, d_lat, d_lon, on_a)| {
+            let lines = if on_a { vec![a.clone()] } else { Vec::new() };
+            (id.to_string(), id.to_string(), MAP_CENTER.0 + d_lat, MAP_CENTER.1 + d_lon, lines)
+        })
+        .collect();
+    let lines: Vec<LineMetadata> = vec![(a.clone(), "Tram A".to_string(), "A".to_string(), Vec::new())];
+    let (stop_to_lines, line_to_stops) = NVTModels::build_line_indices(&stops);
+    NVTModels::build_network_data(
+        stops,
+        lines,
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        HashMap::new(),
+        HashMap::new(),
+        &stop_to_lines,
+        &line_to_stops,
+        &NVTConfig::default(),
+    )
+}
+
+fn map_of(network: &NetworkData) -> String {
+    let stops: Vec<&Stop> = network.stops.iter().collect();
+    NVTViews::stop_map_ascii(&stops, MAP_CENTER.0, MAP_CENTER.1, 1.0, network)
+}
+
+/// Bordered 60×20 map with `marks` at (row, col), as `stop_map_ascii` draws it
+fn expected_map(marks: &[(usize, usize, char)]) -> String {
+    let mut grid = vec![vec![' '; 60]; 20];
+    for &(row, col, mark) in marks {
+        grid[row][col] = mark;
+    }
+    let mut map = format!("┌{}┐\n", "─".repeat(60));
+    for row in grid {
+        map.push('│');
+        map.extend(row);
+        map.push_str("│\n");
+    }
+    map.push_str(&format!("└{}┘", "─".repeat(60)));
+    map
+}
+
+#[test]
+fn stop_map_with_three_stops() {
+    // In a 1 km box a row is ~95 m of latitude and a column ~43 m of longitude;
+    // the third stop is 2 km north, outside the box
+    let network = map_network(&[
+        ("north-west", 0.006, -0.008, true),
+        ("south-east", -0.004, 0.006, false),
+        ("far-north", 0.02, 0.0, true),
+    ]);
+    assert_eq!(map_of(&network), expected_map(&[(3, 11, 'A'), (14, 43, '·'), (10, 30, '★')]));
+}
+
+#[test]
+fn stop_map_marks_shared_cells_with_plus() {
+    let network = map_network(&[("platform-1", -0.004, 0.006, true), ("platform-2", -0.0041, 0.0059, true)]);
+    assert_eq!(map_of(&network), expected_map(&[(14, 43, '+'), (10, 30, '★')]));
+}
+
+#[test]
+fn stop_map_centre_overrides_a_stop() {
+    let network = map_network(&[("here", 0.0, 0.0, true)]);
+    let map = map_of(&network);
+    assert!(map.contains('★'));
+    assert!(!map.contains('A'));
+}
@@ -0,0 +1,117 @@
+// Exit codes of the non-interactive `--cli --stop` mode, run as a subprocess
+// against a local stand-in for the TBM API
+
+use axum::Router;
+use axum::routing::get;
+use serde_json::json;
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// SIRI-Lite discovery responses for Quinconces, served by trams B and C
+fn stop_points() -> serde_json::Value {
+    json!({ "Siri": { "StopPointsDelivery": { "AnnotatedStopPointRef": [{
+        "StopPointRef": { "value": "TBM:StopPoint:QUINC:LOC" },
+        "StopName": { "value": "Quinconces" },
+        "Location": { "latitude": 44.8447, "longitude": -0.5737 },
+        "Lines": [{ "value": "TBM:Line:B:LOC" }, { "value": "TBM:Line:C:LOC" }],
+    }] } } })
+}
+
+fn lines() -> serde_json::Value {
+    let line = |code: &str| json!({
+        "LineRef": { "value": format!("TBM:Line:{}:LOC", code) },
+        "LineName": [{ "value": format!("Tram {}", code) }],
+        "LineCode": { "value": code },
+        "Destinations": [],
+    });
+    json!({ "Siri": { "LinesDelivery": { "AnnotatedLineRef": [line("B"), line("C")] } } })
+}
+
+/// Serve the discovery endpoints on a free local port; the real-time feeds answer 404
+async fn mock_api() -> String {
+    let router = Router::new()
+        .route("/siri/2.0/bordeaux/stoppoints-discovery.json", get(|| async { axum::Json(stop_points()) }))
+        .route("/siri/2.0/bordeaux/lines-discovery.json", get(|| async { axum::Json(lines()) }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await.ok() });
+    format!("http://{}", addr)
+}
+
+/// Run nvt with `args` against the API at `api_base_url`, with a config, cache and
+/// home directory of its own so no state is shared with the user's
+fn run_nvt(api_base_url: &str, args: &[&str]) -> Output {
+    let home = tempfile::tempdir().unwrap();
+    let config = home.path().join("config.toml");
+    std::fs::write(
+        &config,
+        format!("api_base_url = \"{}\"\nmax_retry_attempts = 1\nrequest_timeout_secs = 5\n", api_base_url),
+    )
+    .unwrap();
+
+    nvt_command(home.path())
+        .arg("--config")
+        .arg(&config)
+        .args(args)
+        .output()
+        .expect("nvt ran")
+}
+
+fn nvt_command(home: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_NVT"));
+    command
+        .current_dir(home)
+        .env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .env("XDG_CACHE_HOME", home.join("cache"))
+        .env("XDG_DATA_HOME", home.join("data"))
+        // The GTFS download fails right away instead of waiting on the network
+        .env("HTTPS_PROXY", "http://127.0.0.1:9")
+        .env_remove("https_proxy")
+        .env_remove("HTTP_PROXY")
+        .env_remove("http_proxy")
+        .env_remove("ALL_PROXY")
+        .env_remove("all_proxy");
+    command
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn known_stop_exits_with_0() {
+    let api = mock_api().await;
+
+    let output = run_nvt(&api, &["--cli", "--stop", "Quinconces"]);
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Quinconces"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn json_departures_exit_with_0() {
+    let api = mock_api().await;
+
+    let output = run_nvt(&api, &["--cli", "--stop", "quinc", "--line", "C", "--format", "json"]);
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    let arrivals: serde_json::Value = serde_json::from_slice(&output.stdout).expect("JSON on stdout");
+    assert!(arrivals.is_array());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unknown_stop_or_line_exits_with_2() {
+    let api = mock_api().await;
+
+    let output = run_nvt(&api, &["--cli", "--stop", "Place de la Bourse"]);
+    assert_eq!(output.status.code(), Some(2), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = run_nvt(&api, &["--cli", "--stop", "quinc", "--line", "Z"]);
+    assert_eq!(output.status.code(), Some(2), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn unreachable_api_exits_with_1() {
+    let output = run_nvt("http://127.0.0.1:9", &["--cli", "--stop", "quinc"]);
+    assert_eq!(output.status.code(), Some(1), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = run_nvt("http://127.0.0.1:9", &["--stop", "quinc", "--format", "json"]);
+    assert_eq!(output.status.code(), Some(1));
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).expect("JSON error on stderr");
+    assert!(error["error"].is_string());
+}
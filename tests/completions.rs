@@ -0,0 +1,50 @@
+// Completion scripts printed by `--generate-completions`, run as a subprocess
+
+use std::path::Path;
+use std::process::Command;
+
+/// Completion script for `shell`, with `home` as the home and cache directory
+fn completions(shell: &str, home: &Path) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_NVT"))
+        .env("HOME", home)
+        .env("XDG_CACHE_HOME", home.join("cache"))
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .args(["--generate-completions", shell])
+        .output()
+        .expect("nvt ran");
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn bash_completions_define_the_nvt_function() {
+    let home = tempfile::tempdir().unwrap();
+    let script = completions("bash", home.path());
+    assert!(script.starts_with("_nvt()"), "{}", &script[..script.len().min(200)]);
+    assert!(script.contains("--stop"));
+    assert!(script.contains("--generate-completions"));
+}
+
+#[test]
+fn zsh_and_fish_completions() {
+    let home = tempfile::tempdir().unwrap();
+    assert!(completions("zsh", home.path()).starts_with("#compdef nvt"));
+    assert!(completions("fish", home.path()).contains("complete -c nvt"));
+}
+
+#[test]
+fn cached_stop_ids_and_line_codes_are_offered() {
+    let home = tempfile::tempdir().unwrap();
+    let cache_dir = home.path().join("cache").join("tbm_nvt");
+    std::fs::create_dir_all(&cache_dir).unwrap();
+    std::fs::write(
+        cache_dir.join("gtfs_cache.json"),
+        r#"{"stops": [["QUINC", "Quinconces", 44.8447, -0.5737], ["STJEAN", "Gare Saint-Jean", 44.826, -0.5566]],
+            "route_short_names": {"TBC:C": "C", "TBC:L12": "L12"}}"#,
+    )
+    .unwrap();
+
+    let script = completions("bash", home.path());
+    assert!(script.contains("quinc stjean"), "stop ids missing");
+    assert!(script.contains("C L12"), "line codes missing");
+}